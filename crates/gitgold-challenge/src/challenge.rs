@@ -1,6 +1,6 @@
 use gitgold_core::config::GitGoldConfig;
 use gitgold_core::error::ChallengeError;
-use rand::Rng;
+use rand::{CryptoRng, Rng, RngCore};
 use serde::{Deserialize, Serialize};
 
 /// A proof-of-availability challenge issued to a storage node.
@@ -32,6 +32,27 @@ impl Challenge {
         share_id: u32,
         fragment_size: usize,
         config: &GitGoldConfig,
+    ) -> Result<Self, ChallengeError> {
+        Self::generate_with_rng(
+            repo_hash,
+            fragment_id,
+            share_id,
+            fragment_size,
+            config,
+            &mut rand::thread_rng(),
+        )
+    }
+
+    /// Generate a challenge drawing the id, byte range and nonce from `rng`.
+    ///
+    /// Everything except `issued_at` is then reproducible from the rng seed.
+    pub fn generate_with_rng<R: RngCore + CryptoRng>(
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        fragment_size: usize,
+        config: &GitGoldConfig,
+        rng: &mut R,
     ) -> Result<Self, ChallengeError> {
         let min_range = config.challenge_min_bytes;
         let max_range = config.challenge_max_bytes.min(fragment_size);
@@ -44,8 +65,6 @@ impl Challenge {
             });
         }
 
-        let mut rng = rand::thread_rng();
-
         // Random range size between min and max
         let range_size = rng.gen_range(min_range..=max_range);
         let max_start = fragment_size - range_size;
@@ -59,13 +78,17 @@ impl Challenge {
         let mut nonce = [0u8; 32];
         rng.fill(&mut nonce);
 
+        let mut id_bytes = [0u8; 16];
+        rng.fill(&mut id_bytes);
+        let id = uuid::Builder::from_random_bytes(id_bytes).into_uuid();
+
         let now = std::time::SystemTime::now()
             .duration_since(std::time::UNIX_EPOCH)
             .unwrap()
             .as_secs() as i64;
 
         Ok(Self {
-            id: uuid::Uuid::new_v4().to_string(),
+            id: id.to_string(),
             repo_hash: repo_hash.to_string(),
             fragment_id,
             share_id,
@@ -118,4 +141,20 @@ mod tests {
         let c2 = Challenge::generate("repo", 0, 1, 100_000, &config).unwrap();
         assert_ne!(c1.nonce, c2.nonce);
     }
+
+    #[test]
+    fn test_generate_with_seeded_rng() {
+        use rand::SeedableRng;
+        let config = GitGoldConfig::default();
+        let generate = |seed| {
+            let mut rng = rand_chacha::ChaCha20Rng::from_seed(seed);
+            Challenge::generate_with_rng("repo", 0, 1, 100_000, &config, &mut rng).unwrap()
+        };
+        let (c1, c2, c3) = (generate([1; 32]), generate([1; 32]), generate([2; 32]));
+        assert_eq!(c1.id, c2.id);
+        assert_eq!(c1.byte_range, c2.byte_range);
+        assert_eq!(c1.nonce, c2.nonce);
+        assert_ne!(c1.nonce, c3.nonce);
+        assert_eq!(uuid::Uuid::parse_str(&c1.id).unwrap().get_version_num(), 4);
+    }
 }
@@ -1,4 +1,6 @@
+use gitgold_core::error::StorageError;
 use gitgold_crypto::hash::sha256_pair;
+use gitgold_storage::backend::FragmentBackend;
 use serde::{Deserialize, Serialize};
 
 use crate::challenge::Challenge;
@@ -32,8 +34,21 @@ impl ChallengeProof {
         F: FnOnce(&[u8]) -> String,
     {
         let (start, end) = challenge.byte_range;
-        let range_data = &fragment_data[start..end];
+        Self::create_from_range(challenge, &fragment_data[start..end], response_time_ms, sign_fn)
+    }
 
+    /// Create a proof from the challenged byte range alone.
+    ///
+    /// `range_data` must be exactly `fragment_data[byte_range]`.
+    pub fn create_from_range<F>(
+        challenge: &Challenge,
+        range_data: &[u8],
+        response_time_ms: u64,
+        sign_fn: F,
+    ) -> Self
+    where
+        F: FnOnce(&[u8]) -> String,
+    {
         // hash = SHA-256(data[range] || nonce)
         let hash = sha256_pair(range_data, &challenge.nonce);
 
@@ -49,6 +64,28 @@ impl ChallengeProof {
             signature,
         }
     }
+
+    /// Create a proof for a fragment held in `backend`, reading only the
+    /// challenged byte range.
+    pub fn create_from_backend<B, F>(
+        challenge: &Challenge,
+        backend: &B,
+        response_time_ms: u64,
+        sign_fn: F,
+    ) -> Result<Self, StorageError>
+    where
+        B: FragmentBackend + ?Sized,
+        F: FnOnce(&[u8]) -> String,
+    {
+        let (start, end) = challenge.byte_range;
+        let range_data = backend.read_fragment_range(
+            &challenge.repo_hash,
+            challenge.fragment_id,
+            challenge.share_id,
+            start..end,
+        )?;
+        Ok(Self::create_from_range(challenge, &range_data, response_time_ms, sign_fn))
+    }
 }
 
 #[cfg(test)]
@@ -86,4 +123,27 @@ mod tests {
         // Same challenge + same data = same hash
         assert_eq!(p1.hash, p2.hash);
     }
+
+    #[test]
+    fn test_proof_from_backend_range() {
+        use gitgold_storage::memory::MemoryFragmentStore;
+
+        let config = GitGoldConfig::default();
+        let fragment_data: Vec<u8> = (0..100_000u32).map(|i| (i % 253) as u8).collect();
+        let store = MemoryFragmentStore::new();
+        store.store_fragment("repo", 3, 2, &fragment_data).unwrap();
+        let challenge =
+            Challenge::generate("repo", 3, 2, fragment_data.len(), &config).unwrap();
+
+        let from_store =
+            ChallengeProof::create_from_backend(&challenge, &store, 100, |_| "sig".to_string())
+                .unwrap();
+        let from_data =
+            ChallengeProof::create(&challenge, &fragment_data, 100, |_| "sig".to_string());
+        assert_eq!(from_store.hash, from_data.hash);
+
+        let missing = Challenge::generate("repo", 4, 2, fragment_data.len(), &config).unwrap();
+        assert!(ChallengeProof::create_from_backend(&missing, &store, 100, |_| String::new())
+            .is_err());
+    }
 }
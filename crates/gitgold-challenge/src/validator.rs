@@ -1,8 +1,11 @@
 use gitgold_core::config::GitGoldConfig;
 use gitgold_core::error::ChallengeError;
-use gitgold_core::types::MicroGitGold;
+use gitgold_core::types::{Address, MicroGitGold};
 use gitgold_crypto::hash::sha256_pair;
 use gitgold_crypto::keys::PublicKey;
+use gitgold_crypto::share::Share;
+use gitgold_crypto::vss::ShareCommitments;
+use gitgold_storage::backend::ChallengeRecord;
 
 use crate::challenge::Challenge;
 use crate::proof::ChallengeProof;
@@ -104,6 +107,55 @@ pub fn validate_challenge_response(
     })
 }
 
+/// Audit log entry for a validated challenge, for
+/// `FragmentBackend::record_challenge`.
+///
+/// The entry is timestamped when the response arrived: `issued_at` plus the
+/// response time, in whole seconds.
+pub fn audit_record(
+    challenge: &Challenge,
+    proof: &ChallengeProof,
+    result: &ValidationResult,
+    challenger: &Address,
+    node: &Address,
+) -> ChallengeRecord {
+    ChallengeRecord {
+        challenge_id: challenge.id.clone(),
+        repo_hash: challenge.repo_hash.clone(),
+        fragment_id: challenge.fragment_id,
+        share_id: challenge.share_id,
+        challenger: challenger.clone(),
+        node: node.clone(),
+        nonce: challenge.nonce,
+        success: result.valid,
+        failure_reason: result.reason.clone(),
+        response_time_ms: proof.response_time_ms,
+        challenged_at: challenge.issued_at + (proof.response_time_ms / 1000) as i64,
+    }
+}
+
+/// Check that a fragment returned by a node is a valid share for
+/// `commitments`, before it is used for reconstruction or rewarded.
+///
+/// `fragment_data` is the serialized share envelope as stored by the node.
+/// Returns the parsed share on success.
+pub fn validate_fragment_share(
+    fragment_data: &[u8],
+    commitments: &ShareCommitments,
+) -> Result<Share, ChallengeError> {
+    let share = Share::from_bytes(fragment_data).map_err(|e| ChallengeError::InvalidShare {
+        share_id: 0,
+        reason: e.to_string(),
+    })?;
+    commitments
+        .verify_share(&share)
+        .map_err(|e| ChallengeError::InvalidShare {
+            share_id: share.id,
+            reason: e.to_string(),
+        })?;
+    Ok(share)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -164,6 +216,30 @@ mod tests {
         assert!(result.reason.unwrap().contains("hash mismatch"));
     }
 
+    #[test]
+    fn test_audit_record() {
+        let (data, challenge, kp, config) = setup();
+        let proof = ChallengeProof::create(&challenge, &data, 2_500, |_msg| {
+            hex::encode(vec![0u8; 64])
+        });
+        let result =
+            validate_challenge_response(&challenge, &proof, &data, &kp.public_key(), &config)
+                .unwrap();
+
+        let record = audit_record(
+            &challenge,
+            &proof,
+            &result,
+            &Address::new("challenger"),
+            &Address::new("node"),
+        );
+        assert_eq!(record.key().share_id, challenge.share_id);
+        assert_eq!(record.nonce, challenge.nonce);
+        assert!(!record.success);
+        assert_eq!(record.failure_reason.as_deref(), Some("invalid signature"));
+        assert_eq!(record.challenged_at, challenge.issued_at + 2);
+    }
+
     #[test]
     fn test_bad_signature_rejected() {
         let (data, challenge, kp, config) = setup();
@@ -201,6 +277,24 @@ mod tests {
         assert!(fast_result.reward > slow_result.reward);
     }
 
+    #[test]
+    fn test_fragment_share_validation() {
+        let (shares, commitments) = gitgold_crypto::vss::split(b"node data", 2, 3).unwrap();
+        let share = validate_fragment_share(&shares[2].to_bytes(), &commitments).unwrap();
+        assert_eq!(share, shares[2]);
+
+        let mut garbage = shares[2].clone();
+        garbage.data[3] ^= 0x80;
+        assert!(matches!(
+            validate_fragment_share(&garbage.to_bytes(), &commitments),
+            Err(ChallengeError::InvalidShare { share_id: 3, .. })
+        ));
+        assert!(matches!(
+            validate_fragment_share(b"not a share", &commitments),
+            Err(ChallengeError::InvalidShare { share_id: 0, .. })
+        ));
+    }
+
     #[test]
     fn test_wrong_key_rejected() {
         let (data, challenge, kp, config) = setup();
@@ -1,4 +1,4 @@
-use crate::types::{MicroGitGold, MICRO_PER_COIN};
+use crate::types::{ChunkingStrategy, MicroGitGold, ShareScheme, MICRO_PER_COIN};
 
 /// Configuration with whitepaper defaults.
 #[derive(Debug, Clone)]
@@ -7,8 +7,12 @@ pub struct GitGoldConfig {
     pub k: usize,
     /// Shamir total shares per chunk.
     pub n: usize,
+    /// Secret sharing engine used to split chunks.
+    pub share_scheme: ShareScheme,
     /// Chunk size in bytes (default 512 KB).
     pub chunk_size: usize,
+    /// Fixed-size or content-defined chunking (default fixed).
+    pub chunking: ChunkingStrategy,
     /// Challenge timeout in seconds.
     pub challenge_timeout_secs: u64,
     /// Push fee rate in micro-GC per MB.
@@ -33,6 +37,9 @@ pub struct GitGoldConfig {
     pub challenge_min_bytes: usize,
     /// Maximum challenge byte range size.
     pub challenge_max_bytes: usize,
+    /// Seconds an expired lease or unreferenced fragment is kept before
+    /// garbage collection may delete it (default 90 days).
+    pub gc_grace_secs: u64,
 }
 
 impl Default for GitGoldConfig {
@@ -40,7 +47,9 @@ impl Default for GitGoldConfig {
         Self {
             k: 5,
             n: 9,
+            share_scheme: ShareScheme::Shamir256,
             chunk_size: 512 * 1024, // 512 KB
+            chunking: ChunkingStrategy::Fixed,
             challenge_timeout_secs: 30,
             push_fee_rate: 1_000,                              // 0.001 GC/MB
             pull_fee_rate: 500,                                // 0.0005 GC/MB
@@ -53,6 +62,7 @@ impl Default for GitGoldConfig {
             pull_burn_rate_bps: 500,                            // 5%
             challenge_min_bytes: 1024,                         // 1 KB
             challenge_max_bytes: 64 * 1024,                    // 64 KB
+            gc_grace_secs: 90 * 24 * 60 * 60,                  // 90 days
         }
     }
 }
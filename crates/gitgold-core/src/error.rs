@@ -1,17 +1,46 @@
 use thiserror::Error;
 
+use crate::types::ShareScheme;
+
 #[derive(Debug, Error)]
 pub enum ShamirError {
     #[error("threshold k={k} must be >= 2")]
     ThresholdTooLow { k: usize },
+    #[error("Reed-Solomon needs at least one data share, got k={k}")]
+    NoDataShares { k: usize },
     #[error("total shares n={n} must be >= threshold k={k}")]
     InsufficientShares { k: usize, n: usize },
+    #[error("total shares n={n} exceeds the scheme maximum of {max}")]
+    TooManyShares { n: usize, max: usize },
     #[error("not enough shares for reconstruction: have {have}, need {need}")]
     NotEnoughShares { have: usize, need: usize },
     #[error("empty secret")]
     EmptySecret,
     #[error("duplicate share id: {0}")]
     DuplicateShareId(u32),
+    #[error("unsupported share format version: {0}")]
+    UnsupportedVersion(u8),
+    #[error("malformed share {id}: {reason}")]
+    MalformedShare { id: u32, reason: String },
+    #[error("reconstructed secret is malformed: {0}")]
+    MalformedSecret(String),
+    #[error("unknown share scheme id: {0}")]
+    UnknownScheme(u8),
+    #[error("scheme mismatch: expected {expected:?}, got {found:?}")]
+    SchemeMismatch {
+        expected: ShareScheme,
+        found: ShareScheme,
+    },
+    #[error("share checksum mismatch for share {id}")]
+    ChecksumMismatch { id: u32 },
+    #[error("share {id} is incompatible with the share set: {field} differs")]
+    IncompatibleShare { id: u32, field: &'static str },
+    #[error("share {id} does not match its commitments")]
+    VerificationFailed { id: u32 },
+    #[error("malformed share commitments: {0}")]
+    MalformedCommitments(String),
+    #[error("block {block} has more inconsistent shares than the {max_errors} that can be corrected")]
+    TooManyCorruptShares { block: usize, max_errors: usize },
 }
 
 #[derive(Debug, Error)]
@@ -25,8 +54,41 @@ pub enum StorageError {
     },
     #[error("data too large: {size} bytes exceeds max {max} bytes")]
     DataTooLarge { size: usize, max: usize },
+    #[error("invalid chunking configuration: {0}")]
+    InvalidChunking(String),
     #[error("invalid chunk index: {index} (total: {total})")]
     InvalidChunkIndex { index: u32, total: u32 },
+    #[error("invalid share {share_id}: {reason}")]
+    InvalidShare { share_id: u32, reason: String },
+    #[error("I/O error: {0}")]
+    Io(String),
+    #[error("secret sharing error: {0}")]
+    Sharing(String),
+    #[error("blob not found: {0}")]
+    BlobNotFound(String),
+    #[error("invalid byte range: {start}..{end} for fragment of size {fragment_size}")]
+    InvalidByteRange {
+        start: usize,
+        end: usize,
+        fragment_size: usize,
+    },
+    #[error("encryption error: {0}")]
+    Encryption(String),
+    #[error("blob {0} failed authentication")]
+    Decryption(String),
+    #[error("invalid manifest: {0}")]
+    InvalidManifest(String),
+    #[error("chunks failed manifest verification: {indices:?}")]
+    CorruptChunks { indices: Vec<u32> },
+    #[error(transparent)]
+    Migration(#[from] MigrationError),
+    #[error("{scope} quota exceeded: {used} used + {requested} requested > limit {limit}")]
+    QuotaExceeded {
+        scope: String,
+        used: u64,
+        requested: u64,
+        limit: u64,
+    },
 }
 
 #[derive(Debug, Error)]
@@ -43,6 +105,18 @@ pub enum LedgerError {
     InvalidTransaction(String),
     #[error("supply exceeded: attempted to mint {attempted}, remaining {remaining}")]
     SupplyExceeded { attempted: u64, remaining: u64 },
+    #[error(transparent)]
+    Migration(#[from] MigrationError),
+}
+
+#[derive(Debug, Error)]
+pub enum MigrationError {
+    #[error("database error: {0}")]
+    Database(String),
+    #[error("database schema version {found} is newer than the newest supported version {supported}")]
+    TooNew { found: u32, supported: u32 },
+    #[error("migration to version {version} failed: {reason}")]
+    Failed { version: u32, reason: String },
 }
 
 #[derive(Debug, Error)]
@@ -61,4 +135,6 @@ pub enum ChallengeError {
     },
     #[error("challenge not found: {0}")]
     ChallengeNotFound(String),
+    #[error("invalid share {share_id}: {reason}")]
+    InvalidShare { share_id: u32, reason: String },
 }
@@ -30,6 +30,73 @@ pub type MicroGitGold = u64;
 /// 1 GitGold in micro-GitGold units.
 pub const MICRO_PER_COIN: u64 = 1_000_000;
 
+/// Secret sharing scheme used to encode a chunk into shares.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ShareScheme {
+    /// Shamir secret sharing over GF(2^256 - 189).
+    Shamir256,
+    /// Byte-wise Shamir secret sharing over GF(2^8).
+    Gf256,
+    /// Systematic Reed-Solomon erasure coding over GF(2^8). Not confidential:
+    /// data shares carry plaintext stripes of the chunk.
+    ReedSolomon,
+    /// Shamir secret sharing over the Ristretto scalar field with Pedersen
+    /// commitments, so every share can be verified on its own.
+    PedersenVss,
+}
+
+impl ShareScheme {
+    /// Wire identifier recorded in share headers.
+    pub fn id(self) -> u8 {
+        match self {
+            ShareScheme::Shamir256 => 1,
+            ShareScheme::Gf256 => 2,
+            ShareScheme::ReedSolomon => 3,
+            ShareScheme::PedersenVss => 4,
+        }
+    }
+
+    /// Look up a scheme by its wire identifier.
+    pub fn from_id(id: u8) -> Option<Self> {
+        match id {
+            1 => Some(ShareScheme::Shamir256),
+            2 => Some(ShareScheme::Gf256),
+            3 => Some(ShareScheme::ReedSolomon),
+            4 => Some(ShareScheme::PedersenVss),
+            _ => None,
+        }
+    }
+}
+
+/// How repository data is cut into chunks before sharing.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum ChunkingStrategy {
+    /// Fixed-size chunks of `GitGoldConfig::chunk_size` bytes.
+    Fixed,
+    /// Content-defined chunking (FastCDC). Cut points depend on the data, so
+    /// an insertion only changes the chunks around it.
+    FastCdc {
+        /// Smallest chunk emitted, except for the final one.
+        min_size: usize,
+        /// Target average chunk size; rounded down to a power of two.
+        avg_size: usize,
+        /// Largest chunk emitted.
+        max_size: usize,
+    },
+}
+
+impl ChunkingStrategy {
+    /// FastCDC with the conventional bounds of a quarter and four times the
+    /// average size.
+    pub fn fast_cdc(avg_size: usize) -> Self {
+        ChunkingStrategy::FastCdc {
+            min_size: avg_size / 4,
+            avg_size,
+            max_size: avg_size.saturating_mul(4),
+        }
+    }
+}
+
 /// Transaction types on the GitGold ledger.
 #[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum TransactionType {
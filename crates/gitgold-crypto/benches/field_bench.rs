@@ -6,7 +6,7 @@ fn field_add(c: &mut Criterion) {
     let a = FieldElement::from_u64(123_456_789);
     let b = FieldElement::from_u64(987_654_321);
     c.bench_function("field_add", |bench| {
-        bench.iter(|| black_box(a.clone()) + black_box(b.clone()))
+        bench.iter(|| black_box(a) + black_box(b))
     });
 }
 
@@ -14,14 +14,14 @@ fn field_mul(c: &mut Criterion) {
     let a = FieldElement::from_u64(123_456_789);
     let b = FieldElement::from_u64(987_654_321);
     c.bench_function("field_mul", |bench| {
-        bench.iter(|| black_box(a.clone()) * black_box(b.clone()))
+        bench.iter(|| black_box(a) * black_box(b))
     });
 }
 
 fn field_inv(c: &mut Criterion) {
     let a = FieldElement::from_u64(123_456_789);
     c.bench_function("field_inv", |bench| {
-        bench.iter(|| black_box(a.clone()).inv())
+        bench.iter(|| black_box(a).inv())
     });
 }
 
@@ -29,7 +29,7 @@ fn field_div(c: &mut Criterion) {
     let a = FieldElement::from_u64(123_456_789);
     let b = FieldElement::from_u64(987_654_321);
     c.bench_function("field_div", |bench| {
-        bench.iter(|| black_box(a.clone()) / black_box(b.clone()))
+        bench.iter(|| black_box(a) / black_box(b))
     });
 }
 
@@ -40,7 +40,7 @@ fn field_mul_256bit(c: &mut Criterion) {
     let a = FieldElement::from_bytes_be(&bytes_a);
     let b = FieldElement::from_bytes_be(&bytes_b);
     c.bench_function("field_mul_256bit", |bench| {
-        bench.iter(|| black_box(a.clone()) * black_box(b.clone()))
+        bench.iter(|| black_box(a) * black_box(b))
     });
 }
 
@@ -48,7 +48,7 @@ fn field_inv_256bit(c: &mut Criterion) {
     let bytes = [0xAB; 32];
     let a = FieldElement::from_bytes_be(&bytes);
     c.bench_function("field_inv_256bit", |bench| {
-        bench.iter(|| black_box(a.clone()).inv())
+        bench.iter(|| black_box(a).inv())
     });
 }
 
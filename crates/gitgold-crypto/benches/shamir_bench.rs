@@ -1,5 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use gitgold_crypto::shamir::{reconstruct, split};
+use gitgold_crypto::gf256;
+use gitgold_crypto::shamir::{
+    reconstruct, reconstruct_parallel, reconstruct_robust, split, split_parallel,
+};
 
 fn shamir_split(c: &mut Criterion) {
     let configs: &[(usize, usize)] = &[(3, 5), (5, 9)];
@@ -45,9 +48,9 @@ fn shamir_reconstruct(c: &mut Criterion) {
             let shares = split(&secret, k, n).unwrap();
             group.bench_with_input(
                 BenchmarkId::new(format!("k{k}_n{n}"), label),
-                &(&shares, k),
-                |bench, &(shares, k)| {
-                    bench.iter(|| reconstruct(black_box(shares), black_box(k)).unwrap())
+                &shares,
+                |bench, shares| {
+                    bench.iter(|| reconstruct(black_box(shares)).unwrap())
                 },
             );
         }
@@ -60,10 +63,106 @@ fn shamir_roundtrip(c: &mut Criterion) {
     c.bench_function("shamir_roundtrip_32B_k3n5", |bench| {
         bench.iter(|| {
             let shares = split(black_box(&secret), 3, 5).unwrap();
-            reconstruct(black_box(&shares), 3).unwrap()
+            reconstruct(black_box(&shares)).unwrap()
         })
     });
 }
 
-criterion_group!(benches, shamir_split, shamir_reconstruct, shamir_roundtrip,);
+fn shamir_reconstruct_robust(c: &mut Criterion) {
+    let secret = vec![0xABu8; 512 * 1024];
+    let shares = split(&secret, 5, 9).unwrap();
+    let mut corrupted = shares.clone();
+    for b in corrupted[0].data.iter_mut().step_by(32) {
+        *b ^= 0x01;
+    }
+
+    let mut group = c.benchmark_group("shamir_reconstruct_robust");
+    group.sample_size(10);
+    group.bench_with_input(BenchmarkId::new("k5_n9", "512KB_clean"), &shares, |bench, shares| {
+        bench.iter(|| reconstruct_robust(black_box(shares)).unwrap())
+    });
+    group.bench_with_input(
+        BenchmarkId::new("k5_n9", "512KB_one_bad_share"),
+        &corrupted,
+        |bench, shares| bench.iter(|| reconstruct_robust(black_box(shares)).unwrap()),
+    );
+    group.finish();
+}
+
+fn shamir_parallel(c: &mut Criterion) {
+    let secret = vec![0xABu8; 512 * 1024];
+    let shares = split(&secret, 5, 9).unwrap();
+
+    let mut group = c.benchmark_group("shamir_parallel_512KB_k5n9");
+    group.sample_size(10);
+    group.bench_function("split", |bench| {
+        bench.iter(|| split_parallel(black_box(&secret), 5, 9).unwrap())
+    });
+    group.bench_function("reconstruct", |bench| {
+        bench.iter(|| reconstruct_parallel(black_box(&shares)).unwrap())
+    });
+    group.finish();
+}
+
+fn gf256_split(c: &mut Criterion) {
+    let configs: &[(usize, usize)] = &[(3, 5), (5, 9)];
+    let sizes: &[(usize, &str)] = &[
+        (32, "32B"),
+        (1024, "1KB"),
+        (32 * 1024, "32KB"),
+        (512 * 1024, "512KB"),
+    ];
+
+    let mut group = c.benchmark_group("gf256_split");
+    group.sample_size(10);
+    for &(size, label) in sizes {
+        let secret = vec![0xABu8; size];
+        for &(k, n) in configs {
+            group.bench_with_input(
+                BenchmarkId::new(format!("k{k}_n{n}"), label),
+                &(&secret, k, n),
+                |bench, &(secret, k, n)| {
+                    bench.iter(|| gf256::split(black_box(secret), black_box(k), black_box(n)).unwrap())
+                },
+            );
+        }
+    }
+    group.finish();
+}
+
+fn gf256_reconstruct(c: &mut Criterion) {
+    let configs: &[(usize, usize)] = &[(3, 5), (5, 9)];
+    let sizes: &[(usize, &str)] = &[
+        (32, "32B"),
+        (1024, "1KB"),
+        (32 * 1024, "32KB"),
+        (512 * 1024, "512KB"),
+    ];
+
+    let mut group = c.benchmark_group("gf256_reconstruct");
+    group.sample_size(10);
+    for &(size, label) in sizes {
+        let secret = vec![0xABu8; size];
+        for &(k, n) in configs {
+            let shares = gf256::split(&secret, k, n).unwrap();
+            group.bench_with_input(
+                BenchmarkId::new(format!("k{k}_n{n}"), label),
+                &shares,
+                |bench, shares| bench.iter(|| gf256::reconstruct(black_box(shares)).unwrap()),
+            );
+        }
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    shamir_split,
+    shamir_reconstruct,
+    shamir_roundtrip,
+    shamir_reconstruct_robust,
+    shamir_parallel,
+    gf256_split,
+    gf256_reconstruct,
+);
 criterion_main!(benches);
@@ -1,7 +1,27 @@
 use num_bigint::BigUint;
-use num_traits::{One, Zero};
+use rand::{CryptoRng, RngCore};
 use std::ops::{Add, Div, Mul, Sub};
 
+/// The prime modulus p = 2^256 - 189 as little-endian 64-bit limbs.
+const P: [u64; 4] = [
+    0xFFFF_FFFF_FFFF_FF43,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+];
+
+/// p - 2, the Fermat inversion exponent.
+const P_MINUS_2: [u64; 4] = [
+    0xFFFF_FFFF_FFFF_FF41,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+    0xFFFF_FFFF_FFFF_FFFF,
+];
+
+/// 2^256 mod p. Reduction folds the high half of a product back in as
+/// `hi * 189`.
+const C: u64 = 189;
+
 /// The prime modulus for GF(p): p = 2^256 - 189
 fn prime() -> BigUint {
     let two = BigUint::from(2u32);
@@ -9,76 +29,203 @@ fn prime() -> BigUint {
 }
 
 /// An element in the finite field GF(p) where p = 2^256 - 189.
-#[derive(Debug, Clone, PartialEq, Eq)]
+///
+/// Stored as four little-endian 64-bit limbs, always fully reduced. Addition,
+/// subtraction, multiplication and inversion run in constant time with
+/// respect to the element values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct FieldElement {
-    value: BigUint,
-    modulus: BigUint,
+    limbs: [u64; 4],
 }
 
 impl FieldElement {
     /// Create a new field element, reducing value mod p.
     pub fn new(value: BigUint) -> Self {
-        let modulus = prime();
-        let value = value % &modulus;
-        Self { value, modulus }
+        let reduced = value % prime();
+        let mut limbs = [0u64; 4];
+        for (limb, digit) in limbs.iter_mut().zip(reduced.iter_u64_digits()) {
+            *limb = digit;
+        }
+        Self { limbs }
     }
 
     /// Create a field element from a u64.
     pub fn from_u64(v: u64) -> Self {
-        Self::new(BigUint::from(v))
+        Self {
+            limbs: [v, 0, 0, 0],
+        }
     }
 
     /// Create the zero element.
     pub fn zero() -> Self {
-        Self::new(BigUint::zero())
+        Self::from_u64(0)
     }
 
     /// Create the one element.
     pub fn one() -> Self {
-        Self::new(BigUint::one())
+        Self::from_u64(1)
     }
 
-    /// Create a field element from big-endian bytes.
+    /// Create a field element from big-endian bytes, reducing mod p.
     pub fn from_bytes_be(bytes: &[u8]) -> Self {
-        Self::new(BigUint::from_bytes_be(bytes))
+        if bytes.len() > 32 {
+            return Self::new(BigUint::from_bytes_be(bytes));
+        }
+        let mut buf = [0u8; 32];
+        buf[32 - bytes.len()..].copy_from_slice(bytes);
+        let mut limbs = [0u64; 4];
+        for (i, limb) in limbs.iter_mut().enumerate() {
+            let start = 24 - i * 8;
+            *limb = u64::from_be_bytes(buf[start..start + 8].try_into().unwrap());
+        }
+        // Any 256-bit value is < 2p, so one conditional subtraction reduces it
+        Self {
+            limbs: sub_p_if_ge(limbs, 0),
+        }
     }
 
     /// Export to big-endian bytes, zero-padded to 32 bytes.
     pub fn to_bytes_be(&self) -> Vec<u8> {
-        let bytes = self.value.to_bytes_be();
-        if bytes.len() >= 32 {
-            bytes[bytes.len() - 32..].to_vec()
-        } else {
-            let mut padded = vec![0u8; 32 - bytes.len()];
-            padded.extend_from_slice(&bytes);
-            padded
+        self.to_array_be().to_vec()
+    }
+
+    /// Export to a fixed 32-byte big-endian array.
+    pub fn to_array_be(&self) -> [u8; 32] {
+        let mut out = [0u8; 32];
+        for (i, limb) in self.limbs.iter().enumerate() {
+            let start = 24 - i * 8;
+            out[start..start + 8].copy_from_slice(&limb.to_be_bytes());
         }
+        out
     }
 
-    /// Returns the underlying BigUint value.
-    pub fn value(&self) -> &BigUint {
-        &self.value
+    /// Returns the value as a BigUint.
+    pub fn value(&self) -> BigUint {
+        BigUint::from_bytes_be(&self.to_array_be())
+    }
+
+    /// Whether this is the zero element.
+    pub fn is_zero(&self) -> bool {
+        self.limbs.iter().fold(0, |acc, l| acc | l) == 0
+    }
+
+    /// Sample a uniformly random field element.
+    pub fn random<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        loop {
+            let mut bytes = [0u8; 32];
+            rng.fill_bytes(&mut bytes);
+            let candidate = Self::from_bytes_be(&bytes);
+            // Reject values that were reduced (>= p) to keep the distribution uniform
+            if candidate.to_array_be() == bytes {
+                return candidate;
+            }
+        }
     }
 
     /// Modular multiplicative inverse via Fermat's little theorem: a^(p-2) mod p.
     pub fn inv(&self) -> Self {
-        assert!(!self.value.is_zero(), "cannot invert zero");
-        let exp = &self.modulus - BigUint::from(2u32);
-        let result = self.value.modpow(&exp, &self.modulus);
-        Self {
-            value: result,
-            modulus: self.modulus.clone(),
+        assert!(!self.is_zero(), "cannot invert zero");
+        self.pow(&P_MINUS_2)
+    }
+
+    /// Raise to a (public) 256-bit exponent given as little-endian limbs.
+    fn pow(&self, exp: &[u64; 4]) -> Self {
+        let mut result = Self::one();
+        for limb in exp.iter().rev() {
+            for bit in (0..64).rev() {
+                result = result * result;
+                let product = result * *self;
+                let mask = 0u64.wrapping_sub((limb >> bit) & 1);
+                result.limbs = select(mask, product.limbs, result.limbs);
+            }
         }
+        result
+    }
+}
+
+/// Constant-time select: `a` where mask is all ones, `b` where it is zero.
+fn select(mask: u64, a: [u64; 4], b: [u64; 4]) -> [u64; 4] {
+    let mut out = [0u64; 4];
+    for i in 0..4 {
+        out[i] = (a[i] & mask) | (b[i] & !mask);
+    }
+    out
+}
+
+/// 256-bit addition returning the carry out.
+fn add_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut out = [0u64; 4];
+    let mut carry = 0u64;
+    for i in 0..4 {
+        let (s1, c1) = a[i].overflowing_add(b[i]);
+        let (s2, c2) = s1.overflowing_add(carry);
+        out[i] = s2;
+        carry = (c1 | c2) as u64;
+    }
+    (out, carry)
+}
+
+/// 256-bit subtraction returning the borrow out.
+fn sub_limbs(a: &[u64; 4], b: &[u64; 4]) -> ([u64; 4], u64) {
+    let mut out = [0u64; 4];
+    let mut borrow = 0u64;
+    for i in 0..4 {
+        let (d1, b1) = a[i].overflowing_sub(b[i]);
+        let (d2, b2) = d1.overflowing_sub(borrow);
+        out[i] = d2;
+        borrow = (b1 | b2) as u64;
     }
+    (out, borrow)
+}
+
+/// Subtract p from `value + carry * 2^256` if that quantity is >= p.
+///
+/// Requires the input to be < 2p.
+fn sub_p_if_ge(value: [u64; 4], carry: u64) -> [u64; 4] {
+    let (diff, borrow) = sub_limbs(&value, &P);
+    // Take the difference when there was a carry out, or when no borrow occurred
+    let mask = 0u64.wrapping_sub(carry | (borrow ^ 1));
+    select(mask, diff, value)
+}
+
+/// Reduce a 512-bit product (little-endian limbs) mod p using 2^256 = 189.
+fn reduce_wide(wide: [u64; 8]) -> [u64; 4] {
+    // lo + hi * 189 fits in 256 bits plus a carry below 2^8
+    let mut out = [0u64; 4];
+    let mut carry: u128 = 0;
+    for i in 0..4 {
+        let t = wide[i] as u128 + wide[i + 4] as u128 * C as u128 + carry;
+        out[i] = t as u64;
+        carry = t >> 64;
+    }
+
+    // Fold the carry again; this can wrap 2^256 at most once more
+    let (folded, overflow) = add_limbs(&out, &[(carry as u64) * C, 0, 0, 0]);
+    let (folded, _) = add_limbs(&folded, &[overflow * C, 0, 0, 0]);
+
+    sub_p_if_ge(folded, 0)
+}
+
+fn mul_limbs(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut wide = [0u64; 8];
+    for i in 0..4 {
+        let mut carry = 0u64;
+        for j in 0..4 {
+            let t = a[i] as u128 * b[j] as u128 + wide[i + j] as u128 + carry as u128;
+            wide[i + j] = t as u64;
+            carry = (t >> 64) as u64;
+        }
+        wide[i + 4] = carry;
+    }
+    reduce_wide(wide)
 }
 
 impl Add for FieldElement {
     type Output = Self;
     fn add(self, rhs: Self) -> Self {
-        let value = (&self.value + &rhs.value) % &self.modulus;
+        let (sum, carry) = add_limbs(&self.limbs, &rhs.limbs);
         Self {
-            value,
-            modulus: self.modulus,
+            limbs: sub_p_if_ge(sum, carry),
         }
     }
 }
@@ -86,22 +233,19 @@ impl Add for FieldElement {
 impl Add for &FieldElement {
     type Output = FieldElement;
     fn add(self, rhs: Self) -> FieldElement {
-        let value = (&self.value + &rhs.value) % &self.modulus;
-        FieldElement {
-            value,
-            modulus: self.modulus.clone(),
-        }
+        *self + *rhs
     }
 }
 
 impl Sub for FieldElement {
     type Output = Self;
     fn sub(self, rhs: Self) -> Self {
-        // Add modulus to avoid underflow: (a - b + p) mod p
-        let value = (&self.value + &self.modulus - &rhs.value) % &self.modulus;
+        // Add p back when the subtraction underflows: (a - b + p) mod p
+        let (diff, borrow) = sub_limbs(&self.limbs, &rhs.limbs);
+        let mask = 0u64.wrapping_sub(borrow);
+        let (wrapped, _) = add_limbs(&diff, &P);
         Self {
-            value,
-            modulus: self.modulus,
+            limbs: select(mask, wrapped, diff),
         }
     }
 }
@@ -109,21 +253,15 @@ impl Sub for FieldElement {
 impl Sub for &FieldElement {
     type Output = FieldElement;
     fn sub(self, rhs: Self) -> FieldElement {
-        let value = (&self.value + &self.modulus - &rhs.value) % &self.modulus;
-        FieldElement {
-            value,
-            modulus: self.modulus.clone(),
-        }
+        *self - *rhs
     }
 }
 
 impl Mul for FieldElement {
     type Output = Self;
     fn mul(self, rhs: Self) -> Self {
-        let value = (&self.value * &rhs.value) % &self.modulus;
         Self {
-            value,
-            modulus: self.modulus,
+            limbs: mul_limbs(&self.limbs, &rhs.limbs),
         }
     }
 }
@@ -131,11 +269,7 @@ impl Mul for FieldElement {
 impl Mul for &FieldElement {
     type Output = FieldElement;
     fn mul(self, rhs: Self) -> FieldElement {
-        let value = (&self.value * &rhs.value) % &self.modulus;
-        FieldElement {
-            value,
-            modulus: self.modulus.clone(),
-        }
+        *self * *rhs
     }
 }
 
@@ -158,13 +292,18 @@ impl Div for &FieldElement {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::prelude::*;
+
+    fn reference(bytes: &[u8; 32]) -> BigUint {
+        BigUint::from_bytes_be(bytes) % prime()
+    }
 
     #[test]
     fn test_add() {
         let a = FieldElement::from_u64(10);
         let b = FieldElement::from_u64(20);
         let c = a + b;
-        assert_eq!(*c.value(), BigUint::from(30u32));
+        assert_eq!(c.value(), BigUint::from(30u32));
     }
 
     #[test]
@@ -172,7 +311,7 @@ mod tests {
         let a = FieldElement::from_u64(30);
         let b = FieldElement::from_u64(10);
         let c = a - b;
-        assert_eq!(*c.value(), BigUint::from(20u32));
+        assert_eq!(c.value(), BigUint::from(20u32));
     }
 
     #[test]
@@ -182,7 +321,7 @@ mod tests {
         let c = a - b;
         // Should wrap: (5 - 10 + p) mod p = p - 5
         let expected = prime() - BigUint::from(5u32);
-        assert_eq!(*c.value(), expected);
+        assert_eq!(c.value(), expected);
     }
 
     #[test]
@@ -190,15 +329,15 @@ mod tests {
         let a = FieldElement::from_u64(7);
         let b = FieldElement::from_u64(6);
         let c = a * b;
-        assert_eq!(*c.value(), BigUint::from(42u32));
+        assert_eq!(c.value(), BigUint::from(42u32));
     }
 
     #[test]
     fn test_inv() {
         let a = FieldElement::from_u64(7);
-        let a_inv = a.clone().inv();
+        let a_inv = a.inv();
         let product = a * a_inv;
-        assert_eq!(*product.value(), BigUint::one());
+        assert_eq!(product, FieldElement::one());
     }
 
     #[test]
@@ -206,7 +345,7 @@ mod tests {
         let a = FieldElement::from_u64(42);
         let b = FieldElement::from_u64(7);
         let c = a / b;
-        assert_eq!(*c.value(), BigUint::from(6u32));
+        assert_eq!(c.value(), BigUint::from(6u32));
     }
 
     #[test]
@@ -229,7 +368,7 @@ mod tests {
     fn test_one_times_x_is_x() {
         let x = FieldElement::from_u64(999);
         let one = FieldElement::one();
-        let result = x.clone() * one;
+        let result = x * one;
         assert_eq!(result, x);
     }
 
@@ -239,6 +378,44 @@ mod tests {
         let p = prime();
         let val = &p + BigUint::from(5u32);
         let elem = FieldElement::new(val);
-        assert_eq!(*elem.value(), BigUint::from(5u32));
+        assert_eq!(elem.value(), BigUint::from(5u32));
+    }
+
+    #[test]
+    fn test_from_bytes_reduces_values_above_p() {
+        // p + 5 and 2^256 - 1 both fit in 32 bytes but exceed p
+        let mut bytes = [0xFF; 32];
+        assert_eq!(FieldElement::from_bytes_be(&bytes).value(), BigUint::from(188u32));
+        bytes[31] = 0x43 + 5;
+        assert_eq!(FieldElement::from_bytes_be(&bytes).value(), BigUint::from(5u32));
+    }
+
+    #[test]
+    fn test_p_minus_one_squared() {
+        // (p - 1)^2 = 1 mod p exercises the full reduction path
+        let minus_one = FieldElement::zero() - FieldElement::one();
+        assert_eq!(minus_one * minus_one, FieldElement::one());
+    }
+
+    proptest! {
+        #[test]
+        fn prop_matches_biguint_reference(a in any::<[u8; 32]>(), b in any::<[u8; 32]>()) {
+            let fa = FieldElement::from_bytes_be(&a);
+            let fb = FieldElement::from_bytes_be(&b);
+            let (ra, rb) = (reference(&a), reference(&b));
+            let p = prime();
+
+            prop_assert_eq!(fa.value(), ra.clone());
+            prop_assert_eq!((fa + fb).value(), (&ra + &rb) % &p);
+            prop_assert_eq!((fa - fb).value(), (&ra + &p - &rb) % &p);
+            prop_assert_eq!((fa * fb).value(), (&ra * &rb) % &p);
+        }
+
+        #[test]
+        fn prop_inverse(a in any::<[u8; 32]>()) {
+            let fa = FieldElement::from_bytes_be(&a);
+            prop_assume!(!fa.is_zero());
+            prop_assert_eq!(fa * fa.inv(), FieldElement::one());
+        }
     }
 }
@@ -0,0 +1,314 @@
+use gitgold_core::error::ShamirError;
+use gitgold_core::types::ShareScheme;
+use rand::{thread_rng, CryptoRng, RngCore};
+
+use crate::share::{validate_share_set, Share};
+
+/// Maximum number of shares: x-coordinates are the non-zero bytes.
+pub const MAX_SHARES: usize = 255;
+
+/// Secret bytes whose random coefficients are generated in one RNG call.
+const RNG_BATCH: usize = 4096;
+
+/// Reduction polynomial x^8 + x^4 + x^3 + x + 1 (as used by AES).
+const POLY: u16 = 0x11B;
+
+/// Exponent and logarithm tables for generator 3.
+///
+/// `EXP` is doubled in length so `EXP[log a + log b]` needs no reduction.
+const TABLES: ([u8; 510], [u8; 256]) = build_tables();
+const EXP: [u8; 510] = TABLES.0;
+const LOG: [u8; 256] = TABLES.1;
+
+const fn build_tables() -> ([u8; 510], [u8; 256]) {
+    let mut exp = [0u8; 510];
+    let mut log = [0u8; 256];
+    let mut x: u16 = 1;
+    let mut i = 0;
+    while i < 255 {
+        exp[i] = x as u8;
+        exp[i + 255] = x as u8;
+        log[x as usize] = i as u8;
+        // x *= 3, i.e. x ^ (x << 1), reduced by POLY
+        let mut doubled = x << 1;
+        if doubled & 0x100 != 0 {
+            doubled ^= POLY;
+        }
+        x ^= doubled;
+        i += 1;
+    }
+    (exp, log)
+}
+
+/// Multiply two elements of GF(2^8).
+pub(crate) fn mul(a: u8, b: u8) -> u8 {
+    if a == 0 || b == 0 {
+        return 0;
+    }
+    EXP[LOG[a as usize] as usize + LOG[b as usize] as usize]
+}
+
+/// Divide two elements of GF(2^8). `b` must be non-zero.
+pub(crate) fn div(a: u8, b: u8) -> u8 {
+    if a == 0 {
+        return 0;
+    }
+    EXP[LOG[a as usize] as usize + 255 - LOG[b as usize] as usize]
+}
+
+/// Split a secret into `n` shares where any `k` can reconstruct.
+///
+/// Every byte of the secret is the constant term of its own random polynomial
+/// over GF(2^8), so shares are exactly as long as the secret and need no
+/// padding.
+pub fn split(secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError> {
+    split_with_rng(secret, k, n, &mut thread_rng())
+}
+
+/// `split`, drawing the polynomial coefficients from `rng`.
+pub fn split_with_rng<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>, ShamirError> {
+    if secret.is_empty() {
+        return Err(ShamirError::EmptySecret);
+    }
+    if k < 2 {
+        return Err(ShamirError::ThresholdTooLow { k });
+    }
+    if n < k {
+        return Err(ShamirError::InsufficientShares { k, n });
+    }
+    if n > MAX_SHARES {
+        return Err(ShamirError::TooManyShares { n, max: MAX_SHARES });
+    }
+
+    let mut shares: Vec<Share> = (1..=n as u32)
+        .map(|id| Share {
+            id,
+            scheme: ShareScheme::Gf256,
+            k: k as u32,
+            n: n as u32,
+            secret_len: secret.len() as u64,
+            chunk_index: 0,
+            repo_hash: [0u8; 32],
+            data: Vec::with_capacity(secret.len()),
+        })
+        .collect();
+
+    let mut coeffs = vec![0u8; k];
+    // Random coefficients are drawn in batches rather than per byte
+    let mut random = vec![0u8; (k - 1) * RNG_BATCH];
+
+    for batch in secret.chunks(RNG_BATCH) {
+        rng.fill_bytes(&mut random[..(k - 1) * batch.len()]);
+
+        for (&byte, rand_coeffs) in batch.iter().zip(random.chunks_exact(k - 1)) {
+            // a_0 = secret byte, random coefficients a_1 .. a_{k-1}
+            coeffs[0] = byte;
+            coeffs[1..].copy_from_slice(rand_coeffs);
+
+            // Evaluate at x = id using Horner's method
+            for share in shares.iter_mut() {
+                let x = share.id as u8;
+                let y = coeffs.iter().rev().fold(0u8, |acc, &c| mul(acc, x) ^ c);
+                share.data.push(y);
+            }
+        }
+    }
+
+    Ok(shares)
+}
+
+/// Reconstruct a secret from `k` or more shares.
+///
+/// The threshold and secret length are taken from the share headers.
+pub fn reconstruct(shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+    let k = validate_share_set(shares, ShareScheme::Gf256)?;
+    let selected = &shares[..k];
+
+    check_shares(selected)?;
+
+    Ok(interpolate_at(selected, 0))
+}
+
+/// Regenerate the share for `target_id` from k surviving shares by
+/// interpolating every byte at x = `target_id`.
+pub fn repair_share(shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+    let k = validate_share_set(shares, ShareScheme::Gf256)?;
+    let selected = &shares[..k];
+    check_repair_target(&selected[0], target_id)?;
+    check_shares(selected)?;
+
+    Ok(Share {
+        id: target_id,
+        data: interpolate_at(selected, target_id as u8),
+        ..selected[0].clone()
+    })
+}
+
+/// Check that `target_id` is a valid share id for the split `template`
+/// belongs to, and a GF(2^8) x-coordinate.
+///
+/// The header `n` is untrusted: without the bound an id of 256 would
+/// truncate to x = 0 and the "repaired" share would be the secret.
+pub(crate) fn check_repair_target(template: &Share, target_id: u32) -> Result<(), ShamirError> {
+    check_share_count(template)?;
+    crate::shamir::check_repair_target(template, target_id)
+}
+
+/// Reject a header claiming more shares than GF(2^8) has x-coordinates.
+pub(crate) fn check_share_count(template: &Share) -> Result<(), ShamirError> {
+    if template.n as usize > MAX_SHARES {
+        return Err(ShamirError::TooManyShares {
+            n: template.n as usize,
+            max: MAX_SHARES,
+        });
+    }
+    Ok(())
+}
+
+fn check_shares(shares: &[Share]) -> Result<(), ShamirError> {
+    let secret_len = shares[0].secret_len as usize;
+    for share in shares {
+        if share.id == 0 || share.id as usize > MAX_SHARES {
+            return Err(ShamirError::MalformedShare {
+                id: share.id,
+                reason: "id is not a valid GF(2^8) x-coordinate".to_string(),
+            });
+        }
+        if share.data.len() != secret_len {
+            return Err(ShamirError::MalformedShare {
+                id: share.id,
+                reason: format!(
+                    "data length {} does not match secret length {secret_len}",
+                    share.data.len()
+                ),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Evaluate the shared polynomials of every byte at `at`.
+///
+/// Lagrange basis: L_i(at) = prod_{j!=i} (at - x_j) / (x_i - x_j), where
+/// subtraction in GF(2^8) is XOR.
+fn interpolate_at(shares: &[Share], at: u8) -> Vec<u8> {
+    let xs: Vec<u8> = shares.iter().map(|s| s.id as u8).collect();
+    let basis: Vec<u8> = xs
+        .iter()
+        .enumerate()
+        .map(|(i, &xi)| {
+            xs.iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(1u8, |acc, (_, &xj)| mul(acc, div(at ^ xj, xi ^ xj)))
+        })
+        .collect();
+
+    let mut result = vec![0u8; shares[0].data.len()];
+    for (share, &coeff) in shares.iter().zip(&basis) {
+        for (out, &y) in result.iter_mut().zip(&share.data) {
+            *out ^= mul(y, coeff);
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_field_inverse() {
+        for a in 1..=255u8 {
+            assert_eq!(mul(a, div(1, a)), 1, "inverse of {a}");
+        }
+    }
+
+    #[test]
+    fn test_split_reconstruct_basic() {
+        let secret = b"byte-wise sharing";
+        let shares = split(secret, 3, 5).unwrap();
+        assert!(shares.iter().all(|s| s.data.len() == secret.len()));
+        assert_eq!(reconstruct(&shares[2..]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_any_k_subset_works() {
+        let secret: Vec<u8> = (0..=255).collect();
+        let shares = split(&secret, 2, 4).unwrap();
+        for i in 0..4 {
+            for j in (i + 1)..4 {
+                let pair = [shares[i].clone(), shares[j].clone()];
+                assert_eq!(reconstruct(&pair).unwrap(), secret);
+            }
+        }
+    }
+
+    #[test]
+    fn test_k_minus_1_fails() {
+        let shares = split(b"not enough", 3, 5).unwrap();
+        assert!(matches!(
+            reconstruct(&shares[..2]),
+            Err(ShamirError::NotEnoughShares { have: 2, need: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_too_many_shares() {
+        assert!(matches!(
+            split(b"x", 3, 256),
+            Err(ShamirError::TooManyShares { n: 256, max: 255 })
+        ));
+    }
+
+    #[test]
+    fn test_repair_share() {
+        let shares = split(b"regenerate me", 3, 6).unwrap();
+        let repaired = repair_share(&shares[3..], 1).unwrap();
+        assert_eq!(repaired, shares[0]);
+    }
+
+    #[test]
+    fn test_repair_target_beyond_field_rejected() {
+        let mut shares = split(b"never at x = 0", 2, 3).unwrap();
+        for share in &mut shares {
+            share.n = 256;
+        }
+        assert!(matches!(
+            repair_share(&shares[..2], 256),
+            Err(ShamirError::TooManyShares { n: 256, max: 255 })
+        ));
+
+        for share in &mut shares {
+            share.n = 3;
+        }
+        assert!(matches!(
+            repair_share(&shares[..2], 256),
+            Err(ShamirError::MalformedShare { id: 256, .. })
+        ));
+    }
+
+    #[test]
+    fn test_seeded_split_is_reproducible() {
+        use rand::SeedableRng;
+        let seeded = || rand_chacha::ChaCha20Rng::from_seed([9; 32]);
+        let a = split_with_rng(b"same seed, same shares", 2, 4, &mut seeded()).unwrap();
+        let b = split_with_rng(b"same seed, same shares", 2, 4, &mut seeded()).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(reconstruct(&a[2..]).unwrap(), b"same seed, same shares");
+    }
+
+    #[test]
+    fn test_prime_field_shares_rejected() {
+        let shares = crate::shamir::split(b"wrong engine", 2, 3).unwrap();
+        assert!(matches!(
+            reconstruct(&shares),
+            Err(ShamirError::SchemeMismatch { .. })
+        ));
+    }
+}
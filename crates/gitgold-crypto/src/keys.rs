@@ -1,6 +1,7 @@
 use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
 use gitgold_core::types::Address;
 use rand::rngs::OsRng;
+use rand::{CryptoRng, RngCore};
 use serde::{Deserialize, Serialize};
 
 use crate::hash::sha256_hex;
@@ -12,7 +13,7 @@ pub struct KeyPair {
 }
 
 /// Serializable public key wrapper.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct PublicKey {
     pub bytes: Vec<u8>,
 }
@@ -20,7 +21,12 @@ pub struct PublicKey {
 impl KeyPair {
     /// Generate a new random key pair.
     pub fn generate() -> Self {
-        let signing_key = SigningKey::generate(&mut OsRng);
+        Self::generate_with_rng(&mut OsRng)
+    }
+
+    /// Generate a key pair from the given randomness source.
+    pub fn generate_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        let signing_key = SigningKey::generate(rng);
         Self { signing_key }
     }
 
@@ -116,4 +122,13 @@ mod tests {
         let kp2 = KeyPair::from_bytes(&secret);
         assert_eq!(kp1.address(), kp2.address());
     }
+
+    #[test]
+    fn test_generate_with_seeded_rng() {
+        use rand::SeedableRng;
+        let kp1 = KeyPair::generate_with_rng(&mut rand_chacha::ChaCha20Rng::from_seed([3; 32]));
+        let kp2 = KeyPair::generate_with_rng(&mut rand_chacha::ChaCha20Rng::from_seed([3; 32]));
+        assert_eq!(kp1.address(), kp2.address());
+        assert_ne!(kp1.address(), KeyPair::generate().address());
+    }
 }
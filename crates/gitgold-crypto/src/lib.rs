@@ -1,5 +1,10 @@
 pub mod field;
+pub mod gf256;
 pub mod hash;
 pub mod keys;
+pub mod reed_solomon;
+pub mod scheme;
 pub mod shamir;
+pub mod share;
+pub mod vss;
 pub mod wallet;
@@ -0,0 +1,323 @@
+use gitgold_core::error::ShamirError;
+use gitgold_core::types::ShareScheme;
+
+use crate::gf256::{check_repair_target, check_share_count, div, mul, MAX_SHARES};
+use crate::share::{validate_share_set, Share};
+
+/// Encode data into `k` data shares and `n - k` parity shares.
+///
+/// The data is zero-padded to a multiple of `k` and cut into `k` equal
+/// stripes. Shares 1..=k carry the stripes verbatim; shares k+1..=n carry
+/// parity computed with a Cauchy matrix over GF(2^8), so any `k` shares
+/// recover the data. Storage overhead is n/k, but unlike Shamir the data
+/// shares reveal the plaintext.
+pub fn encode(data: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError> {
+    if data.is_empty() {
+        return Err(ShamirError::EmptySecret);
+    }
+    if k < 1 {
+        return Err(ShamirError::NoDataShares { k });
+    }
+    if n < k {
+        return Err(ShamirError::InsufficientShares { k, n });
+    }
+    if n > MAX_SHARES {
+        return Err(ShamirError::TooManyShares { n, max: MAX_SHARES });
+    }
+
+    let stripe_len = data.len().div_ceil(k);
+    let stripes: Vec<Vec<u8>> = (0..k)
+        .map(|j| {
+            let start = (j * stripe_len).min(data.len());
+            let end = ((j + 1) * stripe_len).min(data.len());
+            let mut stripe = data[start..end].to_vec();
+            stripe.resize(stripe_len, 0);
+            stripe
+        })
+        .collect();
+
+    let mut shares = Vec::with_capacity(n);
+    for id in 1..=n as u32 {
+        let row = (id - 1) as usize;
+        let payload = if row < k {
+            stripes[row].clone()
+        } else {
+            let coeffs = cauchy_row(row, k);
+            let mut parity = vec![0u8; stripe_len];
+            for (stripe, &c) in stripes.iter().zip(&coeffs) {
+                for (p, &b) in parity.iter_mut().zip(stripe) {
+                    *p ^= mul(c, b);
+                }
+            }
+            parity
+        };
+        shares.push(Share {
+            id,
+            scheme: ShareScheme::ReedSolomon,
+            k: k as u32,
+            n: n as u32,
+            secret_len: data.len() as u64,
+            chunk_index: 0,
+            repo_hash: [0u8; 32],
+            data: payload,
+        });
+    }
+
+    Ok(shares)
+}
+
+/// Decode the original data from any `k` shares.
+///
+/// When all data shares are present the stripes are concatenated directly;
+/// otherwise the encoding matrix restricted to the available rows is
+/// inverted to recover the missing stripes.
+pub fn decode(shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+    let k = validate_share_set(shares, ShareScheme::ReedSolomon)?;
+    // Rows past MAX_SHARES would truncate in `cauchy_row`
+    check_share_count(&shares[0])?;
+    let n = shares[0].n as usize;
+    let secret_len = shares[0].secret_len as usize;
+    let stripe_len = secret_len.div_ceil(k);
+
+    for share in shares {
+        if share.id == 0 || share.id as usize > n {
+            return Err(ShamirError::MalformedShare {
+                id: share.id,
+                reason: format!("id outside 1..={n}"),
+            });
+        }
+        if share.data.len() != stripe_len {
+            return Err(ShamirError::MalformedShare {
+                id: share.id,
+                reason: format!(
+                    "stripe length {} does not match expected {stripe_len}",
+                    share.data.len()
+                ),
+            });
+        }
+    }
+
+    // Prefer data shares: they need no arithmetic
+    let mut selected: Vec<&Share> = shares.iter().collect();
+    selected.sort_by_key(|s| s.id);
+    selected.truncate(k);
+
+    let stripes: Vec<Vec<u8>> = if selected.iter().all(|s| s.id as usize <= k) {
+        selected.iter().map(|s| s.data.clone()).collect()
+    } else {
+        let matrix: Vec<Vec<u8>> = selected
+            .iter()
+            .map(|s| encoding_row((s.id - 1) as usize, k))
+            .collect();
+        let inverse = invert(matrix).ok_or_else(|| {
+            ShamirError::MalformedSecret("encoding matrix is singular".to_string())
+        })?;
+
+        inverse
+            .iter()
+            .map(|row| {
+                let mut stripe = vec![0u8; stripe_len];
+                for (share, &c) in selected.iter().zip(row) {
+                    for (out, &b) in stripe.iter_mut().zip(&share.data) {
+                        *out ^= mul(c, b);
+                    }
+                }
+                stripe
+            })
+            .collect()
+    };
+
+    let mut result = stripes.concat();
+    result.truncate(secret_len);
+    Ok(result)
+}
+
+/// Regenerate the share for `target_id` from any k surviving shares.
+///
+/// The shares carry no secret, so the data is decoded and the target row
+/// re-encoded.
+pub fn repair_share(shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+    let data = decode(shares)?;
+    let template = &shares[0];
+    check_repair_target(template, target_id)?;
+
+    let k = template.k as usize;
+    let stripe_len = data.len().div_ceil(k);
+    let coeffs = encoding_row((target_id - 1) as usize, k);
+    let mut payload = vec![0u8; stripe_len];
+    for (j, &c) in coeffs.iter().enumerate() {
+        if c == 0 {
+            continue;
+        }
+        let start = (j * stripe_len).min(data.len());
+        let end = ((j + 1) * stripe_len).min(data.len());
+        for (p, &b) in payload.iter_mut().zip(&data[start..end]) {
+            *p ^= mul(c, b);
+        }
+    }
+
+    Ok(Share {
+        id: target_id,
+        data: payload,
+        ..template.clone()
+    })
+}
+
+/// Row `row` of the n x k systematic encoding matrix: identity on top,
+/// Cauchy rows below.
+fn encoding_row(row: usize, k: usize) -> Vec<u8> {
+    if row < k {
+        let mut unit = vec![0u8; k];
+        unit[row] = 1;
+        unit
+    } else {
+        cauchy_row(row, k)
+    }
+}
+
+/// Cauchy matrix entries 1 / (x_row + y_j) with x_row = row and y_j = j.
+///
+/// Rows are >= k and columns < k, so the two sets are disjoint and every
+/// square submatrix of the stacked [I; C] matrix is invertible.
+fn cauchy_row(row: usize, k: usize) -> Vec<u8> {
+    (0..k).map(|j| div(1, (row ^ j) as u8)).collect()
+}
+
+/// Invert a square matrix over GF(2^8) with Gauss-Jordan elimination.
+fn invert(mut matrix: Vec<Vec<u8>>) -> Option<Vec<Vec<u8>>> {
+    let size = matrix.len();
+    let mut inverse: Vec<Vec<u8>> = (0..size)
+        .map(|i| {
+            let mut row = vec![0u8; size];
+            row[i] = 1;
+            row
+        })
+        .collect();
+
+    for col in 0..size {
+        let pivot = (col..size).find(|&r| matrix[r][col] != 0)?;
+        matrix.swap(col, pivot);
+        inverse.swap(col, pivot);
+
+        let scale = div(1, matrix[col][col]);
+        for c in 0..size {
+            matrix[col][c] = mul(matrix[col][c], scale);
+            inverse[col][c] = mul(inverse[col][c], scale);
+        }
+
+        for r in 0..size {
+            if r == col || matrix[r][col] == 0 {
+                continue;
+            }
+            let factor = matrix[r][col];
+            for c in 0..size {
+                matrix[r][c] ^= mul(factor, matrix[col][c]);
+                inverse[r][c] ^= mul(factor, inverse[col][c]);
+            }
+        }
+    }
+
+    Some(inverse)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_systematic_layout() {
+        let data = b"abcdefghij";
+        let shares = encode(data, 2, 4).unwrap();
+        assert_eq!(shares[0].data, b"abcde");
+        assert_eq!(shares[1].data, b"fghij");
+        assert!(shares.iter().all(|s| s.data.len() == 5));
+    }
+
+    #[test]
+    fn test_any_k_subset_decodes() {
+        let data: Vec<u8> = (0..1000).map(|i| (i * 31 % 251) as u8).collect();
+        let (k, n) = (3, 6);
+        let shares = encode(&data, k, n).unwrap();
+        for a in 0..n {
+            for b in (a + 1)..n {
+                for c in (b + 1)..n {
+                    let subset = [shares[a].clone(), shares[b].clone(), shares[c].clone()];
+                    assert_eq!(decode(&subset).unwrap(), data, "subset {a},{b},{c}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_parity_only_decode() {
+        let data = vec![0xFF; 77];
+        let shares = encode(&data, 4, 8).unwrap();
+        assert_eq!(decode(&shares[4..]).unwrap(), data);
+    }
+
+    #[test]
+    fn test_overhead_is_n_over_k() {
+        let data = vec![0x42; 600];
+        let shares = encode(&data, 4, 6).unwrap();
+        let total: usize = shares.iter().map(|s| s.data.len()).sum();
+        assert_eq!(total, 900); // 1.5x
+    }
+
+    #[test]
+    fn test_not_enough_shares() {
+        let shares = encode(b"erasure coded", 3, 5).unwrap();
+        assert!(matches!(
+            decode(&shares[..2]),
+            Err(ShamirError::NotEnoughShares { have: 2, need: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_single_data_share_allowed() {
+        // k = 1 is plain replication; only k = 0 is rejected
+        let shares = encode(b"replicated", 1, 3).unwrap();
+        assert_eq!(decode(&shares[2..]).unwrap(), b"replicated");
+        assert!(matches!(
+            encode(b"replicated", 0, 3),
+            Err(ShamirError::NoDataShares { k: 0 })
+        ));
+    }
+
+    #[test]
+    fn test_repair_share() {
+        let data: Vec<u8> = (0..97).collect();
+        let shares = encode(&data, 3, 5).unwrap();
+        for target in 1..=5 {
+            let survivors: Vec<Share> = shares
+                .iter()
+                .filter(|s| s.id != target)
+                .take(3)
+                .cloned()
+                .collect();
+            assert_eq!(repair_share(&survivors, target).unwrap(), shares[target as usize - 1]);
+        }
+    }
+
+    #[test]
+    fn test_header_n_beyond_field_rejected() {
+        let mut shares = encode(b"at most 255 rows", 2, 4).unwrap();
+        for share in &mut shares {
+            share.n = 300;
+        }
+        assert!(matches!(
+            decode(&shares[..2]),
+            Err(ShamirError::TooManyShares { n: 300, max: 255 })
+        ));
+        assert!(matches!(
+            repair_share(&shares[..2], 258),
+            Err(ShamirError::TooManyShares { n: 300, max: 255 })
+        ));
+    }
+
+    #[test]
+    fn test_invert_identity() {
+        let identity = vec![vec![1, 0], vec![0, 1]];
+        assert_eq!(invert(identity.clone()).unwrap(), identity);
+        assert!(invert(vec![vec![1, 1], vec![1, 1]]).is_none());
+    }
+}
@@ -0,0 +1,184 @@
+use gitgold_core::config::GitGoldConfig;
+use gitgold_core::error::ShamirError;
+use gitgold_core::types::ShareScheme;
+
+use crate::share::Share;
+use crate::{gf256, reed_solomon, shamir, vss};
+
+/// A k-of-n secret sharing engine.
+///
+/// Shares produced by an engine carry its `ShareScheme` in their header, so
+/// `reconstruct_any` can route a share set back to the engine that made it.
+pub trait SharingScheme: Send + Sync {
+    /// Scheme identifier recorded in share headers.
+    fn scheme(&self) -> ShareScheme;
+
+    /// Split a secret into `n` shares where any `k` can reconstruct.
+    fn split(&self, secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError>;
+
+    /// Reconstruct a secret from `k` or more shares of this scheme.
+    fn reconstruct(&self, shares: &[Share]) -> Result<Vec<u8>, ShamirError>;
+
+    /// Regenerate the share with id `target_id` from `k` or more shares.
+    fn repair(&self, shares: &[Share], target_id: u32) -> Result<Share, ShamirError>;
+}
+
+/// Shamir secret sharing over GF(2^256 - 189) (`shamir.rs`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PrimeFieldEngine;
+
+impl SharingScheme for PrimeFieldEngine {
+    fn scheme(&self) -> ShareScheme {
+        ShareScheme::Shamir256
+    }
+
+    fn split(&self, secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError> {
+        shamir::split(secret, k, n)
+    }
+
+    fn reconstruct(&self, shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+        shamir::reconstruct(shares)
+    }
+
+    fn repair(&self, shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+        shamir::repair_share(shares, target_id)
+    }
+}
+
+/// Byte-wise Shamir secret sharing over GF(2^8) (`gf256.rs`).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gf256Engine;
+
+impl SharingScheme for Gf256Engine {
+    fn scheme(&self) -> ShareScheme {
+        ShareScheme::Gf256
+    }
+
+    fn split(&self, secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError> {
+        gf256::split(secret, k, n)
+    }
+
+    fn reconstruct(&self, shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+        gf256::reconstruct(shares)
+    }
+
+    fn repair(&self, shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+        gf256::repair_share(shares, target_id)
+    }
+}
+
+/// Systematic Reed-Solomon erasure coding (`reed_solomon.rs`).
+///
+/// Trades confidentiality for n/k storage overhead; intended for public
+/// repositories.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ReedSolomonEngine;
+
+impl SharingScheme for ReedSolomonEngine {
+    fn scheme(&self) -> ShareScheme {
+        ShareScheme::ReedSolomon
+    }
+
+    fn split(&self, secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError> {
+        reed_solomon::encode(secret, k, n)
+    }
+
+    fn reconstruct(&self, shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+        reed_solomon::decode(shares)
+    }
+
+    fn repair(&self, shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+        reed_solomon::repair_share(shares, target_id)
+    }
+}
+
+/// Verifiable Shamir secret sharing with Pedersen commitments (`vss.rs`).
+///
+/// `split` discards the commitments; callers that publish them for share
+/// verification use `vss::split` directly.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PedersenVssEngine;
+
+impl SharingScheme for PedersenVssEngine {
+    fn scheme(&self) -> ShareScheme {
+        ShareScheme::PedersenVss
+    }
+
+    fn split(&self, secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError> {
+        vss::split(secret, k, n).map(|(shares, _)| shares)
+    }
+
+    fn reconstruct(&self, shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+        vss::reconstruct(shares)
+    }
+
+    fn repair(&self, shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+        vss::repair_share(shares, target_id)
+    }
+}
+
+/// Get the engine implementing `scheme`.
+pub fn engine(scheme: ShareScheme) -> &'static dyn SharingScheme {
+    match scheme {
+        ShareScheme::Shamir256 => &PrimeFieldEngine,
+        ShareScheme::Gf256 => &Gf256Engine,
+        ShareScheme::ReedSolomon => &ReedSolomonEngine,
+        ShareScheme::PedersenVss => &PedersenVssEngine,
+    }
+}
+
+/// Split a secret with the engine and (k, n) selected in `config`.
+pub fn split_with_config(secret: &[u8], config: &GitGoldConfig) -> Result<Vec<Share>, ShamirError> {
+    engine(config.share_scheme).split(secret, config.k, config.n)
+}
+
+/// Reconstruct a secret with whichever engine produced the shares.
+pub fn reconstruct_any(shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+    let Some(first) = shares.first() else {
+        return Err(ShamirError::NotEnoughShares { have: 0, need: 1 });
+    };
+    engine(first.scheme).reconstruct(shares)
+}
+
+/// Regenerate a lost share with whichever engine produced the shares.
+pub fn repair_any(shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+    let Some(first) = shares.first() else {
+        return Err(ShamirError::NotEnoughShares { have: 0, need: 1 });
+    };
+    engine(first.scheme).repair(shares, target_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_engines_roundtrip() {
+        let secret = b"same secret, either engine";
+        for scheme in [
+            ShareScheme::Shamir256,
+            ShareScheme::Gf256,
+            ShareScheme::ReedSolomon,
+            ShareScheme::PedersenVss,
+        ] {
+            let shares = engine(scheme).split(secret, 3, 5).unwrap();
+            assert!(shares.iter().all(|s| s.scheme == scheme));
+            assert_eq!(reconstruct_any(&shares[1..4]).unwrap(), secret);
+            assert_eq!(repair_any(&shares[2..], 1).unwrap(), shares[0]);
+        }
+    }
+
+    #[test]
+    fn test_config_selects_engine() {
+        let config = GitGoldConfig {
+            share_scheme: ShareScheme::Gf256,
+            ..GitGoldConfig::default()
+        };
+        let secret = vec![0x5A; 100];
+        let shares = split_with_config(&secret, &config).unwrap();
+        assert_eq!(shares.len(), config.n);
+        assert_eq!(shares[0].scheme, ShareScheme::Gf256);
+        assert_eq!(shares[0].data.len(), secret.len());
+        assert_eq!(reconstruct_any(&shares).unwrap(), secret);
+    }
+}
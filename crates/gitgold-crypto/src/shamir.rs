@@ -1,28 +1,98 @@
 use gitgold_core::error::ShamirError;
-use num_bigint::BigUint;
-use num_bigint::RandBigInt;
-use rand::thread_rng;
-use serde::{Deserialize, Serialize};
+use gitgold_core::types::ShareScheme;
+use rand::{thread_rng, CryptoRng, RngCore, SeedableRng};
+use rand_chacha::ChaCha20Rng;
+use rayon::prelude::*;
 
 use crate::field::FieldElement;
+pub use crate::share::Share;
+use crate::share::validate_share_set;
 
-/// A single share from Shamir secret sharing.
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Share {
-    /// Share identifier (1-indexed, used as the x-coordinate).
-    pub id: u32,
-    /// Share data (32 bytes per block, concatenated for multi-block secrets).
-    pub data: Vec<u8>,
-}
-
-/// Block size for chunking secrets into field elements (32 bytes < p).
+/// Size of one encoded field element in share data.
 const BLOCK_SIZE: usize = 32;
 
+/// Secret bytes carried per block. One byte short of `BLOCK_SIZE` so that a
+/// block can never reach the modulus and be silently reduced.
+const PAYLOAD_SIZE: usize = 31;
+
+/// Blocks handled by one task in `split_parallel` and
+/// `reconstruct_parallel` (about 16 KB of secret).
+const PARALLEL_SEGMENT_BLOCKS: usize = 512;
+
 /// Split a secret into `n` shares where any `k` can reconstruct.
 ///
-/// Secrets larger than 32 bytes are split into 32-byte blocks, each
-/// shared independently. The last block is zero-padded.
+/// The secret is packed into 31-byte blocks, each shared independently and
+/// stored as a 32-byte field element. The last block is zero-padded; the
+/// original length is recorded in every share header.
 pub fn split(secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError> {
+    split_with_rng(secret, k, n, &mut thread_rng())
+}
+
+/// `split`, drawing the polynomial coefficients from `rng`.
+///
+/// With a seeded generator the output is fully reproducible: for each block
+/// in order, coefficients a_1 .. a_{k-1} are drawn with
+/// `FieldElement::random`.
+pub fn split_with_rng<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>, ShamirError> {
+    check_split_params(secret, k, n)?;
+    let data = split_blocks(secret, k, n, rng);
+    Ok(build_shares(secret.len(), k, data))
+}
+
+/// `split`, with the blocks spread across the rayon thread pool.
+///
+/// Blocks are processed in segments of `PARALLEL_SEGMENT_BLOCKS` and the
+/// segments are concatenated in order, so share layout is identical to
+/// `split`.
+pub fn split_parallel(secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirError> {
+    split_parallel_with_rng(secret, k, n, &mut thread_rng())
+}
+
+/// `split_parallel`, drawing the polynomial coefficients from `rng`.
+///
+/// Each segment gets its own ChaCha20 generator, seeded with 32 bytes drawn
+/// from `rng` in segment order before any work starts, so a seeded `rng`
+/// gives the same shares however the segments are scheduled. The shares
+/// differ from those `split_with_rng` draws from the same seed.
+pub fn split_parallel_with_rng<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>, ShamirError> {
+    check_split_params(secret, k, n)?;
+    let segment_len = PAYLOAD_SIZE * PARALLEL_SEGMENT_BLOCKS;
+    let seeds: Vec<[u8; 32]> = (0..secret.len().div_ceil(segment_len))
+        .map(|_| {
+            let mut seed = [0u8; 32];
+            rng.fill_bytes(&mut seed);
+            seed
+        })
+        .collect();
+    let segments: Vec<Vec<Vec<u8>>> = secret
+        .par_chunks(segment_len)
+        .zip(seeds)
+        .map(|(segment, seed)| split_blocks(segment, k, n, &mut ChaCha20Rng::from_seed(seed)))
+        .collect();
+
+    let num_blocks = secret.len().div_ceil(PAYLOAD_SIZE);
+    let mut data: Vec<Vec<u8>> = (0..n)
+        .map(|_| Vec::with_capacity(num_blocks * BLOCK_SIZE))
+        .collect();
+    for segment in segments {
+        for (share_data, part) in data.iter_mut().zip(segment) {
+            share_data.extend_from_slice(&part);
+        }
+    }
+    Ok(build_shares(secret.len(), k, data))
+}
+
+fn check_split_params(secret: &[u8], k: usize, n: usize) -> Result<(), ShamirError> {
     if secret.is_empty() {
         return Err(ShamirError::EmptySecret);
     }
@@ -32,146 +102,673 @@ pub fn split(secret: &[u8], k: usize, n: usize) -> Result<Vec<Share>, ShamirErro
     if n < k {
         return Err(ShamirError::InsufficientShares { k, n });
     }
+    Ok(())
+}
 
-    // Pad secret to multiple of BLOCK_SIZE
-    let mut padded = secret.to_vec();
-    if !padded.len().is_multiple_of(BLOCK_SIZE) {
-        padded.resize(padded.len() + (BLOCK_SIZE - padded.len() % BLOCK_SIZE), 0);
-    }
-    let num_blocks = padded.len() / BLOCK_SIZE;
-
-    // Initialize shares
-    let mut shares: Vec<Share> = (1..=n as u32)
-        .map(|id| Share {
+/// Wrap per-share data (index i holds share id i + 1) in share headers.
+fn build_shares(secret_len: usize, k: usize, data: Vec<Vec<u8>>) -> Vec<Share> {
+    let n = data.len();
+    data.into_iter()
+        .zip(1..)
+        .map(|(data, id)| Share {
             id,
-            data: Vec::with_capacity(num_blocks * BLOCK_SIZE),
+            scheme: ShareScheme::Shamir256,
+            k: k as u32,
+            n: n as u32,
+            secret_len: secret_len as u64,
+            chunk_index: 0,
+            repo_hash: [0u8; 32],
+            data,
         })
+        .collect()
+}
+
+/// Share every 31-byte block of `secret`, returning the data of each of the
+/// `n` shares.
+fn split_blocks<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Vec<Vec<u8>> {
+    let num_blocks = secret.len().div_ceil(PAYLOAD_SIZE);
+    let mut shares: Vec<Vec<u8>> = (0..n)
+        .map(|_| Vec::with_capacity(num_blocks * BLOCK_SIZE))
         .collect();
 
-    let mut rng = thread_rng();
+    let xs: Vec<FieldElement> = (1..=n as u64).map(FieldElement::from_u64).collect();
+    let mut coeffs: Vec<FieldElement> = Vec::with_capacity(k);
 
-    for block_idx in 0..num_blocks {
-        let block_start = block_idx * BLOCK_SIZE;
-        let block = &padded[block_start..block_start + BLOCK_SIZE];
-        let secret_elem = FieldElement::from_bytes_be(block);
-
-        // Generate random coefficients a_1 .. a_{k-1}
-        let p = FieldElement::zero(); // just to get the modulus
-        let modulus = num_bigint::BigUint::from_bytes_be(&{
-            // p = 2^256 - 189
-            let two = BigUint::from(2u32);
-            let p_val = two.pow(256) - BigUint::from(189u32);
-            p_val.to_bytes_be()
-        });
-        let _ = p;
+    for block in secret.chunks(PAYLOAD_SIZE) {
+        // Short final block is zero-padded on the right
+        let mut padded = [0u8; PAYLOAD_SIZE];
+        padded[..block.len()].copy_from_slice(block);
+        let secret_elem = FieldElement::from_bytes_be(&padded);
 
-        let mut coeffs: Vec<FieldElement> = Vec::with_capacity(k);
-        coeffs.push(secret_elem); // a_0 = secret
+        // a_0 = secret, random coefficients a_1 .. a_{k-1}
+        coeffs.clear();
+        coeffs.push(secret_elem);
         for _ in 1..k {
-            let rand_val = rng.gen_biguint_below(&modulus);
-            coeffs.push(FieldElement::new(rand_val));
+            coeffs.push(FieldElement::random(rng));
         }
 
         // Evaluate polynomial at x = 1, 2, ..., n using Horner's method
-        for share in shares.iter_mut() {
-            let x = FieldElement::from_u64(share.id as u64);
-            let y = eval_poly(&coeffs, &x);
-            share.data.extend_from_slice(&y.to_bytes_be());
+        for (share, x) in shares.iter_mut().zip(&xs) {
+            let y = eval_poly(&coeffs, x);
+            share.extend_from_slice(&y.to_array_be());
         }
     }
 
-    Ok(shares)
+    shares
 }
 
 /// Reconstruct a secret from `k` or more shares.
 ///
-/// The original secret length must be known; excess zero-padding is included
-/// in the output (caller should truncate to original length).
-pub fn reconstruct(shares: &[Share], k: usize) -> Result<Vec<u8>, ShamirError> {
-    if shares.len() < k {
-        return Err(ShamirError::NotEnoughShares {
-            have: shares.len(),
-            need: k,
+/// The threshold and secret length are taken from the share headers; all
+/// shares must belong to the same split. Returns the secret byte-for-byte.
+pub fn reconstruct(shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+    let (selected, basis) = prepare_reconstruct(shares)?;
+    let num_blocks = selected[0].data.len() / BLOCK_SIZE;
+
+    let mut result = interpolate_blocks(selected, &basis, 0..num_blocks)?;
+    result.truncate(selected[0].secret_len as usize);
+    Ok(result)
+}
+
+/// `reconstruct`, with the blocks spread across the rayon thread pool.
+pub fn reconstruct_parallel(shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+    let (selected, basis) = prepare_reconstruct(shares)?;
+    let num_blocks = selected[0].data.len() / BLOCK_SIZE;
+
+    let segments: Vec<Vec<u8>> = (0..num_blocks.div_ceil(PARALLEL_SEGMENT_BLOCKS))
+        .into_par_iter()
+        .map(|segment| {
+            let start = segment * PARALLEL_SEGMENT_BLOCKS;
+            let end = (start + PARALLEL_SEGMENT_BLOCKS).min(num_blocks);
+            interpolate_blocks(selected, &basis, start..end)
+        })
+        .collect::<Result<_, _>>()?;
+
+    let mut result = segments.concat();
+    result.truncate(selected[0].secret_len as usize);
+    Ok(result)
+}
+
+/// Validate a share set and select the k shares to interpolate, together
+/// with their Lagrange basis at x = 0.
+fn prepare_reconstruct(shares: &[Share]) -> Result<(&[Share], Vec<FieldElement>), ShamirError> {
+    let k = validate_share_set(shares, ShareScheme::Shamir256)?;
+
+    // Use exactly k shares
+    let selected = &shares[..k];
+    block_count(&selected[0])?;
+
+    // The Lagrange basis at x = 0 depends only on the share ids, so it is
+    // computed once and reused for every block.
+    let xs: Vec<FieldElement> = selected
+        .iter()
+        .map(|s| FieldElement::from_u64(s.id as u64))
+        .collect();
+    let basis = lagrange_basis_at(&xs, &FieldElement::zero());
+    Ok((selected, basis))
+}
+
+/// Recover the payload of the given blocks by interpolation at x = 0.
+fn interpolate_blocks(
+    selected: &[Share],
+    basis: &[FieldElement],
+    blocks: std::ops::Range<usize>,
+) -> Result<Vec<u8>, ShamirError> {
+    let mut result = Vec::with_capacity(blocks.len() * PAYLOAD_SIZE);
+
+    for block_idx in blocks {
+        let offset = block_idx * BLOCK_SIZE;
+
+        // Lagrange interpolation at x = 0: S = sum y_i * L_i(0)
+        let mut secret_elem = FieldElement::zero();
+        for (share, coeff) in selected.iter().zip(basis) {
+            let y = FieldElement::from_bytes_be(&share.data[offset..offset + BLOCK_SIZE]);
+            secret_elem = secret_elem + y * *coeff;
+        }
+        push_payload(&mut result, &secret_elem, block_idx)?;
+    }
+
+    Ok(result)
+}
+
+/// Number of blocks in a share, checking its data holds exactly the blocks
+/// its `secret_len` calls for. The header is untrusted (its checksum can be
+/// recomputed by anyone), so the size arithmetic is checked.
+fn block_count(share: &Share) -> Result<usize, ShamirError> {
+    let num_blocks = usize::try_from(share.secret_len)
+        .ok()
+        .map(|len| len.div_ceil(PAYLOAD_SIZE));
+    match num_blocks {
+        Some(blocks) if blocks.checked_mul(BLOCK_SIZE) == Some(share.data.len()) => Ok(blocks),
+        _ => Err(ShamirError::MalformedShare {
+            id: share.id,
+            reason: format!(
+                "data length {} does not match secret length {}",
+                share.data.len(),
+                share.secret_len
+            ),
+        }),
+    }
+}
+
+/// Proactively refresh a share set without reconstructing the secret.
+///
+/// Every share is re-randomised with `refresh_updates`, so the new set encodes
+/// the same secret while old and new shares lie on different polynomials and
+/// cannot be combined. All shares that remain in use must be refreshed
+/// together; a share left out is useless afterwards.
+pub fn refresh(shares: &[Share]) -> Result<Vec<Share>, ShamirError> {
+    refresh_with_rng(shares, &mut thread_rng())
+}
+
+/// `refresh`, drawing the zero-secret polynomials from `rng`.
+pub fn refresh_with_rng<R: RngCore + CryptoRng>(
+    shares: &[Share],
+    rng: &mut R,
+) -> Result<Vec<Share>, ShamirError> {
+    validate_share_set(shares, ShareScheme::Shamir256)?;
+    let first = &shares[0];
+    block_count(first)?;
+    let updates = refresh_updates_with_rng(
+        first.k as usize,
+        first.n as usize,
+        first.secret_len as usize,
+        rng,
+    )?;
+
+    shares
+        .iter()
+        .map(|share| {
+            let update = updates
+                .iter()
+                .find(|u| u.id == share.id)
+                .ok_or_else(|| ShamirError::MalformedShare {
+                    id: share.id,
+                    reason: format!("id outside 1..={}", first.n),
+                })?;
+            apply_refresh(share, update)
+        })
+        .collect()
+}
+
+/// Generate one refresh round: shares of the all-zero secret, one per id.
+///
+/// Each block gets a fresh random polynomial with zero constant term. Holders
+/// add their update to their share with `apply_refresh`, so the refresh can
+/// be distributed without any party seeing the secret.
+pub fn refresh_updates(k: usize, n: usize, secret_len: usize) -> Result<Vec<Share>, ShamirError> {
+    refresh_updates_with_rng(k, n, secret_len, &mut thread_rng())
+}
+
+/// `refresh_updates`, drawing the polynomials from `rng`.
+pub fn refresh_updates_with_rng<R: RngCore + CryptoRng>(
+    k: usize,
+    n: usize,
+    secret_len: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>, ShamirError> {
+    split_with_rng(&vec![0u8; secret_len], k, n, rng)
+}
+
+/// Add a refresh update to a share, keeping its header.
+pub fn apply_refresh(share: &Share, update: &Share) -> Result<Share, ShamirError> {
+    let mismatch = if update.scheme != share.scheme {
+        Some("scheme")
+    } else if update.id != share.id {
+        Some("id")
+    } else if update.k != share.k {
+        Some("k")
+    } else if update.n != share.n {
+        Some("n")
+    } else if update.secret_len != share.secret_len {
+        Some("secret_len")
+    } else if update.data.len() != share.data.len() {
+        Some("data length")
+    } else {
+        None
+    };
+    if let Some(field) = mismatch {
+        return Err(ShamirError::IncompatibleShare {
+            id: update.id,
+            field,
         });
     }
 
-    // Check for duplicate IDs
-    let mut seen = std::collections::HashSet::new();
-    for share in shares.iter().take(k) {
-        if !seen.insert(share.id) {
-            return Err(ShamirError::DuplicateShareId(share.id));
+    let mut data = Vec::with_capacity(share.data.len());
+    for (y, delta) in share
+        .data
+        .chunks_exact(BLOCK_SIZE)
+        .zip(update.data.chunks_exact(BLOCK_SIZE))
+    {
+        let sum = FieldElement::from_bytes_be(y) + FieldElement::from_bytes_be(delta);
+        data.extend_from_slice(&sum.to_array_be());
+    }
+
+    Ok(Share {
+        data,
+        ..share.clone()
+    })
+}
+
+/// Move a secret from its current (k, n) share set to a new (k', n') set
+/// without reconstructing it.
+///
+/// Each of k old shares is itself split with a fresh random polynomial of
+/// degree k' - 1 (a sub-share per new id); new share j is the Lagrange-weighted
+/// sum of the sub-shares for j. The combined polynomial has the same constant
+/// term, so the new set encodes the same secret, yet no step ever computes
+/// that constant term. Old and new shares cannot be mixed.
+pub fn reshare(shares: &[Share], new_k: usize, new_n: usize) -> Result<Vec<Share>, ShamirError> {
+    reshare_with_rng(shares, new_k, new_n, &mut thread_rng())
+}
+
+/// `reshare`, drawing the sub-sharing polynomials from `rng`.
+pub fn reshare_with_rng<R: RngCore + CryptoRng>(
+    shares: &[Share],
+    new_k: usize,
+    new_n: usize,
+    rng: &mut R,
+) -> Result<Vec<Share>, ShamirError> {
+    if new_k < 2 {
+        return Err(ShamirError::ThresholdTooLow { k: new_k });
+    }
+    if new_n < new_k {
+        return Err(ShamirError::InsufficientShares { k: new_k, n: new_n });
+    }
+    let k = validate_share_set(shares, ShareScheme::Shamir256)?;
+    let selected = &shares[..k];
+    let num_blocks = block_count(&selected[0])?;
+
+    let old_xs: Vec<FieldElement> = selected
+        .iter()
+        .map(|s| FieldElement::from_u64(s.id as u64))
+        .collect();
+    let weights = lagrange_basis_at(&old_xs, &FieldElement::zero());
+    let new_xs: Vec<FieldElement> = (1..=new_n as u64).map(FieldElement::from_u64).collect();
+
+    let mut new_shares: Vec<Share> = (1..=new_n as u32)
+        .map(|id| Share {
+            id,
+            k: new_k as u32,
+            n: new_n as u32,
+            data: Vec::with_capacity(num_blocks * BLOCK_SIZE),
+            ..selected[0].clone()
+        })
+        .collect();
+
+    let mut coeffs: Vec<FieldElement> = Vec::with_capacity(new_k);
+    let mut block = vec![FieldElement::zero(); new_n];
+
+    for block_idx in 0..num_blocks {
+        let offset = block_idx * BLOCK_SIZE;
+        block.fill(FieldElement::zero());
+
+        for (share, weight) in selected.iter().zip(&weights) {
+            // Sub-share this old share's value with a fresh polynomial
+            coeffs.clear();
+            coeffs.push(FieldElement::from_bytes_be(&share.data[offset..offset + BLOCK_SIZE]));
+            for _ in 1..new_k {
+                coeffs.push(FieldElement::random(rng));
+            }
+            for (acc, x) in block.iter_mut().zip(&new_xs) {
+                *acc = *acc + eval_poly(&coeffs, x) * *weight;
+            }
+        }
+
+        for (share, y) in new_shares.iter_mut().zip(&block) {
+            share.data.extend_from_slice(&y.to_array_be());
         }
     }
 
-    // Use exactly k shares
+    Ok(new_shares)
+}
+
+/// Regenerate the share for `target_id` from k surviving shares, without
+/// reconstructing the secret.
+///
+/// Each block is Lagrange-interpolated at x = `target_id` instead of x = 0,
+/// which yields exactly the share the original split produced for that id.
+pub fn repair_share(shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+    let k = validate_share_set(shares, ShareScheme::Shamir256)?;
     let selected = &shares[..k];
-    let num_blocks = selected[0].data.len() / BLOCK_SIZE;
+    check_repair_target(&selected[0], target_id)?;
+    let num_blocks = block_count(&selected[0])?;
 
-    let mut result = Vec::with_capacity(num_blocks * BLOCK_SIZE);
+    let xs: Vec<FieldElement> = selected
+        .iter()
+        .map(|s| FieldElement::from_u64(s.id as u64))
+        .collect();
+    let basis = lagrange_basis_at(&xs, &FieldElement::from_u64(target_id as u64));
 
+    let mut data = Vec::with_capacity(num_blocks * BLOCK_SIZE);
     for block_idx in 0..num_blocks {
         let offset = block_idx * BLOCK_SIZE;
+        let mut y = FieldElement::zero();
+        for (share, coeff) in selected.iter().zip(&basis) {
+            y = y + FieldElement::from_bytes_be(&share.data[offset..offset + BLOCK_SIZE]) * *coeff;
+        }
+        data.extend_from_slice(&y.to_array_be());
+    }
 
-        // Collect (x_i, y_i) points for this block
-        let points: Vec<(FieldElement, FieldElement)> = selected
-            .iter()
-            .map(|s| {
-                let x = FieldElement::from_u64(s.id as u64);
-                let y = FieldElement::from_bytes_be(&s.data[offset..offset + BLOCK_SIZE]);
-                (x, y)
-            })
-            .collect();
+    Ok(Share {
+        id: target_id,
+        data,
+        ..selected[0].clone()
+    })
+}
 
-        // Lagrange interpolation at x = 0
-        let secret_elem = lagrange_interpolate_at_zero(&points);
-        result.extend_from_slice(&secret_elem.to_bytes_be());
+/// Check that `target_id` is a valid share id for the split `template`
+/// belongs to.
+pub(crate) fn check_repair_target(template: &Share, target_id: u32) -> Result<(), ShamirError> {
+    if target_id == 0 || target_id > template.n {
+        return Err(ShamirError::MalformedShare {
+            id: target_id,
+            reason: format!("repair target outside 1..={}", template.n),
+        });
     }
+    Ok(())
+}
 
-    Ok(result)
+/// Outcome of `reconstruct_robust`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RobustReconstruction {
+    /// The recovered secret.
+    pub secret: Vec<u8>,
+    /// Ids of shares that disagreed with the decoded polynomial in at least
+    /// one block, ascending. Callers can penalise the nodes that served them.
+    pub inconsistent_ids: Vec<u32>,
 }
 
-/// Evaluate polynomial with coefficients `coeffs` at point `x` using Horner's method.
-/// coeffs[0] is the constant term, coeffs[k-1] is the highest degree.
-fn eval_poly(coeffs: &[FieldElement], x: &FieldElement) -> FieldElement {
-    let mut result = FieldElement::zero();
-    for coeff in coeffs.iter().rev() {
-        result = &(&result * x) + coeff;
+/// Reconstruct a secret from `k` or more shares, using any extra shares to
+/// detect and correct corrupted ones.
+///
+/// Each block is first interpolated from k trusted shares and checked against
+/// every other share. Only blocks that fail the check are decoded with
+/// Berlekamp-Welch, which tolerates up to `(m - k) / 2` bad shares per block
+/// for `m` supplied shares; shares found bad are dropped from the fast path
+/// for the remaining blocks. With exactly k shares this behaves like
+/// `reconstruct` and can detect nothing.
+pub fn reconstruct_robust(shares: &[Share]) -> Result<RobustReconstruction, ShamirError> {
+    let k = validate_share_set(shares, ShareScheme::Shamir256)?;
+    let num_blocks = block_count(&shares[0])?;
+    let secret_len = shares[0].secret_len as usize;
+
+    let max_errors = (shares.len() - k) / 2;
+    let xs: Vec<FieldElement> = shares
+        .iter()
+        .map(|s| FieldElement::from_u64(s.id as u64))
+        .collect();
+    let mut inconsistent = vec![false; shares.len()];
+    let mut fast_path = ConsistencyCheck::new(&xs, &inconsistent, k);
+
+    let mut result = Vec::with_capacity(num_blocks * PAYLOAD_SIZE);
+    let mut ys = Vec::with_capacity(shares.len());
+
+    for block_idx in 0..num_blocks {
+        let offset = block_idx * BLOCK_SIZE;
+        ys.clear();
+        ys.extend(
+            shares
+                .iter()
+                .map(|s| FieldElement::from_bytes_be(&s.data[offset..offset + BLOCK_SIZE])),
+        );
+
+        let secret_elem = match fast_path.as_ref().and_then(|check| check.decode(&ys)) {
+            Some(value) => value,
+            None => {
+                let (poly, errors) = berlekamp_welch(&xs, &ys, k, max_errors).ok_or(
+                    ShamirError::TooManyCorruptShares {
+                        block: block_idx,
+                        max_errors,
+                    },
+                )?;
+                let mut newly_found = false;
+                for i in errors {
+                    newly_found |= !inconsistent[i];
+                    inconsistent[i] = true;
+                }
+                if newly_found {
+                    fast_path = ConsistencyCheck::new(&xs, &inconsistent, k);
+                }
+                poly[0]
+            }
+        };
+        push_payload(&mut result, &secret_elem, block_idx)?;
+    }
+
+    result.truncate(secret_len);
+    let mut inconsistent_ids: Vec<u32> = shares
+        .iter()
+        .zip(&inconsistent)
+        .filter(|(_, &bad)| bad)
+        .map(|(s, _)| s.id)
+        .collect();
+    inconsistent_ids.sort_unstable();
+
+    Ok(RobustReconstruction {
+        secret: result,
+        inconsistent_ids,
+    })
+}
+
+/// Append a reconstructed block's payload, rejecting values outside the
+/// 31-byte range.
+fn push_payload(
+    out: &mut Vec<u8>,
+    value: &FieldElement,
+    block_idx: usize,
+) -> Result<(), ShamirError> {
+    let bytes = value.to_array_be();
+    if bytes[0] != 0 {
+        return Err(ShamirError::MalformedSecret(format!(
+            "block {block_idx} exceeds the {PAYLOAD_SIZE}-byte payload range"
+        )));
+    }
+    out.extend_from_slice(&bytes[1..]);
+    Ok(())
+}
+
+/// Interpolates from k base shares and checks every other trusted share
+/// against the result.
+struct ConsistencyCheck {
+    base: Vec<usize>,
+    at_zero: Vec<FieldElement>,
+    /// (share index, Lagrange basis of the base shares at that share's x)
+    others: Vec<(usize, Vec<FieldElement>)>,
+}
+
+impl ConsistencyCheck {
+    /// Build a check over the shares not flagged in `excluded`, or `None` if
+    /// fewer than k remain.
+    fn new(xs: &[FieldElement], excluded: &[bool], k: usize) -> Option<Self> {
+        let trusted: Vec<usize> = (0..xs.len()).filter(|&i| !excluded[i]).collect();
+        if trusted.len() < k {
+            return None;
+        }
+        let (base, rest) = trusted.split_at(k);
+        let base_xs: Vec<FieldElement> = base.iter().map(|&i| xs[i]).collect();
+
+        Some(Self {
+            base: base.to_vec(),
+            at_zero: lagrange_basis_at(&base_xs, &FieldElement::zero()),
+            others: rest
+                .iter()
+                .map(|&j| (j, lagrange_basis_at(&base_xs, &xs[j])))
+                .collect(),
+        })
+    }
+
+    /// Return f(0) if every trusted share lies on the base polynomial.
+    fn decode(&self, ys: &[FieldElement]) -> Option<FieldElement> {
+        let interpolate = |basis: &[FieldElement]| {
+            self.base
+                .iter()
+                .zip(basis)
+                .fold(FieldElement::zero(), |acc, (&i, b)| acc + ys[i] * *b)
+        };
+        if self
+            .others
+            .iter()
+            .all(|(j, basis)| interpolate(basis) == ys[*j])
+        {
+            Some(interpolate(&self.at_zero))
+        } else {
+            None
+        }
     }
-    result
 }
 
-/// Lagrange interpolation at x=0 to recover the secret.
-fn lagrange_interpolate_at_zero(points: &[(FieldElement, FieldElement)]) -> FieldElement {
-    let k = points.len();
-    let mut secret = FieldElement::zero();
+/// Berlekamp-Welch decoding: find the polynomial P of degree < k that agrees
+/// with all but at most `max_errors` of the points (xs, ys).
+///
+/// Solves Q(x_i) = y_i * E(x_i) for Q of degree < k + e and monic E of degree
+/// e = `max_errors`, then P = Q / E. Returns P's coefficients and the indices
+/// of the points P disagrees with.
+fn berlekamp_welch(
+    xs: &[FieldElement],
+    ys: &[FieldElement],
+    k: usize,
+    max_errors: usize,
+) -> Option<(Vec<FieldElement>, Vec<usize>)> {
+    let e = max_errors;
+    // Unknowns: q_0 .. q_{k+e-1}, then e_0 .. e_{e-1}; last column is the RHS
+    let cols = k + 2 * e;
+    let mut rows: Vec<Vec<FieldElement>> = xs
+        .iter()
+        .zip(ys)
+        .map(|(x, y)| {
+            let mut powers = Vec::with_capacity(k + e);
+            let mut power = FieldElement::one();
+            for _ in 0..k + e {
+                powers.push(power);
+                power = power * *x;
+            }
+            let mut row = Vec::with_capacity(cols + 1);
+            row.extend_from_slice(&powers);
+            row.extend(powers[..e].iter().map(|p| FieldElement::zero() - *y * *p));
+            row.push(*y * powers[e]);
+            row
+        })
+        .collect();
 
-    for i in 0..k {
-        let (ref xi, ref yi) = points[i];
-        let mut numerator = FieldElement::one();
-        let mut denominator = FieldElement::one();
+    let solution = solve_linear(&mut rows, cols)?;
+    let (q, e_low) = solution.split_at(k + e);
+    let mut error_locator = e_low.to_vec();
+    error_locator.push(FieldElement::one());
 
-        for (j, (ref xj, _)) in points.iter().enumerate() {
-            if i == j {
+    let (poly, remainder) = divide_monic(q, &error_locator);
+    if remainder.iter().any(|c| !c.is_zero()) {
+        return None;
+    }
+    let errors: Vec<usize> = (0..xs.len())
+        .filter(|&i| eval_poly(&poly, &xs[i]) != ys[i])
+        .collect();
+    if errors.len() > e {
+        return None;
+    }
+    Some((poly, errors))
+}
+
+/// Solve an augmented linear system by Gauss-Jordan elimination, setting
+/// free variables to zero. Returns `None` if the system is inconsistent.
+fn solve_linear(rows: &mut [Vec<FieldElement>], cols: usize) -> Option<Vec<FieldElement>> {
+    let mut pivot_cols = Vec::with_capacity(cols);
+    let mut r = 0;
+    for c in 0..cols {
+        if r == rows.len() {
+            break;
+        }
+        let Some(p) = (r..rows.len()).find(|&i| !rows[i][c].is_zero()) else {
+            continue;
+        };
+        rows.swap(r, p);
+
+        let inv = rows[r][c].inv();
+        for v in rows[r][c..].iter_mut() {
+            *v = *v * inv;
+        }
+        let pivot_row = rows[r].clone();
+        for (i, row) in rows.iter_mut().enumerate() {
+            if i == r || row[c].is_zero() {
                 continue;
             }
-            // numerator *= x_j (evaluating at x=0, so (0 - x_j) = -x_j, but
-            // the sign cancels in num/denom, so we use x_j directly with the
-            // standard formula: L_i(0) = prod_{j!=i} (0 - x_j) / (x_i - x_j)
-            //                           = prod_{j!=i} x_j / (x_j - x_i)
-            numerator = &numerator * xj;
-            denominator = &denominator * &(xj - xi);
+            let factor = row[c];
+            for (v, pv) in row[c..].iter_mut().zip(&pivot_row[c..]) {
+                *v = *v - factor * *pv;
+            }
+        }
+        pivot_cols.push(c);
+        r += 1;
+    }
+
+    // Remaining rows have all-zero coefficients
+    if rows[r..].iter().any(|row| !row[cols].is_zero()) {
+        return None;
+    }
+    let mut solution = vec![FieldElement::zero(); cols];
+    for (row, &c) in rows.iter().zip(&pivot_cols) {
+        solution[c] = row[cols];
+    }
+    Some(solution)
+}
+
+/// Divide `num` by the monic polynomial `den`, returning (quotient, remainder).
+fn divide_monic(num: &[FieldElement], den: &[FieldElement]) -> (Vec<FieldElement>, Vec<FieldElement>) {
+    let degree = den.len() - 1;
+    let mut rem = num.to_vec();
+    let mut quot = vec![FieldElement::zero(); num.len().saturating_sub(degree)];
+    for i in (0..quot.len()).rev() {
+        let coeff = rem[i + degree];
+        quot[i] = coeff;
+        for (j, d) in den.iter().enumerate() {
+            rem[i + j] = rem[i + j] - coeff * *d;
         }
+    }
+    rem.truncate(degree);
+    (quot, rem)
+}
 
-        let lagrange_coeff = &(yi * &numerator) / &denominator;
-        secret = &secret + &lagrange_coeff;
+/// Evaluate polynomial with coefficients `coeffs` at point `x` using Horner's method.
+/// coeffs[0] is the constant term, coeffs[k-1] is the highest degree.
+fn eval_poly(coeffs: &[FieldElement], x: &FieldElement) -> FieldElement {
+    let mut result = FieldElement::zero();
+    for coeff in coeffs.iter().rev() {
+        result = result * *x + *coeff;
     }
+    result
+}
 
-    secret
+/// Lagrange basis coefficients L_i(at) for the given x-coordinates.
+///
+/// L_i(at) = prod_{j!=i} (at - x_j) / (x_i - x_j)
+fn lagrange_basis_at(xs: &[FieldElement], at: &FieldElement) -> Vec<FieldElement> {
+    xs.iter()
+        .enumerate()
+        .map(|(i, xi)| {
+            let mut numerator = FieldElement::one();
+            let mut denominator = FieldElement::one();
+            for (j, xj) in xs.iter().enumerate() {
+                if i == j {
+                    continue;
+                }
+                numerator = numerator * (*at - *xj);
+                denominator = denominator * (*xi - *xj);
+            }
+            numerator / denominator
+        })
+        .collect()
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use proptest::collection::vec;
+    use proptest::prelude::*;
 
     #[test]
     fn test_split_reconstruct_basic() {
@@ -179,7 +776,7 @@ mod tests {
         let shares = split(secret, 3, 5).unwrap();
         assert_eq!(shares.len(), 5);
 
-        let recovered = reconstruct(&shares[..3], 3).unwrap();
+        let recovered = reconstruct(&shares[..3]).unwrap();
         assert_eq!(&recovered[..secret.len()], secret);
     }
 
@@ -192,7 +789,7 @@ mod tests {
 
         // Try every possible 3-of-7 combination
         for combo in combinations(&shares, k) {
-            let recovered = reconstruct(&combo, k).unwrap();
+            let recovered = reconstruct(&combo).unwrap();
             assert_eq!(&recovered[..secret.len()], secret);
         }
     }
@@ -201,7 +798,7 @@ mod tests {
     fn test_k_minus_1_fails() {
         let secret = b"cannot reconstruct with too few";
         let shares = split(secret, 5, 9).unwrap();
-        let result = reconstruct(&shares[..4], 5);
+        let result = reconstruct(&shares[..4]);
         assert!(result.is_err());
     }
 
@@ -210,7 +807,7 @@ mod tests {
         // Secret larger than 32 bytes
         let secret = vec![0xAB; 100]; // 100 bytes -> 4 blocks (128 bytes padded)
         let shares = split(&secret, 3, 5).unwrap();
-        let recovered = reconstruct(&shares[..3], 3).unwrap();
+        let recovered = reconstruct(&shares[..3]).unwrap();
         assert_eq!(&recovered[..100], &secret[..]);
     }
 
@@ -218,7 +815,7 @@ mod tests {
     fn test_large_secret() {
         let secret = vec![0x42; 1024]; // 1KB secret
         let shares = split(&secret, 5, 9).unwrap();
-        let recovered = reconstruct(&shares[..5], 5).unwrap();
+        let recovered = reconstruct(&shares[..5]).unwrap();
         assert_eq!(&recovered[..1024], &secret[..]);
     }
 
@@ -250,7 +847,7 @@ mod tests {
     fn test_duplicate_share_id() {
         let shares = split(b"test data for duplicate check!!", 3, 5).unwrap();
         let dup_shares = vec![shares[0].clone(), shares[0].clone(), shares[2].clone()];
-        let result = reconstruct(&dup_shares, 3);
+        let result = reconstruct(&dup_shares);
         assert!(matches!(result, Err(ShamirError::DuplicateShareId(_))));
     }
 
@@ -259,15 +856,418 @@ mod tests {
         let secret = b"same result from any k shares!!";
         let shares = split(secret, 3, 6).unwrap();
 
-        let r1 = reconstruct(&[shares[0].clone(), shares[1].clone(), shares[2].clone()], 3).unwrap();
-        let r2 = reconstruct(&[shares[3].clone(), shares[4].clone(), shares[5].clone()], 3).unwrap();
-        let r3 = reconstruct(&[shares[0].clone(), shares[3].clone(), shares[5].clone()], 3).unwrap();
+        let r1 = reconstruct(&[shares[0].clone(), shares[1].clone(), shares[2].clone()]).unwrap();
+        let r2 = reconstruct(&[shares[3].clone(), shares[4].clone(), shares[5].clone()]).unwrap();
+        let r3 = reconstruct(&[shares[0].clone(), shares[3].clone(), shares[5].clone()]).unwrap();
 
         assert_eq!(r1, r2);
         assert_eq!(r2, r3);
         assert_eq!(&r1[..secret.len()], secret);
     }
 
+    #[test]
+    fn test_exact_length_preserved() {
+        for len in [1, 7, 22, 23, 31, 32, 33, 62, 100] {
+            let secret: Vec<u8> = (0..len).map(|i| (i * 37 % 256) as u8).collect();
+            let shares = split(&secret, 3, 5).unwrap();
+            let recovered = reconstruct(&shares[..3]).unwrap();
+            assert_eq!(recovered, secret, "length {len}");
+        }
+    }
+
+    #[test]
+    fn test_all_ff_block_roundtrip() {
+        // 0xFF..FF as a 32-byte block is >= p and used to be reduced mod p
+        let secret = vec![0xFF; 64];
+        let shares = split(&secret, 3, 5).unwrap();
+        let recovered = reconstruct(&shares[2..5]).unwrap();
+        assert_eq!(recovered, secret);
+    }
+
+    #[test]
+    fn test_share_headers() {
+        let shares = split(&[0x11; 40], 2, 3).unwrap();
+        for share in &shares {
+            assert_eq!(share.scheme, ShareScheme::Shamir256);
+            assert_eq!((share.k, share.n), (2, 3));
+            assert_eq!(share.secret_len, 40);
+            assert_eq!(share.data.len(), 2 * BLOCK_SIZE);
+        }
+    }
+
+    #[test]
+    fn test_reconstruct_from_envelopes() {
+        let secret = b"self-describing shares";
+        let bytes: Vec<Vec<u8>> = split(secret, 3, 5)
+            .unwrap()
+            .iter()
+            .map(Share::to_bytes)
+            .collect();
+        let shares: Vec<Share> = bytes[1..4]
+            .iter()
+            .map(|b| Share::from_bytes(b).unwrap())
+            .collect();
+        assert_eq!(reconstruct(&shares).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_mixed_share_sets_rejected() {
+        let a = split(b"first secret", 2, 3).unwrap();
+        let b = split(b"second secret!", 2, 3).unwrap();
+        assert!(matches!(
+            reconstruct(&[a[0].clone(), b[1].clone()]),
+            Err(ShamirError::IncompatibleShare {
+                field: "secret_len",
+                ..
+            })
+        ));
+
+        let c = split(b"first secret", 3, 4).unwrap();
+        assert!(matches!(
+            reconstruct(&[a[0].clone(), c[1].clone()]),
+            Err(ShamirError::IncompatibleShare { field: "k", .. })
+        ));
+    }
+
+    #[test]
+    fn test_oversized_secret_len_rejected() {
+        // A re-encoded header carries a valid checksum, so only the length
+        // check stands between it and the block arithmetic
+        let mut shares = split(&[0x11; 80], 2, 3).unwrap();
+        for share in shares.iter_mut() {
+            share.secret_len = u64::MAX;
+            *share = Share::from_bytes(&share.to_bytes()).unwrap();
+        }
+        assert!(matches!(reconstruct(&shares), Err(ShamirError::MalformedShare { .. })));
+        assert!(reconstruct_robust(&shares).is_err());
+        assert!(repair_share(&shares[..2], 3).is_err());
+        assert!(reshare(&shares, 2, 3).is_err());
+        assert!(refresh(&shares).is_err());
+    }
+
+    #[test]
+    fn test_truncated_share_rejected() {
+        let mut shares = split(&[0x11; 80], 2, 3).unwrap();
+        for share in shares.iter_mut() {
+            share.data.truncate(40);
+        }
+        assert!(matches!(
+            reconstruct(&shares),
+            Err(ShamirError::MalformedShare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_robust_no_corruption() {
+        let secret = vec![0x3C; 200];
+        let shares = split(&secret, 3, 7).unwrap();
+        let report = reconstruct_robust(&shares).unwrap();
+        assert_eq!(report.secret, secret);
+        assert!(report.inconsistent_ids.is_empty());
+    }
+
+    #[test]
+    fn test_robust_corrects_bad_shares() {
+        let secret: Vec<u8> = (0..300).map(|i| (i * 7 % 256) as u8).collect();
+        let mut shares = split(&secret, 3, 7).unwrap();
+        // One base share corrupted throughout, one extra share in a late block only
+        for b in shares[0].data.iter_mut().step_by(32) {
+            *b ^= 0x01;
+        }
+        shares[5].data[32 * 8 + 20] ^= 0xFF;
+
+        // Plain reconstruction uses the bad base share
+        assert_ne!(reconstruct(&shares).ok(), Some(secret.clone()));
+
+        let report = reconstruct_robust(&shares).unwrap();
+        assert_eq!(report.secret, secret);
+        assert_eq!(report.inconsistent_ids, vec![1, 6]);
+    }
+
+    #[test]
+    fn test_robust_too_many_errors() {
+        let mut shares = split(b"only one error is correctable", 3, 6).unwrap();
+        shares[1].data[31] ^= 0x01;
+        shares[4].data[31] ^= 0x01;
+        assert!(matches!(
+            reconstruct_robust(&shares),
+            Err(ShamirError::TooManyCorruptShares {
+                block: 0,
+                max_errors: 1
+            })
+        ));
+    }
+
+    #[test]
+    fn test_robust_with_exactly_k_shares() {
+        let secret = b"no redundancy";
+        let shares = split(secret, 3, 5).unwrap();
+        let report = reconstruct_robust(&shares[..3]).unwrap();
+        assert_eq!(report.secret, secret);
+        assert!(report.inconsistent_ids.is_empty());
+    }
+
+    #[test]
+    fn test_berlekamp_welch_locates_errors() {
+        // f(x) = 5 + 3x over points x = 1..=6, with y_2 and y_5 corrupted
+        let xs: Vec<FieldElement> = (1..=6).map(FieldElement::from_u64).collect();
+        let mut ys: Vec<FieldElement> = (1..=6).map(|x| FieldElement::from_u64(5 + 3 * x)).collect();
+        ys[1] = FieldElement::from_u64(1000);
+        ys[4] = FieldElement::from_u64(0);
+
+        let (poly, errors) = berlekamp_welch(&xs, &ys, 2, 2).unwrap();
+        assert_eq!(poly, vec![FieldElement::from_u64(5), FieldElement::from_u64(3)]);
+        assert_eq!(errors, vec![1, 4]);
+    }
+
+    #[test]
+    fn test_refresh_preserves_secret() {
+        let secret: Vec<u8> = (0..100).collect();
+        let shares = split(&secret, 3, 5).unwrap();
+        let refreshed = refresh(&shares).unwrap();
+
+        for (old, new) in shares.iter().zip(&refreshed) {
+            assert_eq!(old.id, new.id);
+            assert_ne!(old.data, new.data);
+        }
+        for combo in combinations(&refreshed, 3) {
+            assert_eq!(reconstruct(&combo).unwrap(), secret);
+        }
+
+        let twice = refresh(&refreshed).unwrap();
+        assert_eq!(reconstruct(&twice[2..]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_refresh_old_new_mixture_fails() {
+        let secret = b"compromised nodes hold stale shares".to_vec();
+        let old = split(&secret, 3, 5).unwrap();
+        let new = refresh(&old).unwrap();
+
+        // Every 3-subset that mixes epochs
+        for combo in combinations(&(0..5).collect::<Vec<usize>>(), 3) {
+            for old_count in 1..3 {
+                let mixed: Vec<Share> = combo
+                    .iter()
+                    .enumerate()
+                    .map(|(pos, &i)| if pos < old_count { old[i].clone() } else { new[i].clone() })
+                    .collect();
+                match reconstruct(&mixed) {
+                    Ok(recovered) => assert_ne!(recovered, secret),
+                    Err(e) => assert!(matches!(e, ShamirError::MalformedSecret(_))),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_distributed_refresh() {
+        let secret = b"each node applies its own update";
+        let shares = split(secret, 2, 4).unwrap();
+        let updates = refresh_updates(2, 4, secret.len()).unwrap();
+        assert_eq!(reconstruct(&updates).unwrap(), vec![0u8; secret.len()]);
+
+        let refreshed: Vec<Share> = shares
+            .iter()
+            .zip(&updates)
+            .map(|(s, u)| apply_refresh(s, u).unwrap())
+            .collect();
+        assert_eq!(reconstruct(&refreshed[1..3]).unwrap(), secret);
+
+        assert!(matches!(
+            apply_refresh(&shares[0], &updates[1]),
+            Err(ShamirError::IncompatibleShare { field: "id", .. })
+        ));
+    }
+
+    #[test]
+    fn test_repair_share_matches_original() {
+        let secret: Vec<u8> = (0..250).map(|i| (i * 3) as u8).collect();
+        let shares = split(&secret, 3, 7).unwrap();
+
+        let survivors = [shares[0].clone(), shares[3].clone(), shares[5].clone()];
+        for target in [2, 3, 5, 7] {
+            let repaired = repair_share(&survivors, target).unwrap();
+            assert_eq!(repaired, shares[target as usize - 1]);
+        }
+
+        let repaired = repair_share(&survivors, 7).unwrap();
+        let set = [shares[1].clone(), repaired, shares[4].clone()];
+        assert_eq!(reconstruct(&set).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_repair_share_invalid_target() {
+        let shares = split(b"repair", 2, 3).unwrap();
+        assert!(repair_share(&shares, 0).is_err());
+        assert!(repair_share(&shares, 4).is_err());
+        assert!(matches!(
+            repair_share(&shares[..1], 2),
+            Err(ShamirError::NotEnoughShares { have: 1, need: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_reshare_changes_threshold() {
+        let secret: Vec<u8> = (0..150).map(|i| (i * 11) as u8).collect();
+        let shares = split(&secret, 5, 9).unwrap();
+
+        let hot = reshare(&shares[4..], 3, 5).unwrap();
+        assert_eq!(hot.len(), 5);
+        assert!(hot.iter().all(|s| s.k == 3 && s.n == 5));
+        for combo in combinations(&hot, 3) {
+            assert_eq!(reconstruct(&combo).unwrap(), secret);
+        }
+        assert!(matches!(
+            reconstruct(&hot[..2]),
+            Err(ShamirError::NotEnoughShares { have: 2, need: 3 })
+        ));
+
+        let churny = reshare(&hot, 5, 11).unwrap();
+        assert_eq!(reconstruct(&churny[6..]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_reshare_keeps_origin_and_rejects_mixing() {
+        let mut shares = split(b"adaptive redundancy", 2, 3).unwrap();
+        crate::share::assign_origin(&mut shares, &[0x77; 32], 9);
+        let resharded = reshare(&shares, 2, 4).unwrap();
+        assert!(resharded
+            .iter()
+            .all(|s| s.chunk_index == 9 && s.repo_hash == [0x77; 32]));
+
+        let mixed = [shares[0].clone(), resharded[1].clone()];
+        assert!(matches!(
+            reconstruct(&mixed),
+            Err(ShamirError::IncompatibleShare { field: "n", .. })
+        ));
+    }
+
+    #[test]
+    fn test_reshare_invalid_parameters() {
+        let shares = split(b"x", 2, 3).unwrap();
+        assert!(matches!(
+            reshare(&shares, 1, 3),
+            Err(ShamirError::ThresholdTooLow { k: 1 })
+        ));
+        assert!(matches!(
+            reshare(&shares, 4, 3),
+            Err(ShamirError::InsufficientShares { k: 4, n: 3 })
+        ));
+        assert!(reshare(&shares[..1], 2, 3).is_err());
+    }
+
+    #[test]
+    fn test_parallel_matches_serial_layout() {
+        // Spans several parallel segments plus a partial one
+        let secret: Vec<u8> = (0..40_000).map(|i| (i * 13 % 256) as u8).collect();
+        let shares = split_parallel(&secret, 3, 5).unwrap();
+        assert_eq!(shares.len(), 5);
+        assert!(shares
+            .iter()
+            .zip(1..)
+            .all(|(s, id)| s.id == id && s.data.len() == secret.len().div_ceil(31) * 32));
+
+        assert_eq!(reconstruct(&shares[2..]).unwrap(), secret);
+        assert_eq!(reconstruct_parallel(&shares[..3]).unwrap(), secret);
+
+        let serial = split(&secret, 3, 5).unwrap();
+        assert_eq!(reconstruct_parallel(&serial[1..4]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_parallel_seeded_rng_is_reproducible() {
+        let secret: Vec<u8> = (0..40_000).map(|i| (i * 7 % 256) as u8).collect();
+        let a = split_parallel_with_rng(&secret, 3, 5, &mut ChaCha20Rng::seed_from_u64(9)).unwrap();
+        let b = split_parallel_with_rng(&secret, 3, 5, &mut ChaCha20Rng::seed_from_u64(9)).unwrap();
+        let c = split_parallel_with_rng(&secret, 3, 5, &mut ChaCha20Rng::seed_from_u64(10)).unwrap();
+        assert!(a.iter().zip(&b).all(|(x, y)| x.data == y.data));
+        assert!(a.iter().zip(&c).all(|(x, y)| x.data != y.data));
+        assert_eq!(reconstruct(&a[1..4]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_parallel_small_and_invalid() {
+        let shares = split_parallel(b"tiny", 2, 3).unwrap();
+        assert_eq!(reconstruct_parallel(&shares).unwrap(), b"tiny");
+        assert!(matches!(
+            split_parallel(b"", 2, 3),
+            Err(ShamirError::EmptySecret)
+        ));
+        assert!(matches!(
+            reconstruct_parallel(&shares[..1]),
+            Err(ShamirError::NotEnoughShares { have: 1, need: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_seeded_rng_is_reproducible() {
+        let secret = b"deterministic shares for simulations";
+        let a = split_with_rng(secret, 3, 5, &mut ChaCha20Rng::from_seed([7; 32])).unwrap();
+        let b = split_with_rng(secret, 3, 5, &mut ChaCha20Rng::from_seed([7; 32])).unwrap();
+        let c = split_with_rng(secret, 3, 5, &mut ChaCha20Rng::from_seed([8; 32])).unwrap();
+        assert_eq!(a, b);
+        assert_ne!(a, c);
+
+        let r1 = refresh_with_rng(&a, &mut ChaCha20Rng::from_seed([1; 32])).unwrap();
+        let r2 = refresh_with_rng(&a, &mut ChaCha20Rng::from_seed([1; 32])).unwrap();
+        assert_eq!(r1, r2);
+        let s1 = reshare_with_rng(&a, 2, 4, &mut ChaCha20Rng::from_seed([2; 32])).unwrap();
+        let s2 = reshare_with_rng(&a, 2, 4, &mut ChaCha20Rng::from_seed([2; 32])).unwrap();
+        assert_eq!(s1, s2);
+        assert_eq!(reconstruct(&s1[1..3]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_known_answer_vectors() {
+        let doc: serde_json::Value =
+            serde_json::from_str(include_str!("../test-vectors/shamir_kat.json")).unwrap();
+        let hex_field = |v: &serde_json::Value, key: &str| hex::decode(v[key].as_str().unwrap()).unwrap();
+
+        for vector in doc["vectors"].as_array().unwrap() {
+            let name = vector["name"].as_str().unwrap();
+            let seed: [u8; 32] = hex_field(vector, "seed").try_into().unwrap();
+            let k = vector["k"].as_u64().unwrap() as usize;
+            let n = vector["n"].as_u64().unwrap() as usize;
+            let secret = hex_field(vector, "secret");
+            let expected = vector["shares"].as_array().unwrap();
+
+            let shares = split_with_rng(&secret, k, n, &mut ChaCha20Rng::from_seed(seed)).unwrap();
+            assert_eq!(shares.len(), expected.len(), "{name}");
+            for (share, want) in shares.iter().zip(expected) {
+                assert_eq!(share.id as u64, want["id"].as_u64().unwrap(), "{name}");
+                assert_eq!(share.data, hex_field(want, "data"), "{name} share {}", share.id);
+                assert_eq!(share.to_bytes(), hex_field(want, "envelope"), "{name} share {}", share.id);
+            }
+
+            // Reconstruction from the published envelopes, last k shares
+            let parsed: Vec<Share> = expected[n - k..]
+                .iter()
+                .map(|want| Share::from_bytes(&hex_field(want, "envelope")).unwrap())
+                .collect();
+            assert_eq!(reconstruct(&parsed).unwrap(), secret, "{name}");
+        }
+    }
+
+    proptest! {
+        #![proptest_config(ProptestConfig::with_cases(64))]
+
+        #[test]
+        fn prop_roundtrip_arbitrary_bytes(secret in vec(any::<u8>(), 1..200)) {
+            let shares = split(&secret, 3, 5).unwrap();
+            let recovered = reconstruct(&shares[1..4]).unwrap();
+            prop_assert_eq!(recovered, secret);
+        }
+
+        #[test]
+        fn prop_roundtrip_all_ff(len in 1usize..200) {
+            let secret = vec![0xFF; len];
+            let shares = split(&secret, 2, 3).unwrap();
+            let recovered = reconstruct(&shares[1..]).unwrap();
+            prop_assert_eq!(recovered, secret);
+        }
+    }
+
     /// Generate all k-element combinations from a slice.
     fn combinations<T: Clone>(items: &[T], k: usize) -> Vec<Vec<T>> {
         if k == 0 {
@@ -0,0 +1,314 @@
+use gitgold_core::error::ShamirError;
+use gitgold_core::types::{Hash256, ShareScheme};
+use serde::{Deserialize, Serialize};
+
+use crate::hash::sha256;
+
+/// Magic bytes at the start of every serialized share.
+const MAGIC: &[u8; 4] = b"GGSH";
+
+/// Current share envelope format version.
+///
+/// Version 1 packs the secret into 31-byte payload blocks so every block value
+/// is below the field modulus.
+pub const SHARE_FORMAT_VERSION: u8 = 1;
+
+/// Serialized header length: magic, version, scheme, k, n, id, secret_len,
+/// chunk_index, repo_hash.
+const HEADER_LEN: usize = 4 + 1 + 1 + 4 + 4 + 4 + 8 + 4 + 32;
+
+/// Trailing SHA-256 checksum over header and data.
+const CHECKSUM_LEN: usize = 32;
+
+/// A single share of a secret, together with everything needed to recombine it.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Share {
+    /// Share identifier (1-indexed, used as the x-coordinate).
+    pub id: u32,
+    /// Scheme that produced the share data.
+    pub scheme: ShareScheme,
+    /// Threshold: number of shares required for reconstruction.
+    pub k: u32,
+    /// Total number of shares produced for the secret.
+    pub n: u32,
+    /// Length of the original secret in bytes.
+    pub secret_len: u64,
+    /// Index of the chunk this share belongs to within its repository.
+    pub chunk_index: u32,
+    /// Hash of the repository the chunk belongs to (zero if unassigned).
+    pub repo_hash: Hash256,
+    /// Scheme-specific share data.
+    pub data: Vec<u8>,
+}
+
+impl Share {
+    /// Serialize into the self-describing envelope:
+    ///
+    /// `magic || version || scheme || k || n || id || secret_len || chunk_index
+    /// || repo_hash || data || sha256(everything before)`
+    ///
+    /// Integers are big-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(HEADER_LEN + self.data.len() + CHECKSUM_LEN);
+        out.extend_from_slice(MAGIC);
+        out.push(SHARE_FORMAT_VERSION);
+        out.push(self.scheme.id());
+        out.extend_from_slice(&self.k.to_be_bytes());
+        out.extend_from_slice(&self.n.to_be_bytes());
+        out.extend_from_slice(&self.id.to_be_bytes());
+        out.extend_from_slice(&self.secret_len.to_be_bytes());
+        out.extend_from_slice(&self.chunk_index.to_be_bytes());
+        out.extend_from_slice(&self.repo_hash);
+        out.extend_from_slice(&self.data);
+        let checksum = sha256(&out);
+        out.extend_from_slice(&checksum);
+        out
+    }
+
+    /// Parse and verify a serialized share envelope.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ShamirError> {
+        if bytes.len() < HEADER_LEN + CHECKSUM_LEN || &bytes[..4] != MAGIC {
+            return Err(ShamirError::MalformedShare {
+                id: 0,
+                reason: "not a share envelope".to_string(),
+            });
+        }
+
+        let version = bytes[4];
+        if version != SHARE_FORMAT_VERSION {
+            return Err(ShamirError::UnsupportedVersion(version));
+        }
+        let scheme = ShareScheme::from_id(bytes[5]).ok_or(ShamirError::UnknownScheme(bytes[5]))?;
+
+        let k = read_u32(bytes, 6);
+        let n = read_u32(bytes, 10);
+        let id = read_u32(bytes, 14);
+        let secret_len = u64::from_be_bytes(bytes[18..26].try_into().unwrap());
+        let chunk_index = read_u32(bytes, 26);
+        let mut repo_hash = [0u8; 32];
+        repo_hash.copy_from_slice(&bytes[30..HEADER_LEN]);
+
+        let body_end = bytes.len() - CHECKSUM_LEN;
+        if sha256(&bytes[..body_end]) != bytes[body_end..] {
+            return Err(ShamirError::ChecksumMismatch { id });
+        }
+        if k == 0 || n < k || id == 0 || id > n {
+            return Err(ShamirError::MalformedShare {
+                id,
+                reason: format!("invalid parameters k={k}, n={n}, id={id}"),
+            });
+        }
+
+        Ok(Self {
+            id,
+            scheme,
+            k,
+            n,
+            secret_len,
+            chunk_index,
+            repo_hash,
+            data: bytes[HEADER_LEN..body_end].to_vec(),
+        })
+    }
+}
+
+/// Tag every share with the repository and chunk it belongs to.
+pub fn assign_origin(shares: &mut [Share], repo_hash: &Hash256, chunk_index: u32) {
+    for share in shares {
+        share.repo_hash = *repo_hash;
+        share.chunk_index = chunk_index;
+    }
+}
+
+/// Check that `shares` form a usable set for `scheme`: all headers agree,
+/// the parameters are valid (`1 <= k <= n`, every id in `1..=n`), ids are
+/// unique, and at least k shares are present. Shares built in code are held
+/// to the same rules `from_bytes` enforces on parsed ones.
+///
+/// Returns the threshold k.
+pub fn validate_share_set(shares: &[Share], scheme: ShareScheme) -> Result<usize, ShamirError> {
+    let Some(first) = shares.first() else {
+        return Err(ShamirError::NotEnoughShares { have: 0, need: 1 });
+    };
+    if first.scheme != scheme {
+        return Err(ShamirError::SchemeMismatch {
+            expected: scheme,
+            found: first.scheme,
+        });
+    }
+    if first.k == 0 || first.n < first.k {
+        return Err(ShamirError::MalformedShare {
+            id: first.id,
+            reason: format!("invalid parameters k={}, n={}", first.k, first.n),
+        });
+    }
+
+    let mut seen = std::collections::HashSet::new();
+    for share in shares {
+        let mismatch = if share.scheme != first.scheme {
+            Some("scheme")
+        } else if share.k != first.k {
+            Some("k")
+        } else if share.n != first.n {
+            Some("n")
+        } else if share.secret_len != first.secret_len {
+            Some("secret_len")
+        } else if share.chunk_index != first.chunk_index {
+            Some("chunk_index")
+        } else if share.repo_hash != first.repo_hash {
+            Some("repo_hash")
+        } else if share.data.len() != first.data.len() {
+            Some("data length")
+        } else {
+            None
+        };
+        if let Some(field) = mismatch {
+            return Err(ShamirError::IncompatibleShare {
+                id: share.id,
+                field,
+            });
+        }
+        if share.id == 0 || share.id > share.n {
+            return Err(ShamirError::MalformedShare {
+                id: share.id,
+                reason: format!("share id must be in 1..={}", share.n),
+            });
+        }
+        if !seen.insert(share.id) {
+            return Err(ShamirError::DuplicateShareId(share.id));
+        }
+    }
+
+    let k = first.k as usize;
+    if shares.len() < k {
+        return Err(ShamirError::NotEnoughShares {
+            have: shares.len(),
+            need: k,
+        });
+    }
+    Ok(k)
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_be_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_share() -> Share {
+        Share {
+            id: 3,
+            scheme: ShareScheme::Shamir256,
+            k: 2,
+            n: 4,
+            secret_len: 5,
+            chunk_index: 7,
+            repo_hash: [0xAA; 32],
+            data: vec![1, 2, 3, 4, 5],
+        }
+    }
+
+    #[test]
+    fn test_envelope_roundtrip() {
+        let share = sample_share();
+        let bytes = share.to_bytes();
+        assert_eq!(bytes.len(), HEADER_LEN + 5 + CHECKSUM_LEN);
+        assert_eq!(Share::from_bytes(&bytes).unwrap(), share);
+    }
+
+    #[test]
+    fn test_corrupted_envelope_rejected() {
+        let mut bytes = sample_share().to_bytes();
+        bytes[HEADER_LEN + 1] ^= 0x01;
+        assert!(matches!(
+            Share::from_bytes(&bytes),
+            Err(ShamirError::ChecksumMismatch { id: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_unknown_version_rejected() {
+        let mut bytes = sample_share().to_bytes();
+        bytes[4] = 99;
+        assert!(matches!(
+            Share::from_bytes(&bytes),
+            Err(ShamirError::UnsupportedVersion(99))
+        ));
+    }
+
+    #[test]
+    fn test_unknown_scheme_rejected() {
+        let mut bytes = sample_share().to_bytes();
+        bytes[5] = 0xEE;
+        assert!(matches!(
+            Share::from_bytes(&bytes),
+            Err(ShamirError::UnknownScheme(0xEE))
+        ));
+    }
+
+    #[test]
+    fn test_not_an_envelope() {
+        assert!(matches!(
+            Share::from_bytes(b"raw fragment bytes"),
+            Err(ShamirError::MalformedShare { .. })
+        ));
+    }
+
+    #[test]
+    fn test_mixed_chunks_rejected() {
+        let a = sample_share();
+        let mut b = sample_share();
+        b.id = 4;
+        b.chunk_index = 8;
+        assert!(matches!(
+            validate_share_set(&[a, b], ShareScheme::Shamir256),
+            Err(ShamirError::IncompatibleShare {
+                id: 4,
+                field: "chunk_index"
+            })
+        ));
+    }
+
+    #[test]
+    fn test_invalid_parameters_rejected() {
+        // Built in code, so never checked by from_bytes
+        let mut zero_k = sample_share();
+        zero_k.k = 0;
+        assert!(matches!(
+            validate_share_set(&[zero_k.clone()], ShareScheme::Shamir256),
+            Err(ShamirError::MalformedShare { id: 3, .. })
+        ));
+        assert!(crate::shamir::reconstruct(&[zero_k]).is_err());
+
+        let mut zero_id = sample_share();
+        zero_id.id = 0;
+        assert!(matches!(
+            validate_share_set(&[zero_id, sample_share()], ShareScheme::Shamir256),
+            Err(ShamirError::MalformedShare { id: 0, .. })
+        ));
+        let mut beyond_n = sample_share();
+        beyond_n.id = 5;
+        assert!(validate_share_set(&[beyond_n], ShareScheme::Shamir256).is_err());
+        let mut k_above_n = sample_share();
+        k_above_n.k = 5;
+        assert!(validate_share_set(&[k_above_n], ShareScheme::Shamir256).is_err());
+
+        let rs = Share {
+            scheme: ShareScheme::ReedSolomon,
+            k: 0,
+            ..sample_share()
+        };
+        assert!(crate::scheme::reconstruct_any(&[rs]).is_err());
+    }
+
+    #[test]
+    fn test_assign_origin() {
+        let mut shares = vec![sample_share(), sample_share()];
+        assign_origin(&mut shares, &[0x11; 32], 42);
+        assert!(shares
+            .iter()
+            .all(|s| s.repo_hash == [0x11; 32] && s.chunk_index == 42));
+    }
+}
@@ -0,0 +1,658 @@
+use curve25519_dalek::constants::{RISTRETTO_BASEPOINT_POINT, RISTRETTO_BASEPOINT_TABLE};
+use curve25519_dalek::ristretto::{CompressedRistretto, RistrettoPoint};
+use curve25519_dalek::scalar::Scalar;
+use curve25519_dalek::traits::{IsIdentity, VartimeMultiscalarMul};
+use gitgold_core::error::ShamirError;
+use gitgold_core::types::{Hash256, ShareScheme};
+use rand::{thread_rng, CryptoRng, RngCore};
+use sha2::{Digest, Sha256, Sha512};
+
+use crate::share::{validate_share_set, Share};
+
+/// Size of one encoded scalar in share data.
+const BLOCK_SIZE: usize = 32;
+
+/// Secret bytes carried per block. The Ristretto group order is just above
+/// 2^252, so 31-byte blocks are always canonical scalars.
+const PAYLOAD_SIZE: usize = 31;
+
+/// Magic bytes at the start of serialized commitments.
+const COMMITMENT_MAGIC: &[u8; 4] = b"GGVC";
+
+/// Current commitment format version.
+const COMMITMENT_VERSION: u8 = 1;
+
+/// Serialized commitment header: magic, version, k, n, secret_len.
+const COMMITMENT_HEADER_LEN: usize = 4 + 1 + 4 + 4 + 8;
+
+/// Domain separator for the Pedersen blinding generator H.
+const BLINDING_GENERATOR_LABEL: &[u8] = b"gitgold-vss-pedersen-blinding-generator";
+
+/// Domain separator for the block aggregation challenge.
+const AGGREGATION_LABEL: &[u8] = b"gitgold-vss-aggregation-challenge";
+
+/// Public commitments that let anyone verify a single share in isolation.
+///
+/// Every block of the secret is shared with its own polynomial, so committing
+/// to each would cost k points per 31-byte block. Instead the block
+/// polynomials are folded into one with powers of a challenge `rho`, derived
+/// by hashing the digest of every share (Fiat-Shamir). A share is valid if its
+/// digest is listed here and its folded value opens the Pedersen commitments:
+///
+/// `sum_b rho^b * y_b * G + r * H == sum_j id^j * C_j`
+///
+/// where `y_b` are the share's block values and `r` its blinding value. A
+/// dealer cannot pick `rho` after the shares, so inconsistent shares fail with
+/// overwhelming probability; a node cannot alter its share without changing
+/// its digest.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ShareCommitments {
+    /// Threshold of the committed split.
+    pub k: u32,
+    /// Number of shares in the committed split.
+    pub n: u32,
+    /// Length of the original secret in bytes.
+    pub secret_len: u64,
+    /// `SHA-256(id || data)` of every share, indexed by `id - 1`.
+    pub share_digests: Vec<Hash256>,
+    /// Pedersen commitments `C_j = A_j * G + b_j * H` to the folded
+    /// polynomial coefficients, lowest degree first.
+    pub points: Vec<RistrettoPoint>,
+}
+
+/// Split a secret into `n` verifiable shares where any `k` can reconstruct.
+///
+/// Share data is one 32-byte little-endian scalar per 31-byte secret block,
+/// followed by the share's 32-byte blinding value.
+pub fn split(
+    secret: &[u8],
+    k: usize,
+    n: usize,
+) -> Result<(Vec<Share>, ShareCommitments), ShamirError> {
+    split_with_rng(secret, k, n, &mut thread_rng())
+}
+
+/// `split`, drawing the polynomial coefficients and blinding values from `rng`.
+pub fn split_with_rng<R: RngCore + CryptoRng>(
+    secret: &[u8],
+    k: usize,
+    n: usize,
+    rng: &mut R,
+) -> Result<(Vec<Share>, ShareCommitments), ShamirError> {
+    if secret.is_empty() {
+        return Err(ShamirError::EmptySecret);
+    }
+    if k < 2 {
+        return Err(ShamirError::ThresholdTooLow { k });
+    }
+    if n < k {
+        return Err(ShamirError::InsufficientShares { k, n });
+    }
+
+    let num_blocks = secret.len().div_ceil(PAYLOAD_SIZE);
+    let mut shares: Vec<Share> = (1..=n as u32)
+        .map(|id| Share {
+            id,
+            scheme: ShareScheme::PedersenVss,
+            k: k as u32,
+            n: n as u32,
+            secret_len: secret.len() as u64,
+            chunk_index: 0,
+            repo_hash: [0u8; 32],
+            data: Vec::with_capacity((num_blocks + 1) * BLOCK_SIZE),
+        })
+        .collect();
+
+    let xs: Vec<Scalar> = (1..=n as u64).map(Scalar::from).collect();
+
+    // Block polynomials are kept until rho is known, so they can be folded
+    let mut block_polys: Vec<Vec<Scalar>> = Vec::with_capacity(num_blocks);
+    for block in secret.chunks(PAYLOAD_SIZE) {
+        let mut bytes = [0u8; BLOCK_SIZE];
+        bytes[..block.len()].copy_from_slice(block);
+
+        let mut coeffs = Vec::with_capacity(k);
+        coeffs.push(Scalar::from_bytes_mod_order(bytes));
+        for _ in 1..k {
+            coeffs.push(random_scalar(rng));
+        }
+
+        for (share, x) in shares.iter_mut().zip(&xs) {
+            share.data.extend_from_slice(eval_poly(&coeffs, x).as_bytes());
+        }
+        block_polys.push(coeffs);
+    }
+
+    let blinding: Vec<Scalar> = (0..k).map(|_| random_scalar(rng)).collect();
+    for (share, x) in shares.iter_mut().zip(&xs) {
+        share.data.extend_from_slice(eval_poly(&blinding, x).as_bytes());
+    }
+
+    let share_digests: Vec<Hash256> = shares.iter().map(share_digest).collect();
+    let rho = aggregation_challenge(k as u32, n as u32, secret.len() as u64, &share_digests);
+
+    // A_j = sum_b rho^b * a_{b,j}, by Horner's method over the blocks
+    let mut folded = vec![Scalar::ZERO; k];
+    for coeffs in block_polys.iter().rev() {
+        for (acc, coeff) in folded.iter_mut().zip(coeffs) {
+            *acc = *acc * rho + coeff;
+        }
+    }
+
+    let h = blinding_generator();
+    let points = folded
+        .iter()
+        .zip(&blinding)
+        .map(|(a, b)| a * RISTRETTO_BASEPOINT_TABLE + b * h)
+        .collect();
+
+    let commitments = ShareCommitments {
+        k: k as u32,
+        n: n as u32,
+        secret_len: secret.len() as u64,
+        share_digests,
+        points,
+    };
+    Ok((shares, commitments))
+}
+
+/// Reconstruct a secret from `k` or more shares.
+///
+/// Shares are not checked against commitments; use `reconstruct_verified`
+/// when shares come from untrusted nodes.
+pub fn reconstruct(shares: &[Share]) -> Result<Vec<u8>, ShamirError> {
+    let k = validate_share_set(shares, ShareScheme::PedersenVss)?;
+    let selected = &shares[..k];
+    let num_blocks = block_count(selected[0].secret_len)
+        .filter(|&blocks| share_len(blocks) == Some(selected[0].data.len()))
+        .ok_or_else(|| length_mismatch(&selected[0]))?;
+    let secret_len = selected[0].secret_len as usize;
+
+    let xs: Vec<Scalar> = selected.iter().map(|s| Scalar::from(s.id as u64)).collect();
+    let basis = lagrange_basis_at(&xs, &Scalar::ZERO);
+
+    let mut result = Vec::with_capacity(num_blocks * PAYLOAD_SIZE);
+    for block_idx in 0..num_blocks {
+        let mut secret_elem = Scalar::ZERO;
+        for (share, coeff) in selected.iter().zip(&basis) {
+            secret_elem += read_scalar(share, block_idx)? * coeff;
+        }
+        let bytes = secret_elem.to_bytes();
+        if bytes[PAYLOAD_SIZE] != 0 {
+            return Err(ShamirError::MalformedSecret(format!(
+                "block {block_idx} exceeds the {PAYLOAD_SIZE}-byte payload range"
+            )));
+        }
+        result.extend_from_slice(&bytes[..PAYLOAD_SIZE]);
+    }
+
+    result.truncate(secret_len);
+    Ok(result)
+}
+
+/// Regenerate the share for `target_id` from k surviving shares.
+///
+/// The block values and the blinding value are all interpolated at
+/// x = `target_id`, so the repaired share is identical to the original and
+/// still verifies against the split's commitments.
+pub fn repair_share(shares: &[Share], target_id: u32) -> Result<Share, ShamirError> {
+    let k = validate_share_set(shares, ShareScheme::PedersenVss)?;
+    let selected = &shares[..k];
+    crate::shamir::check_repair_target(&selected[0], target_id)?;
+    let num_blocks = block_count(selected[0].secret_len)
+        .filter(|&blocks| share_len(blocks) == Some(selected[0].data.len()))
+        .ok_or_else(|| length_mismatch(&selected[0]))?;
+
+    let xs: Vec<Scalar> = selected.iter().map(|s| Scalar::from(s.id as u64)).collect();
+    let basis = lagrange_basis_at(&xs, &Scalar::from(target_id as u64));
+
+    // The blinding value is the final block
+    let mut data = Vec::with_capacity((num_blocks + 1) * BLOCK_SIZE);
+    for block_idx in 0..=num_blocks {
+        let mut y = Scalar::ZERO;
+        for (share, coeff) in selected.iter().zip(&basis) {
+            y += read_scalar(share, block_idx)? * coeff;
+        }
+        data.extend_from_slice(y.as_bytes());
+    }
+
+    Ok(Share {
+        id: target_id,
+        data,
+        ..selected[0].clone()
+    })
+}
+
+/// Reconstruct a secret using only the shares that verify against
+/// `commitments`. Invalid shares are skipped rather than mixed in.
+pub fn reconstruct_verified(
+    shares: &[Share],
+    commitments: &ShareCommitments,
+) -> Result<Vec<u8>, ShamirError> {
+    let valid: Vec<Share> = shares
+        .iter()
+        .filter(|s| commitments.verify_share(s).is_ok())
+        .cloned()
+        .collect();
+    if valid.len() < commitments.k as usize {
+        return Err(ShamirError::NotEnoughShares {
+            have: valid.len(),
+            need: commitments.k as usize,
+        });
+    }
+    reconstruct(&valid)
+}
+
+impl ShareCommitments {
+    /// Verify a single share against the commitments.
+    pub fn verify_share(&self, share: &Share) -> Result<(), ShamirError> {
+        let (folded, blinding) = self.open(share)?;
+        let x = Scalar::from(share.id as u64);
+
+        // folded * G + blinding * H - sum_j x^j * C_j == identity
+        let mut scalars = vec![folded, blinding];
+        let mut power = Scalar::ONE;
+        for _ in 0..self.points.len() {
+            scalars.push(-power);
+            power *= x;
+        }
+        let bases = [RISTRETTO_BASEPOINT_POINT, blinding_generator()]
+            .into_iter()
+            .chain(self.points.iter().copied());
+
+        if RistrettoPoint::vartime_multiscalar_mul(scalars, bases).is_identity() {
+            Ok(())
+        } else {
+            Err(ShamirError::VerificationFailed { id: share.id })
+        }
+    }
+
+    /// Verify several shares with a single multiscalar multiplication.
+    ///
+    /// The per-share equations are combined with random weights, so one check
+    /// costs about as much as verifying a single share. On failure the shares
+    /// are re-checked individually to report the first invalid one.
+    pub fn verify_batch(&self, shares: &[Share]) -> Result<(), ShamirError> {
+        let mut rng = thread_rng();
+        let mut g_scalar = Scalar::ZERO;
+        let mut h_scalar = Scalar::ZERO;
+        let mut point_scalars = vec![Scalar::ZERO; self.points.len()];
+
+        for share in shares {
+            let (folded, blinding) = self.open(share)?;
+            let weight = random_scalar(&mut rng);
+            g_scalar += weight * folded;
+            h_scalar += weight * blinding;
+
+            let x = Scalar::from(share.id as u64);
+            let mut power = weight;
+            for acc in point_scalars.iter_mut() {
+                *acc -= power;
+                power *= x;
+            }
+        }
+
+        let scalars = [g_scalar, h_scalar].into_iter().chain(point_scalars);
+        let bases = [RISTRETTO_BASEPOINT_POINT, blinding_generator()]
+            .into_iter()
+            .chain(self.points.iter().copied());
+
+        if RistrettoPoint::vartime_multiscalar_mul(scalars, bases).is_identity() {
+            return Ok(());
+        }
+        shares.iter().try_for_each(|s| self.verify_share(s))
+    }
+
+    /// Serialize as `magic || version || k || n || secret_len || digests ||
+    /// compressed points` (integers big-endian).
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            COMMITMENT_HEADER_LEN + (self.share_digests.len() + self.points.len()) * 32,
+        );
+        out.extend_from_slice(COMMITMENT_MAGIC);
+        out.push(COMMITMENT_VERSION);
+        out.extend_from_slice(&self.k.to_be_bytes());
+        out.extend_from_slice(&self.n.to_be_bytes());
+        out.extend_from_slice(&self.secret_len.to_be_bytes());
+        for digest in &self.share_digests {
+            out.extend_from_slice(digest);
+        }
+        for point in &self.points {
+            out.extend_from_slice(point.compress().as_bytes());
+        }
+        out
+    }
+
+    /// Parse serialized commitments, rejecting points that do not decode.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, ShamirError> {
+        if bytes.len() < COMMITMENT_HEADER_LEN || &bytes[..4] != COMMITMENT_MAGIC {
+            return Err(ShamirError::MalformedCommitments(
+                "not a commitment envelope".to_string(),
+            ));
+        }
+        if bytes[4] != COMMITMENT_VERSION {
+            return Err(ShamirError::UnsupportedVersion(bytes[4]));
+        }
+
+        let k = u32::from_be_bytes(bytes[5..9].try_into().unwrap());
+        let n = u32::from_be_bytes(bytes[9..13].try_into().unwrap());
+        let secret_len = u64::from_be_bytes(bytes[13..21].try_into().unwrap());
+        if k < 2 || n < k {
+            return Err(ShamirError::MalformedCommitments(format!(
+                "invalid parameters k={k}, n={n}"
+            )));
+        }
+
+        let body = &bytes[COMMITMENT_HEADER_LEN..];
+        if body.len() != (n as usize + k as usize) * 32 {
+            return Err(ShamirError::MalformedCommitments(format!(
+                "expected {} digests and {} points, got {} bytes",
+                n,
+                k,
+                body.len()
+            )));
+        }
+
+        let (digests, points) = body.split_at(n as usize * 32);
+        let share_digests = digests
+            .chunks_exact(32)
+            .map(|c| c.try_into().unwrap())
+            .collect();
+        let points = points
+            .chunks_exact(32)
+            .map(|c| {
+                CompressedRistretto::from_slice(c)
+                    .ok()
+                    .and_then(|p| p.decompress())
+                    .ok_or_else(|| {
+                        ShamirError::MalformedCommitments("invalid Ristretto point".to_string())
+                    })
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            k,
+            n,
+            secret_len,
+            share_digests,
+            points,
+        })
+    }
+
+    /// Check a share's header and digest, and fold its blocks into a single
+    /// scalar. Returns `(folded value, blinding value)`.
+    fn open(&self, share: &Share) -> Result<(Scalar, Scalar), ShamirError> {
+        let mismatch = if share.scheme != ShareScheme::PedersenVss {
+            Some("scheme")
+        } else if share.k != self.k {
+            Some("k")
+        } else if share.n != self.n {
+            Some("n")
+        } else if share.secret_len != self.secret_len {
+            Some("secret_len")
+        } else {
+            None
+        };
+        if let Some(field) = mismatch {
+            return Err(ShamirError::IncompatibleShare {
+                id: share.id,
+                field,
+            });
+        }
+        if share.id == 0 || share.id > self.n {
+            return Err(ShamirError::MalformedShare {
+                id: share.id,
+                reason: format!("id outside 1..={}", self.n),
+            });
+        }
+
+        let num_blocks = block_count(self.secret_len)
+            .filter(|&blocks| share_len(blocks) == Some(share.data.len()))
+            .ok_or(ShamirError::VerificationFailed { id: share.id })?;
+        if share_digest(share) != self.share_digests[share.id as usize - 1] {
+            return Err(ShamirError::VerificationFailed { id: share.id });
+        }
+
+        let rho = aggregation_challenge(self.k, self.n, self.secret_len, &self.share_digests);
+        let mut folded = Scalar::ZERO;
+        for block_idx in (0..num_blocks).rev() {
+            folded = folded * rho + read_scalar(share, block_idx)?;
+        }
+        let blinding = read_scalar(share, num_blocks)?;
+        Ok((folded, blinding))
+    }
+}
+
+/// `SHA-256(id || data)`, binding a share's value to its position.
+fn share_digest(share: &Share) -> Hash256 {
+    let mut hasher = Sha256::new();
+    hasher.update(share.id.to_be_bytes());
+    hasher.update(&share.data);
+    hasher.finalize().into()
+}
+
+/// Fiat-Shamir challenge used to fold the block polynomials.
+fn aggregation_challenge(k: u32, n: u32, secret_len: u64, share_digests: &[Hash256]) -> Scalar {
+    let mut hasher = Sha512::new();
+    hasher.update(AGGREGATION_LABEL);
+    hasher.update(k.to_be_bytes());
+    hasher.update(n.to_be_bytes());
+    hasher.update(secret_len.to_be_bytes());
+    for digest in share_digests {
+        hasher.update(digest);
+    }
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&hasher.finalize());
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+/// Second Pedersen generator H, derived by hashing to the group so that its
+/// discrete log relative to G is unknown.
+fn blinding_generator() -> RistrettoPoint {
+    let mut wide = [0u8; 64];
+    wide.copy_from_slice(&Sha512::digest(BLINDING_GENERATOR_LABEL));
+    RistrettoPoint::from_uniform_bytes(&wide)
+}
+
+fn random_scalar<R: RngCore + CryptoRng>(rng: &mut R) -> Scalar {
+    let mut wide = [0u8; 64];
+    rng.fill_bytes(&mut wide);
+    Scalar::from_bytes_mod_order_wide(&wide)
+}
+
+/// Secret blocks for a `secret_len` taken from an untrusted share header or
+/// commitment, or `None` if it does not fit in memory.
+fn block_count(secret_len: u64) -> Option<usize> {
+    usize::try_from(secret_len)
+        .ok()
+        .map(|len| len.div_ceil(PAYLOAD_SIZE))
+}
+
+/// Data length of a share carrying `num_blocks` blocks plus the blinding
+/// value, or `None` on overflow.
+fn share_len(num_blocks: usize) -> Option<usize> {
+    num_blocks.checked_add(1)?.checked_mul(BLOCK_SIZE)
+}
+
+fn length_mismatch(share: &Share) -> ShamirError {
+    ShamirError::MalformedShare {
+        id: share.id,
+        reason: format!(
+            "data length {} does not match secret length {}",
+            share.data.len(),
+            share.secret_len
+        ),
+    }
+}
+
+/// Read the canonical scalar stored at `block_idx` in a share.
+fn read_scalar(share: &Share, block_idx: usize) -> Result<Scalar, ShamirError> {
+    let offset = block_idx * BLOCK_SIZE;
+    let bytes: [u8; BLOCK_SIZE] = share.data[offset..offset + BLOCK_SIZE].try_into().unwrap();
+    Option::from(Scalar::from_canonical_bytes(bytes)).ok_or_else(|| ShamirError::MalformedShare {
+        id: share.id,
+        reason: format!("block {block_idx} is not a canonical scalar"),
+    })
+}
+
+fn eval_poly(coeffs: &[Scalar], x: &Scalar) -> Scalar {
+    let mut result = Scalar::ZERO;
+    for coeff in coeffs.iter().rev() {
+        result = result * x + coeff;
+    }
+    result
+}
+
+/// Lagrange basis coefficients L_i(at) = prod_{j!=i} (at - x_j) / (x_i - x_j).
+fn lagrange_basis_at(xs: &[Scalar], at: &Scalar) -> Vec<Scalar> {
+    xs.iter()
+        .enumerate()
+        .map(|(i, xi)| {
+            let mut num = Scalar::ONE;
+            let mut den = Scalar::ONE;
+            for (j, xj) in xs.iter().enumerate() {
+                if i != j {
+                    num *= at - xj;
+                    den *= xi - xj;
+                }
+            }
+            num * den.invert()
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_reconstruct_basic() {
+        let secret = b"verifiable secret sharing";
+        let (shares, _) = split(secret, 3, 5).unwrap();
+        assert_eq!(reconstruct(&shares[2..]).unwrap(), secret);
+    }
+
+    #[test]
+    fn test_multi_block_roundtrip() {
+        let secret: Vec<u8> = (0..1000).map(|i| (i % 256) as u8).collect();
+        let (shares, commitments) = split(&secret, 4, 7).unwrap();
+        assert_eq!(shares[0].data.len(), (secret.len().div_ceil(31) + 1) * 32);
+        assert_eq!(reconstruct(&shares[3..]).unwrap(), secret);
+        assert!(commitments.verify_batch(&shares).is_ok());
+    }
+
+    #[test]
+    fn test_every_share_verifies() {
+        let (shares, commitments) = split(&[0xFF; 200], 3, 6).unwrap();
+        for share in &shares {
+            commitments.verify_share(share).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_tampered_share_rejected() {
+        let (mut shares, commitments) = split(b"tamper with me", 2, 4).unwrap();
+        shares[1].data[0] ^= 0x01;
+        assert!(matches!(
+            commitments.verify_share(&shares[1]),
+            Err(ShamirError::VerificationFailed { id: 2 })
+        ));
+        assert!(matches!(
+            commitments.verify_batch(&shares),
+            Err(ShamirError::VerificationFailed { id: 2 })
+        ));
+    }
+
+    #[test]
+    fn test_share_from_other_split_rejected() {
+        let (shares, _) = split(b"first split", 2, 3).unwrap();
+        let (_, other) = split(b"other split", 2, 3).unwrap();
+        assert!(other.verify_share(&shares[0]).is_err());
+    }
+
+    #[test]
+    fn test_seeded_split_is_reproducible() {
+        use rand::SeedableRng;
+        let seeded = || rand_chacha::ChaCha20Rng::from_seed([4; 32]);
+        let (a, ca) = split_with_rng(&[0x42; 70], 3, 5, &mut seeded()).unwrap();
+        let (b, cb) = split_with_rng(&[0x42; 70], 3, 5, &mut seeded()).unwrap();
+        assert_eq!(a, b);
+        assert_eq!(ca.to_bytes(), cb.to_bytes());
+    }
+
+    #[test]
+    fn test_inconsistent_dealer_detected() {
+        // A share that matches its digest but not the polynomial: the digest
+        // list is recomputed so only the commitment equation can catch it.
+        let (mut shares, mut commitments) = split(&[7u8; 100], 3, 5).unwrap();
+        shares[4].data[32] ^= 0x01;
+        commitments.share_digests = shares.iter().map(share_digest).collect();
+        assert!(matches!(
+            commitments.verify_share(&shares[4]),
+            Err(ShamirError::VerificationFailed { id: 5 })
+        ));
+    }
+
+    #[test]
+    fn test_reconstruct_verified_skips_bad_shares() {
+        let secret = b"byzantine nodes return garbage";
+        let (mut shares, commitments) = split(secret, 3, 5).unwrap();
+        shares[0].data[5] ^= 0xFF;
+        shares[1].data[40] ^= 0xFF;
+
+        // Plain reconstruction silently uses the garbage
+        assert_ne!(reconstruct(&shares).ok().as_deref(), Some(&secret[..]));
+        assert_eq!(reconstruct_verified(&shares, &commitments).unwrap(), secret);
+
+        shares[2].data[0] ^= 0xFF;
+        assert!(matches!(
+            reconstruct_verified(&shares, &commitments),
+            Err(ShamirError::NotEnoughShares { have: 2, need: 3 })
+        ));
+    }
+
+    #[test]
+    fn test_repaired_share_verifies() {
+        let (shares, commitments) = split(&[0x42; 90], 3, 5).unwrap();
+        let repaired = repair_share(&shares[2..], 1).unwrap();
+        assert_eq!(repaired, shares[0]);
+        commitments.verify_share(&repaired).unwrap();
+    }
+
+    #[test]
+    fn test_commitments_roundtrip() {
+        let (shares, commitments) = split(b"serialize me", 3, 4).unwrap();
+        let bytes = commitments.to_bytes();
+        assert_eq!(bytes.len(), COMMITMENT_HEADER_LEN + (4 + 3) * 32);
+
+        let parsed = ShareCommitments::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, commitments);
+        assert!(parsed.verify_batch(&shares).is_ok());
+    }
+
+    #[test]
+    fn test_oversized_secret_len_rejected() {
+        let (mut shares, commitments) = split(b"vss secret", 2, 3).unwrap();
+        for share in shares.iter_mut() {
+            share.secret_len = u64::MAX;
+        }
+        assert!(matches!(reconstruct(&shares), Err(ShamirError::MalformedShare { .. })));
+        assert!(repair_share(&shares[..2], 3).is_err());
+        assert!(commitments.verify_share(&shares[0]).is_err());
+    }
+
+    #[test]
+    fn test_malformed_commitments_rejected() {
+        let (_, commitments) = split(b"x", 2, 2).unwrap();
+        let mut bytes = commitments.to_bytes();
+        assert!(ShareCommitments::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // Overwrite the last point with a non-canonical encoding
+        let len = bytes.len();
+        bytes[len - 32..].copy_from_slice(&[0xFF; 32]);
+        assert!(matches!(
+            ShareCommitments::from_bytes(&bytes),
+            Err(ShamirError::MalformedCommitments(_))
+        ));
+    }
+}
@@ -1,4 +1,5 @@
 use gitgold_core::types::Address;
+use rand::{CryptoRng, RngCore};
 
 use crate::keys::{KeyPair, PublicKey};
 
@@ -15,6 +16,13 @@ impl Wallet {
         }
     }
 
+    /// Create a wallet whose key pair is generated from `rng`.
+    pub fn new_with_rng<R: RngCore + CryptoRng>(rng: &mut R) -> Self {
+        Self {
+            key_pair: KeyPair::generate_with_rng(rng),
+        }
+    }
+
     /// Create a wallet from existing secret key bytes.
     pub fn from_secret(bytes: &[u8; 32]) -> Self {
         Self {
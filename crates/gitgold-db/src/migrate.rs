@@ -0,0 +1,198 @@
+use gitgold_core::error::MigrationError;
+use gitgold_core::time::unix_now;
+use rusqlite::{Connection, OptionalExtension};
+
+/// One forward schema change.
+///
+/// Migrations are listed in version order starting at 1, each one step
+/// newer than the last (`check_numbering`), and are never edited once
+/// released: a change to the schema is a new migration.
+///
+/// Databases created before versioning have their tables but no
+/// `schema_version` rows, so `migrate` runs version 1 on them too. A
+/// version 1 migration must therefore create its tables and indexes with
+/// `IF NOT EXISTS`, adopting such a database as it stands.
+#[derive(Debug, Clone, Copy)]
+pub struct Migration {
+    pub version: u32,
+    pub description: &'static str,
+    /// Statements run to move the schema from `version - 1` to `version`.
+    pub sql: &'static str,
+}
+
+/// Bring a database up to the newest version in `migrations`, returning
+/// that version.
+///
+/// The applied versions are recorded in a `schema_version` table. Each
+/// pending migration runs in its own transaction together with its
+/// `schema_version` row, so an interrupted upgrade resumes at the first
+/// migration that did not commit. A database without the table is at
+/// version 0. Databases written by a newer release (a recorded version past
+/// the last migration) are refused with `MigrationError::TooNew` and left
+/// untouched. A misnumbered list fails with `MigrationError::Failed`
+/// before anything is applied.
+pub fn migrate(conn: &Connection, migrations: &[Migration]) -> Result<u32, MigrationError> {
+    check_numbering(migrations)?;
+    let latest = migrations.last().map_or(0, |m| m.version);
+
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS schema_version (
+            version     INTEGER PRIMARY KEY,
+            description TEXT NOT NULL,
+            applied_at  INTEGER NOT NULL
+        );",
+    )
+    .map_err(|e| MigrationError::Database(e.to_string()))?;
+
+    let current = schema_version(conn)?;
+    if current > latest {
+        return Err(MigrationError::TooNew {
+            found: current,
+            supported: latest,
+        });
+    }
+
+    for migration in &migrations[current as usize..] {
+        let failed = |e: rusqlite::Error| MigrationError::Failed {
+            version: migration.version,
+            reason: e.to_string(),
+        };
+        let tx = conn.unchecked_transaction().map_err(failed)?;
+        tx.execute_batch(migration.sql).map_err(failed)?;
+        tx.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (?1, ?2, ?3)",
+            rusqlite::params![migration.version, migration.description, unix_now()],
+        )
+        .map_err(failed)?;
+        tx.commit().map_err(failed)?;
+    }
+
+    Ok(latest)
+}
+
+/// Check that `migrations` are numbered consecutively from 1.
+pub fn check_numbering(migrations: &[Migration]) -> Result<(), MigrationError> {
+    match migrations
+        .iter()
+        .zip(1..)
+        .find(|(migration, expected)| migration.version != *expected)
+    {
+        Some((migration, expected)) => Err(MigrationError::Failed {
+            version: migration.version,
+            reason: format!("expected version {expected}: migrations must be numbered consecutively from 1"),
+        }),
+        None => Ok(()),
+    }
+}
+
+/// The schema version recorded in a database; 0 if none is.
+pub fn schema_version(conn: &Connection) -> Result<u32, MigrationError> {
+    let has_table: bool = conn
+        .query_row(
+            "SELECT COUNT(*) > 0 FROM sqlite_master WHERE type = 'table' AND name = 'schema_version'",
+            [],
+            |row| row.get(0),
+        )
+        .map_err(|e| MigrationError::Database(e.to_string()))?;
+    if !has_table {
+        return Ok(0);
+    }
+
+    let version: Option<u32> = conn
+        .query_row("SELECT MAX(version) FROM schema_version", [], |row| row.get(0))
+        .optional()
+        .map_err(|e| MigrationError::Database(e.to_string()))?
+        .flatten();
+    Ok(version.unwrap_or(0))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MIGRATIONS: &[Migration] = &[
+        Migration {
+            version: 1,
+            description: "items",
+            sql: "CREATE TABLE items (id INTEGER PRIMARY KEY, name TEXT NOT NULL);",
+        },
+        Migration {
+            version: 2,
+            description: "item price",
+            sql: "ALTER TABLE items ADD COLUMN price INTEGER NOT NULL DEFAULT 0;",
+        },
+    ];
+
+    #[test]
+    fn test_fresh_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        assert_eq!(migrate(&conn, MIGRATIONS).unwrap(), 2);
+        assert_eq!(schema_version(&conn).unwrap(), 2);
+        conn.execute("INSERT INTO items (name, price) VALUES ('a', 3)", [])
+            .unwrap();
+
+        // Re-running is a no-op
+        assert_eq!(migrate(&conn, MIGRATIONS).unwrap(), 2);
+    }
+
+    #[test]
+    fn test_upgrade_keeps_data() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn, &MIGRATIONS[..1]).unwrap();
+        conn.execute("INSERT INTO items (name) VALUES ('old')", [])
+            .unwrap();
+
+        migrate(&conn, MIGRATIONS).unwrap();
+        let price: i64 = conn
+            .query_row("SELECT price FROM items WHERE name = 'old'", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(price, 0);
+    }
+
+    #[test]
+    fn test_refuses_newer_database() {
+        let conn = Connection::open_in_memory().unwrap();
+        migrate(&conn, MIGRATIONS).unwrap();
+
+        assert!(matches!(
+            migrate(&conn, &MIGRATIONS[..1]),
+            Err(MigrationError::TooNew { found: 2, supported: 1 })
+        ));
+    }
+
+    #[test]
+    fn test_misnumbered_migrations_rejected() {
+        let conn = Connection::open_in_memory().unwrap();
+        let gap = [MIGRATIONS[0], Migration { version: 3, ..MIGRATIONS[1] }];
+
+        assert!(matches!(
+            migrate(&conn, &gap),
+            Err(MigrationError::Failed { version: 3, .. })
+        ));
+        assert_eq!(schema_version(&conn).unwrap(), 0);
+        check_numbering(MIGRATIONS).unwrap();
+    }
+
+    #[test]
+    fn test_failed_migration_rolls_back() {
+        let conn = Connection::open_in_memory().unwrap();
+        let broken = [
+            MIGRATIONS[0],
+            Migration {
+                version: 2,
+                description: "broken",
+                sql: "ALTER TABLE items ADD COLUMN extra TEXT; SELECT * FROM missing;",
+            },
+        ];
+
+        assert!(matches!(
+            migrate(&conn, &broken),
+            Err(MigrationError::Failed { version: 2, .. })
+        ));
+        assert_eq!(schema_version(&conn).unwrap(), 1);
+        // The partial step was rolled back, so the fixed migration applies
+        migrate(&conn, MIGRATIONS).unwrap();
+        assert!(conn.prepare("SELECT extra FROM items").is_err());
+    }
+}
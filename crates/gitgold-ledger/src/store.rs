@@ -1,5 +1,6 @@
 use gitgold_core::error::LedgerError;
 use gitgold_core::types::{Address, MicroGitGold, TransactionType};
+use gitgold_db::migrate::{migrate, Migration};
 use rusqlite::Connection;
 use std::collections::HashSet;
 
@@ -8,6 +9,28 @@ use crate::merkle::MerkleTree;
 use crate::supply::SupplyTracker;
 use crate::transaction::Transaction;
 
+/// Ledger database schema history, applied by `migrate` on open.
+pub const LEDGER_MIGRATIONS: &[Migration] = &[Migration {
+    version: 1,
+    description: "transactions table",
+    sql: "
+        CREATE TABLE IF NOT EXISTS transactions (
+            tx_id       TEXT PRIMARY KEY,
+            tx_type     TEXT NOT NULL,
+            from_addr   TEXT NOT NULL,
+            to_addr     TEXT NOT NULL,
+            amount      INTEGER NOT NULL,
+            metadata    TEXT NOT NULL,
+            timestamp   INTEGER NOT NULL,
+            signature   TEXT NOT NULL
+        );
+
+        CREATE INDEX IF NOT EXISTS idx_tx_from ON transactions (from_addr);
+        CREATE INDEX IF NOT EXISTS idx_tx_to   ON transactions (to_addr);
+        CREATE INDEX IF NOT EXISTS idx_tx_time ON transactions (timestamp);
+    ",
+}];
+
 /// Append-only ledger backed by SQLite.
 ///
 /// On open, replays all transactions to rebuild balances.
@@ -34,25 +57,7 @@ impl Ledger {
     }
 
     fn init(conn: Connection) -> Result<Self, LedgerError> {
-        conn.execute_batch(
-            "
-            CREATE TABLE IF NOT EXISTS transactions (
-                tx_id       TEXT PRIMARY KEY,
-                tx_type     TEXT NOT NULL,
-                from_addr   TEXT NOT NULL,
-                to_addr     TEXT NOT NULL,
-                amount      INTEGER NOT NULL,
-                metadata    TEXT NOT NULL,
-                timestamp   INTEGER NOT NULL,
-                signature   TEXT NOT NULL
-            );
-
-            CREATE INDEX IF NOT EXISTS idx_tx_from ON transactions (from_addr);
-            CREATE INDEX IF NOT EXISTS idx_tx_to   ON transactions (to_addr);
-            CREATE INDEX IF NOT EXISTS idx_tx_time ON transactions (timestamp);
-            ",
-        )
-        .map_err(|e| LedgerError::Database(e.to_string()))?;
+        migrate(&conn, LEDGER_MIGRATIONS)?;
 
         let mut ledger = Self {
             conn,
@@ -210,6 +215,7 @@ impl Ledger {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gitgold_core::error::MigrationError;
 
     fn mint_tx(to: &str, amount: MicroGitGold) -> Transaction {
         Transaction {
@@ -337,4 +343,44 @@ mod tests {
         ledger.append(mint_tx("bob", 200)).unwrap();
         assert_eq!(ledger.tx_count(), 2);
     }
+
+    #[test]
+    fn test_upgrade_v1_fixture() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.sqlite");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(include_str!("../test-fixtures/ledger_v1.sql"))
+            .unwrap();
+        drop(conn);
+
+        let ledger = Ledger::open(path.to_str().unwrap()).unwrap();
+        assert_eq!(gitgold_db::migrate::schema_version(&ledger.conn).unwrap(), 1);
+        assert_eq!(ledger.balance(&Address::new("alice")), 750_000);
+        assert_eq!(ledger.balance(&Address::new("bob")), 250_000);
+    }
+
+    #[test]
+    fn test_migrations_numbered() {
+        gitgold_db::migrate::check_numbering(LEDGER_MIGRATIONS).unwrap();
+    }
+
+    #[test]
+    fn test_refuses_newer_schema() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("ledger.sqlite");
+        drop(Ledger::open(path.to_str().unwrap()).unwrap());
+
+        let conn = Connection::open(&path).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at) VALUES (2, 'future', 0)",
+            [],
+        )
+        .unwrap();
+        drop(conn);
+
+        assert!(matches!(
+            Ledger::open(path.to_str().unwrap()),
+            Err(LedgerError::Migration(MigrationError::TooNew { found: 2, supported: 1 }))
+        ));
+    }
 }
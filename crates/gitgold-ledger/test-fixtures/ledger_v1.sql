@@ -0,0 +1,22 @@
+-- Ledger database as written by the v1 release, before schema versioning:
+-- no schema_version table. Alice is minted 1,000,000 and sends 250,000 to bob.
+
+CREATE TABLE transactions (
+    tx_id       TEXT PRIMARY KEY,
+    tx_type     TEXT NOT NULL,
+    from_addr   TEXT NOT NULL,
+    to_addr     TEXT NOT NULL,
+    amount      INTEGER NOT NULL,
+    metadata    TEXT NOT NULL,
+    timestamp   INTEGER NOT NULL,
+    signature   TEXT NOT NULL
+);
+
+CREATE INDEX idx_tx_from ON transactions (from_addr);
+CREATE INDEX idx_tx_to   ON transactions (to_addr);
+CREATE INDEX idx_tx_time ON transactions (timestamp);
+
+INSERT INTO transactions VALUES
+    ('tx-mint', 'Mint', '0000000000000000000000000000000000000000000000000000000000000000',
+     'alice', 1000000, '{}', 1700000000, ''),
+    ('tx-send', 'Transfer', 'alice', 'bob', 250000, '{}', 1700000060, '');
@@ -1,5 +1,8 @@
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion};
-use gitgold_storage::chunk::{chunk_data, reassemble_chunks, DEFAULT_CHUNK_SIZE};
+use gitgold_core::config::GitGoldConfig;
+use gitgold_crypto::scheme;
+use gitgold_storage::chunk::{chunk_data, chunk_data_cdc, reassemble_chunks, DEFAULT_CHUNK_SIZE};
+use gitgold_storage::parallel::{reconstruct_chunks_parallel, split_chunks_parallel};
 
 fn bench_chunk_data(c: &mut Criterion) {
     let sizes: &[(usize, &str)] = &[
@@ -20,6 +23,30 @@ fn bench_chunk_data(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_chunk_data_cdc(c: &mut Criterion) {
+    let sizes: &[(usize, &str)] = &[(1_500_000, "1.5MB"), (10_000_000, "10MB")];
+
+    let mut group = c.benchmark_group("chunk_data_cdc");
+    for &(size, label) in sizes {
+        // Non-uniform input so cut points are found by the hash, not max_size
+        let data: Vec<u8> = (0..size as u32)
+            .map(|i| (i.wrapping_mul(2_654_435_761) >> 11) as u8)
+            .collect();
+        group.bench_with_input(BenchmarkId::new("fastcdc_512KB_avg", label), &data, |bench, data| {
+            bench.iter(|| {
+                chunk_data_cdc(
+                    black_box(data),
+                    DEFAULT_CHUNK_SIZE / 4,
+                    DEFAULT_CHUNK_SIZE,
+                    DEFAULT_CHUNK_SIZE * 4,
+                )
+                .unwrap()
+            })
+        });
+    }
+    group.finish();
+}
+
 fn bench_reassemble(c: &mut Criterion) {
     let sizes: &[(usize, &str)] = &[
         (1_024, "1KB"),
@@ -52,5 +79,56 @@ fn bench_roundtrip(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, bench_chunk_data, bench_reassemble, bench_roundtrip,);
+fn bench_split_chunks(c: &mut Criterion) {
+    let config = GitGoldConfig::default();
+    let data = vec![0xABu8; 4 * 1024 * 1024];
+    let chunks = chunk_data(&data, DEFAULT_CHUNK_SIZE);
+
+    let mut group = c.benchmark_group("split_chunks_4MB_k5n9");
+    group.sample_size(10);
+    group.bench_function("serial", |bench| {
+        bench.iter(|| {
+            black_box(&chunks)
+                .iter()
+                .map(|(_, chunk)| scheme::split_with_config(chunk, &config).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("parallel", |bench| {
+        bench.iter(|| split_chunks_parallel(black_box(&chunks), &config, &[0; 32]).unwrap())
+    });
+    group.finish();
+}
+
+fn bench_reconstruct_chunks(c: &mut Criterion) {
+    let config = GitGoldConfig::default();
+    let data = vec![0xABu8; 4 * 1024 * 1024];
+    let chunks = chunk_data(&data, DEFAULT_CHUNK_SIZE);
+    let split = split_chunks_parallel(&chunks, &config, &[0; 32]).unwrap();
+
+    let mut group = c.benchmark_group("reconstruct_chunks_4MB_k5n9");
+    group.sample_size(10);
+    group.bench_function("serial", |bench| {
+        bench.iter(|| {
+            black_box(&split)
+                .iter()
+                .map(|(_, shares)| scheme::reconstruct_any(shares).unwrap())
+                .collect::<Vec<_>>()
+        })
+    });
+    group.bench_function("parallel", |bench| {
+        bench.iter(|| reconstruct_chunks_parallel(black_box(&split)).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    bench_chunk_data,
+    bench_chunk_data_cdc,
+    bench_reassemble,
+    bench_roundtrip,
+    bench_split_chunks,
+    bench_reconstruct_chunks,
+);
 criterion_main!(benches);
@@ -1,6 +1,27 @@
+use gitgold_core::config::GitGoldConfig;
+use gitgold_core::error::StorageError;
+use gitgold_core::types::ChunkingStrategy;
+
 /// Default chunk size: 512 KB.
 pub const DEFAULT_CHUNK_SIZE: usize = 512 * 1024;
 
+/// Gear table for the FastCDC rolling hash: 256 pseudo-random 64-bit values
+/// from a fixed SplitMix64 sequence, so cut points are stable across builds.
+const GEAR: [u64; 256] = {
+    let mut table = [0u64; 256];
+    let mut state: u64 = 0x6769_7467_6f6c_6463; // "gitgoldc"
+    let mut i = 0;
+    while i < 256 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        table[i] = z ^ (z >> 31);
+        i += 1;
+    }
+    table
+};
+
 /// Split data into fixed-size chunks.
 ///
 /// Returns a vector of (chunk_index, chunk_data) tuples.
@@ -12,6 +33,128 @@ pub fn chunk_data(data: &[u8], chunk_size: usize) -> Vec<(u32, Vec<u8>)> {
         .collect()
 }
 
+/// Split data into content-defined chunks with FastCDC.
+///
+/// A Gear rolling hash is computed over each byte after `min_size`; a chunk
+/// ends where the top bits of the hash are zero. Up to `avg_size` a stricter
+/// mask is used and after it a looser one (normalized chunking), which keeps
+/// sizes close to the average. Chunks never exceed `max_size`. Because cut
+/// points depend only on nearby content, inserting or deleting bytes changes
+/// the chunks around the edit and leaves the rest identical.
+///
+/// Fails with `InvalidChunking` unless `0 < min_size <= avg_size <= max_size`.
+pub fn chunk_data_cdc(
+    data: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+) -> Result<Vec<(u32, Vec<u8>)>, StorageError> {
+    check_cdc_bounds(min_size, avg_size, max_size)?;
+    let mut chunks = Vec::new();
+    let mut offset = 0;
+    while offset < data.len() {
+        let len = cut_point(&data[offset..], min_size, avg_size, max_size);
+        chunks.push((chunks.len() as u32, data[offset..offset + len].to_vec()));
+        offset += len;
+    }
+    Ok(chunks)
+}
+
+/// Split data with the chunking strategy selected in `config`.
+///
+/// The strategy may come from user input, so it is checked first (see
+/// `check_chunking`).
+pub fn chunk_with_config(
+    data: &[u8],
+    config: &GitGoldConfig,
+) -> Result<Vec<(u32, Vec<u8>)>, StorageError> {
+    check_chunking(config)?;
+    match config.chunking {
+        ChunkingStrategy::Fixed => Ok(chunk_data(data, config.chunk_size)),
+        ChunkingStrategy::FastCdc {
+            min_size,
+            avg_size,
+            max_size,
+        } => chunk_data_cdc(data, min_size, avg_size, max_size),
+    }
+}
+
+/// Check that `config` describes a usable chunking: a non-zero
+/// `chunk_size` for fixed chunks, or FastCDC bounds with
+/// `0 < min_size <= avg_size <= max_size`.
+pub fn check_chunking(config: &GitGoldConfig) -> Result<(), StorageError> {
+    match config.chunking {
+        ChunkingStrategy::Fixed if config.chunk_size == 0 => Err(StorageError::InvalidChunking(
+            "chunk_size must be non-zero".to_string(),
+        )),
+        ChunkingStrategy::Fixed => Ok(()),
+        ChunkingStrategy::FastCdc {
+            min_size,
+            avg_size,
+            max_size,
+        } => check_cdc_bounds(min_size, avg_size, max_size),
+    }
+}
+
+fn check_cdc_bounds(min_size: usize, avg_size: usize, max_size: usize) -> Result<(), StorageError> {
+    if 0 < min_size && min_size <= avg_size && avg_size <= max_size {
+        Ok(())
+    } else {
+        Err(StorageError::InvalidChunking(format!(
+            "FastCDC bounds must satisfy 0 < min <= avg <= max, got min {min_size}, avg {avg_size}, max {max_size}"
+        )))
+    }
+}
+
+/// Length of the first FastCDC chunk of `data`.
+///
+/// Only `data[..max_size]` is examined, so the result is the same whether
+/// `data` is the whole remaining input or a buffer of at least `max_size`
+/// bytes. Fails with `InvalidChunking` on invalid size bounds, as
+/// `chunk_data_cdc`.
+pub fn cdc_cut_point(
+    data: &[u8],
+    min_size: usize,
+    avg_size: usize,
+    max_size: usize,
+) -> Result<usize, StorageError> {
+    check_cdc_bounds(min_size, avg_size, max_size)?;
+    Ok(cut_point(data, min_size, avg_size, max_size))
+}
+
+/// `cdc_cut_point` for bounds already checked.
+pub(crate) fn cut_point(data: &[u8], min_size: usize, avg_size: usize, max_size: usize) -> usize {
+    if data.len() <= min_size {
+        return data.len();
+    }
+
+    let bits = avg_size.ilog2();
+    let mask_small = top_bits_mask(bits + 1);
+    let mask_large = top_bits_mask(bits.saturating_sub(1));
+    let end = data.len().min(max_size);
+    let normal = avg_size.min(end);
+
+    let mut hash = 0u64;
+    for (i, &byte) in data.iter().enumerate().take(end).skip(min_size) {
+        hash = (hash << 1).wrapping_add(GEAR[byte as usize]);
+        let mask = if i < normal { mask_small } else { mask_large };
+        if hash & mask == 0 {
+            return i + 1;
+        }
+    }
+    end
+}
+
+/// Mask selecting the `bits` most significant bits, which in a Gear hash
+/// depend on the widest window of input.
+fn top_bits_mask(bits: u32) -> u64 {
+    match bits {
+        0 => 0,
+        64.. => u64::MAX,
+        _ => u64::MAX << (64 - bits),
+    }
+}
+
 /// Reassemble chunks into the original data.
 ///
 /// Chunks must be sorted by index. Missing chunks cause an error.
@@ -90,6 +233,106 @@ mod tests {
         assert!(reassemble_chunks(chunks).is_err());
     }
 
+    /// Deterministic pseudo-random bytes (xorshift64).
+    fn noise(len: usize, seed: u64) -> Vec<u8> {
+        let mut state = seed;
+        (0..len)
+            .map(|_| {
+                state ^= state << 13;
+                state ^= state >> 7;
+                state ^= state << 17;
+                state as u8
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_cdc_respects_bounds() {
+        let data = noise(400_000, 1);
+        let chunks = chunk_data_cdc(&data, 2048, 8192, 32768).unwrap();
+        assert!(chunks.len() > 10);
+        for (i, (idx, chunk)) in chunks.iter().enumerate() {
+            assert_eq!(*idx, i as u32);
+            assert!(chunk.len() <= 32768);
+            if i + 1 < chunks.len() {
+                assert!(chunk.len() >= 2048);
+            }
+        }
+        let mean = data.len() / chunks.len();
+        assert!((4096..=16384).contains(&mean), "mean chunk size {mean}");
+        assert_eq!(reassemble_chunks(chunks).unwrap(), data);
+    }
+
+    #[test]
+    fn test_cdc_insertion_keeps_most_chunks() {
+        let original = noise(1_000_000, 2);
+        let mut edited = vec![0x42];
+        edited.extend_from_slice(&original);
+
+        let before = chunk_data_cdc(&original, 4096, 16384, 65536).unwrap();
+        let after = chunk_data_cdc(&edited, 4096, 16384, 65536).unwrap();
+        let known: std::collections::HashSet<&Vec<u8>> = before.iter().map(|(_, c)| c).collect();
+        let reused = after.iter().filter(|(_, c)| known.contains(c)).count();
+        assert!(reused + 2 >= after.len(), "only {reused} of {} chunks reused", after.len());
+
+        // Fixed-size chunking shares nothing after a one-byte shift
+        let fixed_before = chunk_data(&original, 16384);
+        let fixed_known: std::collections::HashSet<&Vec<u8>> =
+            fixed_before.iter().map(|(_, c)| c).collect();
+        let fixed_after = chunk_data(&edited, 16384);
+        assert_eq!(fixed_after.iter().filter(|(_, c)| fixed_known.contains(c)).count(), 0);
+    }
+
+    #[test]
+    fn test_cdc_uniform_data_hits_max() {
+        let chunks = chunk_data_cdc(&[0u8; 10_000], 100, 1000, 3000).unwrap();
+        let sizes: Vec<usize> = chunks.iter().map(|(_, c)| c.len()).collect();
+        assert!(sizes.iter().all(|&s| s <= 3000));
+        assert_eq!(sizes.iter().sum::<usize>(), 10_000);
+    }
+
+    #[test]
+    fn test_chunk_with_config() {
+        let data = noise(50_000, 3);
+        let fixed = GitGoldConfig {
+            chunk_size: 4096,
+            ..GitGoldConfig::default()
+        };
+        assert_eq!(chunk_with_config(&data, &fixed).unwrap(), chunk_data(&data, 4096));
+
+        let cdc = GitGoldConfig {
+            chunking: ChunkingStrategy::fast_cdc(4096),
+            ..GitGoldConfig::default()
+        };
+        assert_eq!(
+            chunk_with_config(&data, &cdc).unwrap(),
+            chunk_data_cdc(&data, 1024, 4096, 16384).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_invalid_chunking_rejected() {
+        assert!(matches!(
+            chunk_data_cdc(b"data", 0, 16, 64),
+            Err(StorageError::InvalidChunking(_))
+        ));
+        assert!(cdc_cut_point(b"data", 32, 16, 64).is_err());
+
+        // fast_cdc(avg) has min_size 0 for avg < 4
+        for chunking in [ChunkingStrategy::fast_cdc(3), ChunkingStrategy::fast_cdc(0)] {
+            let config = GitGoldConfig {
+                chunking,
+                ..GitGoldConfig::default()
+            };
+            assert!(chunk_with_config(b"data", &config).is_err());
+        }
+        let config = GitGoldConfig {
+            chunk_size: 0,
+            ..GitGoldConfig::default()
+        };
+        assert!(check_chunking(&config).is_err());
+    }
+
     #[test]
     fn test_empty_data() {
         let chunks = chunk_data(b"", 512);
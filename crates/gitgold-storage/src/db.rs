@@ -1,191 +1,1377 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{Read, Seek, SeekFrom, Write};
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+
 use gitgold_core::error::StorageError;
-use rusqlite::Connection;
-use std::time::{SystemTime, UNIX_EPOCH};
+use gitgold_core::time::unix_now;
+use gitgold_core::types::Address;
+use gitgold_crypto::hash::sha256_hex;
+use gitgold_crypto::vss::ShareCommitments;
+use rusqlite::{Connection, DatabaseName, OptionalExtension, Transaction};
 
+use crate::backend::check_range;
+use crate::encryption::{self, EncryptionKey, SEGMENT_LEN, SEGMENT_OVERHEAD};
+pub use crate::backend::{
+    ChallengeRecord, FragmentBackend, FragmentKey, FragmentMeta, FragmentRecord, FragmentStatus,
+    GcEntry, GcReason, Lease, NodeChallengeStats, RepairReport, RepoUsage, StorageStats,
+};
+use crate::manifest::RepoManifest;
 use crate::schema::init_schema;
 
-/// Fragment metadata returned from queries.
-#[derive(Debug, Clone)]
-pub struct FragmentRecord {
-    pub repo_hash: String,
-    pub fragment_id: u32,
-    pub share_id: u32,
-    pub data: Vec<u8>,
-    pub data_hash: String,
-    pub stored_at: i64,
-    pub last_challenged: Option<i64>,
-}
-
-/// SQLite-backed fragment store.
+/// SQLite-indexed fragment store.
+///
+/// Blob bytes live either inside the database (`open`, `in_memory`) or as
+/// files under a directory (`open_fs`), where SQLite only holds the index.
+/// Blob files are sharded by the first two bytes of their hash
+/// (`blobs/ab/cd/abcd...`) so no directory grows unboundedly.
+///
+/// With an encryption key set (`set_encryption_key`), blob bytes are sealed
+/// with ChaCha20-Poly1305 before they are written, inline or to a file.
+/// Blobs are read with whichever key of the store's keyring they were
+/// sealed with.
 pub struct FragmentStore {
     conn: Connection,
+    /// Directory holding blob files, or `None` when blobs are stored inline.
+    blob_dir: Option<PathBuf>,
+    /// Keys blobs can be read with, by key id.
+    keyring: HashMap<String, EncryptionKey>,
+    /// Id of the keyring key sealing newly written blobs, or `None` to
+    /// store plaintext.
+    active_key: Option<String>,
 }
 
 impl FragmentStore {
     /// Open (or create) a fragment store at the given path.
     pub fn open(path: &str) -> Result<Self, StorageError> {
         let conn = Connection::open(path).map_err(|e| StorageError::Database(e.to_string()))?;
-        init_schema(&conn).map_err(|e| StorageError::Database(e.to_string()))?;
-        Ok(Self { conn })
+        init_schema(&conn)?;
+        Ok(Self {
+            conn,
+            blob_dir: None,
+            keyring: HashMap::new(),
+            active_key: None,
+        })
     }
 
     /// Create an in-memory fragment store (for tests).
     pub fn in_memory() -> Result<Self, StorageError> {
         let conn =
             Connection::open_in_memory().map_err(|e| StorageError::Database(e.to_string()))?;
-        init_schema(&conn).map_err(|e| StorageError::Database(e.to_string()))?;
-        Ok(Self { conn })
+        init_schema(&conn)?;
+        Ok(Self {
+            conn,
+            blob_dir: None,
+            keyring: HashMap::new(),
+            active_key: None,
+        })
+    }
+
+    /// Open (or create) a filesystem fragment store rooted at `root`.
+    ///
+    /// Blob data is written to files under `root/blobs`; the SQLite index is
+    /// kept at `root/index.sqlite`.
+    pub fn open_fs(root: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let root = root.as_ref();
+        let blob_dir = root.join(BLOB_DIR);
+        fs::create_dir_all(&blob_dir).map_err(|e| StorageError::Io(e.to_string()))?;
+        let conn = Connection::open(root.join("index.sqlite"))
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        init_schema(&conn)?;
+        Ok(Self {
+            conn,
+            blob_dir: Some(blob_dir),
+            keyring: HashMap::new(),
+            active_key: None,
+        })
+    }
+
+    /// Encrypt blobs written from now on with `key`, or store them as
+    /// plaintext with `None`.
+    ///
+    /// The key joins the store's keyring, which keeps every key set or
+    /// added: blobs already stored keep the key they were sealed with and
+    /// stay readable while it is in the keyring (plaintext blobs are always
+    /// readable). Use `rotate_encryption_key` to re-encrypt them.
+    pub fn set_encryption_key(&mut self, key: Option<EncryptionKey>) {
+        self.active_key = key.map(|key| {
+            let id = key.id().to_string();
+            self.keyring.insert(id.clone(), key);
+            id
+        });
+    }
+
+    /// Add a key for reading blobs sealed with it, without sealing new
+    /// blobs with it (for example the previous key after a restart).
+    pub fn add_decryption_key(&mut self, key: EncryptionKey) {
+        self.keyring.insert(key.id().to_string(), key);
+    }
+
+    /// Id of the key new blobs are sealed with, if any.
+    pub fn encryption_key_id(&self) -> Option<&str> {
+        self.active_key.as_deref()
+    }
+
+    /// Make `new_key` the store's key, then re-encrypt every blob not yet
+    /// sealed with it (or decrypt them all, with `None`). Returns the number
+    /// of blobs rewritten.
+    ///
+    /// Each blob is opened with the keyring key it names and rewritten in
+    /// its own step: an interrupted rotation leaves every blob readable
+    /// under either its old key or the new one, and running it again
+    /// finishes the remaining blobs as long as their keys are in the
+    /// keyring.
+    pub fn rotate_encryption_key(
+        &mut self,
+        new_key: Option<EncryptionKey>,
+    ) -> Result<u64, StorageError> {
+        self.set_encryption_key(new_key);
+        let pending: Vec<(String, Option<String>)> = {
+            let mut stmt = self
+                .conn
+                .prepare("SELECT hash, key_id FROM blobs WHERE key_id IS NOT ?1")
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            let rows = stmt
+                .query_map(rusqlite::params![self.active_key], |row| {
+                    Ok((row.get(0)?, row.get(1)?))
+                })
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            rows.collect::<Result<_, _>>()
+                .map_err(|e| StorageError::Database(e.to_string()))?
+        };
+
+        for (data_hash, key_id) in &pending {
+            let inline: Option<Vec<u8>> = self
+                .conn
+                .query_row(
+                    "SELECT data FROM blobs WHERE hash = ?1",
+                    rusqlite::params![data_hash],
+                    |row| row.get(0),
+                )
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            let data = self.load_blob(inline, key_id.as_deref(), data_hash)?;
+            let (stored, new_id) = self.seal_blob(data_hash, &data);
+
+            match &self.blob_dir {
+                Some(dir) => {
+                    // New file first, so the index never names a missing one
+                    let new_name = blob_file_name(data_hash, new_id);
+                    write_blob_file(&blob_path(dir, &new_name), &stored)?;
+                    self.conn
+                        .execute(
+                            "UPDATE blobs SET key_id = ?1 WHERE hash = ?2",
+                            rusqlite::params![new_id, data_hash],
+                        )
+                        .map_err(|e| StorageError::Database(e.to_string()))?;
+                    let old_name = blob_file_name(data_hash, key_id.as_deref());
+                    let _ = fs::remove_file(blob_path(dir, &old_name));
+                }
+                None => {
+                    self.conn
+                        .execute(
+                            "UPDATE blobs SET data = ?1, key_id = ?2 WHERE hash = ?3",
+                            rusqlite::params![stored, new_id, data_hash],
+                        )
+                        .map_err(|e| StorageError::Database(e.to_string()))?;
+                }
+            }
+        }
+
+        Ok(pending.len() as u64)
+    }
+
+    /// Start writing fragments of one repository in a single transaction.
+    ///
+    /// Nothing written through the returned `RepoWriteTransaction` is
+    /// visible to other connections until `commit`; dropping it without
+    /// committing rolls every write back. Other writes through this store
+    /// fail while it is open.
+    ///
+    /// The transaction writes to this store directly, bypassing any
+    /// `QuotaStore` wrapping it; use `QuotaStore::begin_repo_write` to have
+    /// each write checked against its quotas.
+    pub fn begin_repo_write(
+        &self,
+        repo_hash: &str,
+    ) -> Result<RepoWriteTransaction<'_>, StorageError> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        Ok(RepoWriteTransaction {
+            store: self,
+            tx,
+            repo_hash: repo_hash.to_string(),
+            stored_at: unix_now(),
+            freed: Vec::new(),
+            written: 0,
+        })
     }
 
-    /// Store a fragment. Replaces any existing fragment with the same key.
+    /// Store `data` as a blob, or take one more reference to the identical
+    /// blob already stored. Returns the blob hash.
+    ///
+    /// Blob files are written before the caller's transaction commits; if it
+    /// rolls back, the file is left behind unreferenced.
+    fn acquire_blob(&self, conn: &Connection, data: &[u8]) -> Result<String, StorageError> {
+        let data_hash = sha256_hex(data);
+        let rows = conn
+            .prepare_cached("UPDATE blobs SET refcount = refcount + 1 WHERE hash = ?1")
+            .and_then(|mut stmt| stmt.execute(rusqlite::params![data_hash]))
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows > 0 {
+            return Ok(data_hash);
+        }
+
+        let (stored, key_id) = self.seal_blob(&data_hash, data);
+        let inline = match &self.blob_dir {
+            Some(dir) => {
+                let name = blob_file_name(&data_hash, key_id);
+                write_blob_file(&blob_path(dir, &name), &stored)?;
+                None
+            }
+            None => Some(&stored),
+        };
+        conn.prepare_cached(
+            "INSERT INTO blobs (hash, data, size, refcount, key_id) VALUES (?1, ?2, ?3, 1, ?4)",
+        )
+        .and_then(|mut stmt| {
+            stmt.execute(rusqlite::params![data_hash, inline, data.len() as i64, key_id])
+        })
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(data_hash)
+    }
+
+    /// Drop one reference to a blob, deleting its index row when none
+    /// remain. The file names of deleted blobs are pushed to `freed` so the
+    /// files can be removed once the transaction has committed.
+    fn release_blob(
+        &self,
+        conn: &Connection,
+        data_hash: &str,
+        freed: &mut Vec<String>,
+    ) -> Result<(), StorageError> {
+        let deleted: Option<Option<String>> = conn
+            .prepare_cached("UPDATE blobs SET refcount = refcount - 1 WHERE hash = ?1")
+            .and_then(|mut stmt| stmt.execute(rusqlite::params![data_hash]))
+            .and_then(|_| {
+                conn.prepare_cached(
+                    "DELETE FROM blobs WHERE hash = ?1 AND refcount <= 0 RETURNING key_id",
+                )?
+                .query_row(rusqlite::params![data_hash], |row| row.get(0))
+                .optional()
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if let Some(key_id) = deleted {
+            freed.push(blob_file_name(data_hash, key_id.as_deref()));
+        }
+
+        Ok(())
+    }
+
+    /// Remove the files of blobs freed by a committed transaction. A file
+    /// that cannot be removed is only an orphan: nothing references it.
+    ///
+    /// A blob freed and then acquired again by the same transaction has an
+    /// index row naming the same file once more, so that file is kept. When
+    /// the index cannot be checked the file is kept too: an orphan is
+    /// harmless, a missing blob is not.
+    fn remove_blob_files(&self, freed: &[String]) {
+        if let Some(dir) = &self.blob_dir {
+            for name in freed {
+                let data_hash = name.split('.').next().unwrap_or(name);
+                let current: Result<Option<Option<String>>, _> = self
+                    .conn
+                    .prepare_cached("SELECT key_id FROM blobs WHERE hash = ?1")
+                    .and_then(|mut stmt| {
+                        stmt.query_row(rusqlite::params![data_hash], |row| row.get(0))
+                            .optional()
+                    });
+                match current {
+                    Ok(Some(key_id)) if blob_file_name(data_hash, key_id.as_deref()) == *name => {}
+                    Ok(_) => {
+                        let _ = fs::remove_file(blob_path(dir, name));
+                    }
+                    Err(_) => {}
+                }
+            }
+        }
+    }
+
+    /// Point a fragment at `data_hash`, whose reference the caller has
+    /// already acquired, and release the blob it referenced before (if any).
+    #[allow(clippy::too_many_arguments)]
+    fn bind_fragment(
+        &self,
+        conn: &Connection,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        data_hash: &str,
+        stored_at: i64,
+        freed: &mut Vec<String>,
+    ) -> Result<(), StorageError> {
+        let previous = fragment_blob(conn, repo_hash, fragment_id, share_id)?;
+        conn.prepare_cached(
+            "INSERT OR REPLACE INTO fragments
+             (repo_hash, fragment_id, share_id, data_hash, stored_at)
+             VALUES (?1, ?2, ?3, ?4, ?5)",
+        )
+        .and_then(|mut stmt| {
+            stmt.execute(rusqlite::params![repo_hash, fragment_id, share_id, data_hash, stored_at])
+        })
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        if let Some(previous) = previous {
+            self.release_blob(conn, &previous, freed)?;
+        }
+
+        Ok(())
+    }
+
+    /// Seal blob data with the store's active key, if one is set. Returns
+    /// the bytes to store and the key id to record.
+    fn seal_blob<'a>(&'a self, data_hash: &str, data: &[u8]) -> (Vec<u8>, Option<&'a str>) {
+        match self.active_key.as_deref().and_then(|id| self.keyring.get(id)) {
+            Some(key) => (key.seal(data_hash, data), Some(key.id())),
+            None => (data.to_vec(), None),
+        }
+    }
+
+    /// Keyring key a blob is sealed with.
+    fn blob_key(&self, data_hash: &str, key_id: &str) -> Result<&EncryptionKey, StorageError> {
+        self.keyring.get(key_id).ok_or_else(|| {
+            StorageError::Encryption(format!(
+                "blob {data_hash} is sealed with key {key_id}, which is not in the keyring"
+            ))
+        })
+    }
+
+    /// Plaintext bytes of a blob: inline data or the blob file, decrypted
+    /// if it was sealed under `key_id`.
+    fn load_blob(
+        &self,
+        inline: Option<Vec<u8>>,
+        key_id: Option<&str>,
+        data_hash: &str,
+    ) -> Result<Vec<u8>, StorageError> {
+        let stored = match (inline, &self.blob_dir) {
+            (Some(data), _) => data,
+            (None, Some(dir)) => fs::read(blob_path(dir, &blob_file_name(data_hash, key_id)))
+                .map_err(|e| StorageError::Io(e.to_string()))?,
+            (None, None) => return Err(StorageError::BlobNotFound(data_hash.to_string())),
+        };
+        let Some(key_id) = key_id else {
+            return Ok(stored);
+        };
+        self.blob_key(data_hash, key_id)?.open(data_hash, &stored)
+    }
+
+    /// Read `len` stored bytes of a blob starting at `offset`: through
+    /// SQLite incremental BLOB I/O for inline blobs, which reads only the
+    /// pages holding them, or a seek into the blob file.
+    fn read_blob_bytes(
+        &self,
+        rowid: i64,
+        inline: bool,
+        data_hash: &str,
+        key_id: Option<&str>,
+        offset: u64,
+        len: usize,
+    ) -> Result<Vec<u8>, StorageError> {
+        let mut buf = vec![0; len];
+        if inline {
+            let blob = self
+                .conn
+                .blob_open(DatabaseName::Main, "blobs", "data", rowid, true)
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            blob.read_at_exact(&mut buf, offset as usize)
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+        } else if let Some(dir) = &self.blob_dir {
+            let io = |e: std::io::Error| StorageError::Io(e.to_string());
+            let mut file = File::open(blob_path(dir, &blob_file_name(data_hash, key_id))).map_err(io)?;
+            file.seek(SeekFrom::Start(offset)).map_err(io)?;
+            file.read_exact(&mut buf).map_err(io)?;
+        } else {
+            return Err(StorageError::BlobNotFound(data_hash.to_string()));
+        }
+
+        Ok(buf)
+    }
+}
+
+/// An open write to one repository of a `FragmentStore`, for ingesting a
+/// push atomically. Created by `FragmentStore::begin_repo_write`.
+///
+/// Blob files of an `open_fs` store are written as fragments are stored; if
+/// the transaction rolls back they are left behind unreferenced.
+pub struct RepoWriteTransaction<'a> {
+    store: &'a FragmentStore,
+    tx: Transaction<'a>,
+    repo_hash: String,
+    stored_at: i64,
+    freed: Vec<String>,
+    written: usize,
+}
+
+impl RepoWriteTransaction<'_> {
+    /// The repository being written.
+    pub fn repo_hash(&self) -> &str {
+        &self.repo_hash
+    }
+
+    /// Store a fragment, replacing any existing fragment with the same key.
     pub fn store_fragment(
+        &mut self,
+        fragment_id: u32,
+        share_id: u32,
+        data: &[u8],
+    ) -> Result<(), StorageError> {
+        let data_hash = self.store.acquire_blob(&self.tx, data)?;
+        self.store.bind_fragment(
+            &self.tx,
+            &self.repo_hash,
+            fragment_id,
+            share_id,
+            &data_hash,
+            self.stored_at,
+            &mut self.freed,
+        )?;
+        self.written += 1;
+        Ok(())
+    }
+
+    /// Whether the repository holds a fragment, counting ones stored earlier
+    /// in this transaction.
+    pub fn has_fragment(&self, fragment_id: u32, share_id: u32) -> Result<bool, StorageError> {
+        Ok(fragment_blob(&self.tx, &self.repo_hash, fragment_id, share_id)?.is_some())
+    }
+
+    /// Make every write durable. Returns the number of fragments stored.
+    pub fn commit(self) -> Result<usize, StorageError> {
+        self.tx
+            .commit()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        self.store.remove_blob_files(&self.freed);
+        Ok(self.written)
+    }
+
+    /// Discard every write. Equivalent to dropping the transaction.
+    pub fn rollback(self) -> Result<(), StorageError> {
+        self.tx
+            .rollback()
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+}
+
+/// Columns selected for a `FragmentRecord`, with the inline blob data
+/// (NULL for file-backed blobs) and the blob's key id.
+const FRAGMENT_COLUMNS: &str = "f.repo_hash, f.fragment_id, f.share_id, b.data, f.data_hash,
+                                f.stored_at, f.last_challenged, f.status, b.key_id";
+
+/// A fragment row whose blob data may still have to be read from disk or
+/// decrypted: (record, inline data, key id).
+type FragmentRow = (FragmentRecord, Option<Vec<u8>>, Option<String>);
+
+fn fragment_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<FragmentRow> {
+    Ok((
+        FragmentRecord {
+            repo_hash: row.get(0)?,
+            fragment_id: row.get::<_, u32>(1)?,
+            share_id: row.get::<_, u32>(2)?,
+            data: Vec::new(),
+            data_hash: row.get(4)?,
+            stored_at: row.get(5)?,
+            last_challenged: row.get(6)?,
+            status: FragmentStatus::parse(&row.get::<_, String>(7)?).unwrap_or_default(),
+        },
+        row.get(3)?,
+        row.get(8)?,
+    ))
+}
+
+impl FragmentBackend for FragmentStore {
+    fn store_fragment(
         &self,
         repo_hash: &str,
         fragment_id: u32,
         share_id: u32,
         data: &[u8],
     ) -> Result<(), StorageError> {
-        let data_hash = gitgold_crypto::hash::sha256_hex(data);
+        let mut freed = Vec::new();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let data_hash = self.acquire_blob(&tx, data)?;
+        self.bind_fragment(&tx, repo_hash, fragment_id, share_id, &data_hash, unix_now(), &mut freed)?;
+        tx.commit()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        self.remove_blob_files(&freed);
+
+        Ok(())
+    }
+
+    fn link_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        data_hash: &str,
+    ) -> Result<(), StorageError> {
+        let mut freed = Vec::new();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let rows = tx
+            .execute(
+                "UPDATE blobs SET refcount = refcount + 1 WHERE hash = ?1",
+                rusqlite::params![data_hash],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if rows == 0 {
+            return Err(StorageError::BlobNotFound(data_hash.to_string()));
+        }
+        self.bind_fragment(&tx, repo_hash, fragment_id, share_id, data_hash, unix_now(), &mut freed)?;
+        tx.commit()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        self.remove_blob_files(&freed);
+
+        Ok(())
+    }
+
+    fn store_fragments_batch(
+        &self,
+        repo_hash: &str,
+        fragments: &[(u32, u32, Vec<u8>)],
+    ) -> Result<(), StorageError> {
+        let mut tx = self.begin_repo_write(repo_hash)?;
+        for (fragment_id, share_id, data) in fragments {
+            tx.store_fragment(*fragment_id, *share_id, data)?;
+        }
+        tx.commit()?;
+
+        Ok(())
+    }
+
+    fn has_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<bool, StorageError> {
+        Ok(fragment_blob(&self.conn, repo_hash, fragment_id, share_id)?.is_some())
+    }
+
+    fn get_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<FragmentRecord, StorageError> {
+        let (mut record, inline, key_id) = self
+            .conn
+            .query_row(
+                &format!(
+                    "SELECT {FRAGMENT_COLUMNS}
+                     FROM fragments f JOIN blobs b ON b.hash = f.data_hash
+                     WHERE f.repo_hash = ?1 AND f.fragment_id = ?2 AND f.share_id = ?3"
+                ),
+                rusqlite::params![repo_hash, fragment_id, share_id],
+                fragment_row,
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => StorageError::FragmentNotFound {
+                    repo_hash: repo_hash.to_string(),
+                    fragment_id,
+                },
+                other => StorageError::Database(other.to_string()),
+            })?;
+
+        record.data = self.load_blob(inline, key_id.as_deref(), &record.data_hash)?;
+        Ok(record)
+    }
+
+    fn list_fragments(&self, repo_hash: &str) -> Result<Vec<FragmentRecord>, StorageError> {
+        let mut stmt = self
+            .conn
+            .prepare(&format!(
+                "SELECT {FRAGMENT_COLUMNS}
+                 FROM fragments f JOIN blobs b ON b.hash = f.data_hash
+                 WHERE f.repo_hash = ?1
+                 ORDER BY f.fragment_id, f.share_id"
+            ))
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let rows = stmt
+            .query_map(rusqlite::params![repo_hash], fragment_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        rows.into_iter()
+            .map(|(mut record, inline, key_id)| {
+                record.data = self.load_blob(inline, key_id.as_deref(), &record.data_hash)?;
+                Ok(record)
+            })
+            .collect()
+    }
+
+    fn list_fragment_meta(
+        &self,
+        repo_hash: &str,
+        after: Option<(u32, u32)>,
+        limit: usize,
+    ) -> Result<Vec<FragmentMeta>, StorageError> {
+        // (-1, -1) sorts before every stored key
+        let (fragment_id, share_id) = after.map_or((-1, -1), |(f, s)| (f as i64, s as i64));
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT f.repo_hash, f.fragment_id, f.share_id, f.data_hash, b.size,
+                        f.stored_at, f.last_challenged, f.status
+                 FROM fragments f JOIN blobs b ON b.hash = f.data_hash
+                 WHERE f.repo_hash = ?1 AND (f.fragment_id, f.share_id) > (?2, ?3)
+                 ORDER BY f.fragment_id, f.share_id
+                 LIMIT ?4",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let metas = stmt
+            .query_map(
+                rusqlite::params![repo_hash, fragment_id, share_id, limit as i64],
+                |row| {
+                    Ok(FragmentMeta {
+                        repo_hash: row.get(0)?,
+                        fragment_id: row.get(1)?,
+                        share_id: row.get(2)?,
+                        data_hash: row.get(3)?,
+                        size: row.get(4)?,
+                        stored_at: row.get(5)?,
+                        last_challenged: row.get(6)?,
+                        status: FragmentStatus::parse(&row.get::<_, String>(7)?)
+                            .unwrap_or_default(),
+                    })
+                },
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(metas)
+    }
+
+    fn read_fragment_range(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        range: Range<usize>,
+    ) -> Result<Vec<u8>, StorageError> {
+        let (rowid, size, inline, data_hash, key_id): (i64, u64, bool, String, Option<String>) = self
+            .conn
+            .query_row(
+                "SELECT b.rowid, b.size, b.data IS NOT NULL, b.hash, b.key_id
+                 FROM fragments f JOIN blobs b ON b.hash = f.data_hash
+                 WHERE f.repo_hash = ?1 AND f.fragment_id = ?2 AND f.share_id = ?3",
+                rusqlite::params![repo_hash, fragment_id, share_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?, row.get(4)?)),
+            )
+            .map_err(|e| match e {
+                rusqlite::Error::QueryReturnedNoRows => StorageError::FragmentNotFound {
+                    repo_hash: repo_hash.to_string(),
+                    fragment_id,
+                },
+                other => StorageError::Database(other.to_string()),
+            })?;
+        check_range(&range, size)?;
+
+        let Some(key_id) = key_id else {
+            let offset = range.start as u64;
+            return self.read_blob_bytes(rowid, inline, &data_hash, None, offset, range.len());
+        };
+        if range.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        // Only the sealed segments covering the range are read and opened
+        let key = self.blob_key(&data_hash, &key_id)?;
+        let (sealed, first_segment) = encryption::sealed_range(size, &range);
+        let stored = self.read_blob_bytes(
+            rowid,
+            inline,
+            &data_hash,
+            Some(&key_id),
+            sealed.start,
+            (sealed.end - sealed.start) as usize,
+        )?;
+        let plaintext = key.open_segments(&data_hash, size, first_segment, &stored)?;
+        let offset = range.start - first_segment as usize * SEGMENT_LEN;
+        plaintext
+            .get(offset..offset + range.len())
+            .map(<[u8]>::to_vec)
+            .ok_or(StorageError::Decryption(data_hash))
+    }
+
+    fn delete_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<bool, StorageError> {
+        let mut freed = Vec::new();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let Some(data_hash) = fragment_blob(&tx, repo_hash, fragment_id, share_id)? else {
+            return Ok(false);
+        };
+        tx.execute(
+            "DELETE FROM fragments WHERE repo_hash = ?1 AND fragment_id = ?2 AND share_id = ?3",
+            rusqlite::params![repo_hash, fragment_id, share_id],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        self.release_blob(&tx, &data_hash, &mut freed)?;
+        tx.commit()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        self.remove_blob_files(&freed);
+
+        Ok(true)
+    }
+
+    fn replace_repo_fragments(
+        &self,
+        repo_hash: &str,
+        fragments: &[(u32, u32, Vec<u8>)],
+    ) -> Result<(), StorageError> {
+        let mut freed = Vec::new();
         let now = unix_now();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let old_blobs: Vec<String> = {
+            let mut stmt = tx
+                .prepare("SELECT data_hash FROM fragments WHERE repo_hash = ?1")
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            let hashes = stmt
+                .query_map(rusqlite::params![repo_hash], |row| row.get(0))
+                .map_err(|e| StorageError::Database(e.to_string()))?
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|e| StorageError::Database(e.to_string()))?;
+            hashes
+        };
+        tx.execute(
+            "DELETE FROM fragments WHERE repo_hash = ?1",
+            rusqlite::params![repo_hash],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        for data_hash in &old_blobs {
+            self.release_blob(&tx, data_hash, &mut freed)?;
+        }
+        for (fragment_id, share_id, data) in fragments {
+            let data_hash = self.acquire_blob(&tx, data)?;
+            self.bind_fragment(&tx, repo_hash, *fragment_id, *share_id, &data_hash, now, &mut freed)?;
+        }
+
+        tx.commit()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        self.remove_blob_files(&freed);
+
+        Ok(())
+    }
+
+    fn scan_fragment_hashes(
+        &self,
+        after: Option<&FragmentKey>,
+        limit: usize,
+    ) -> Result<Vec<(FragmentKey, String)>, StorageError> {
+        let (repo_hash, fragment_id, share_id) = match after {
+            Some(key) => (key.repo_hash.as_str(), key.fragment_id as i64, key.share_id as i64),
+            // Sorts before every stored key
+            None => ("", -1, -1),
+        };
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT repo_hash, fragment_id, share_id, data_hash FROM fragments
+                 WHERE (repo_hash, fragment_id, share_id) > (?1, ?2, ?3)
+                 ORDER BY repo_hash, fragment_id, share_id
+                 LIMIT ?4",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let keys = stmt
+            .query_map(
+                rusqlite::params![repo_hash, fragment_id, share_id, limit as i64],
+                |row| {
+                    let key = FragmentKey {
+                        repo_hash: row.get(0)?,
+                        fragment_id: row.get(1)?,
+                        share_id: row.get(2)?,
+                    };
+                    Ok((key, row.get(3)?))
+                },
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(keys)
+    }
+
+    fn set_fragment_status(
+        &self,
+        key: &FragmentKey,
+        status: FragmentStatus,
+    ) -> Result<bool, StorageError> {
+        let rows = self
+            .conn
+            .execute(
+                "UPDATE fragments SET status = ?1
+                 WHERE repo_hash = ?2 AND fragment_id = ?3 AND share_id = ?4",
+                rusqlite::params![status.as_str(), key.repo_hash, key.fragment_id, key.share_id],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(rows > 0)
+    }
+
+    fn rewrite_blob(&self, data: &[u8]) -> Result<(), StorageError> {
+        let data_hash = sha256_hex(data);
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let old_key_id: Option<String> = tx
+            .query_row(
+                "SELECT key_id FROM blobs WHERE hash = ?1",
+                rusqlite::params![data_hash],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .ok_or_else(|| StorageError::BlobNotFound(data_hash.clone()))?;
+
+        // Rewritten under the current key, like any newly stored blob
+        let (stored, key_id) = self.seal_blob(&data_hash, data);
+        let inline = match &self.blob_dir {
+            Some(_) => None,
+            None => Some(&stored),
+        };
+        tx.execute(
+            "UPDATE blobs SET data = ?1, key_id = ?2 WHERE hash = ?3",
+            rusqlite::params![inline, key_id, data_hash],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+        let mut stale = None;
+        if let Some(dir) = &self.blob_dir {
+            write_blob_file(&blob_path(dir, &blob_file_name(&data_hash, key_id)), &stored)?;
+            if old_key_id.as_deref() != key_id {
+                stale = Some(blob_path(dir, &blob_file_name(&data_hash, old_key_id.as_deref())));
+            }
+        }
+        tx.execute(
+            "UPDATE fragments SET status = ?1 WHERE data_hash = ?2",
+            rusqlite::params![FragmentStatus::Ok.as_str(), data_hash],
+        )
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        tx.commit()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        if let Some(path) = stale {
+            let _ = fs::remove_file(path);
+        }
+        Ok(())
+    }
+
+    fn blob_refcount(&self, data_hash: &str) -> Result<u64, StorageError> {
+        let refcount: Option<u64> = self
+            .conn
+            .query_row(
+                "SELECT refcount FROM blobs WHERE hash = ?1",
+                rusqlite::params![data_hash],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(refcount.unwrap_or(0))
+    }
+
+    fn blob_size(&self, data_hash: &str) -> Result<Option<u64>, StorageError> {
+        self.conn
+            .query_row(
+                "SELECT size FROM blobs WHERE hash = ?1",
+                rusqlite::params![data_hash],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn physical_size(&self, size: u64) -> u64 {
+        match self.active_key {
+            Some(_) => encryption::sealed_len(size),
+            None => size,
+        }
+    }
+
+    fn stats(&self) -> Result<StorageStats, StorageError> {
+        let (fragments, logical_bytes) = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*), COALESCE(SUM(b.size), 0)
+                 FROM fragments f JOIN blobs b ON b.hash = f.data_hash",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        let (blobs, physical_bytes) = self
+            .conn
+            .query_row(
+                // Sealed blobs carry a nonce and tag per segment
+                "SELECT COUNT(*), COALESCE(SUM(CASE WHEN key_id IS NULL THEN size
+                     ELSE size + ?2 * MAX(1, (size + ?1 - 1) / ?1) END), 0)
+                 FROM blobs",
+                rusqlite::params![SEGMENT_LEN as i64, SEGMENT_OVERHEAD as i64],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(StorageStats {
+            fragments,
+            blobs,
+            logical_bytes,
+            physical_bytes,
+        })
+    }
+
+    fn repo_usage(&self, repo_hash: &str) -> Result<RepoUsage, StorageError> {
+        let (fragments, bytes) = self
+            .conn
+            .query_row(
+                "SELECT COUNT(*), COALESCE(SUM(b.size), 0)
+                 FROM fragments f JOIN blobs b ON b.hash = f.data_hash
+                 WHERE f.repo_hash = ?1",
+                rusqlite::params![repo_hash],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(RepoUsage {
+            repo_hash: repo_hash.to_string(),
+            fragments,
+            bytes,
+        })
+    }
+
+    fn usage(&self) -> Result<Vec<RepoUsage>, StorageError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT f.repo_hash, COUNT(*), SUM(b.size)
+                 FROM fragments f JOIN blobs b ON b.hash = f.data_hash
+                 GROUP BY f.repo_hash
+                 ORDER BY f.repo_hash",
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        let usage = stmt
+            .query_map([], |row| {
+                Ok(RepoUsage {
+                    repo_hash: row.get(0)?,
+                    fragments: row.get(1)?,
+                    bytes: row.get(2)?,
+                })
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(usage)
+    }
+
+    fn store_commitments(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        commitments: &ShareCommitments,
+    ) -> Result<(), StorageError> {
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO share_commitments (repo_hash, fragment_id, commitments)
+                 VALUES (?1, ?2, ?3)",
+                rusqlite::params![repo_hash, fragment_id, commitments.to_bytes()],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn get_commitments(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+    ) -> Result<Option<ShareCommitments>, StorageError> {
+        let bytes: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT commitments FROM share_commitments
+                 WHERE repo_hash = ?1 AND fragment_id = ?2",
+                rusqlite::params![repo_hash, fragment_id],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        bytes
+            .map(|b| {
+                ShareCommitments::from_bytes(&b).map_err(|e| StorageError::Database(e.to_string()))
+            })
+            .transpose()
+    }
+
+    fn store_manifest(&self, repo_hash: &str, manifest: &RepoManifest) -> Result<(), StorageError> {
+        manifest.verify_signature()?;
+        self.conn
+            .execute(
+                "INSERT OR REPLACE INTO repo_manifests (repo_hash, manifest) VALUES (?1, ?2)",
+                rusqlite::params![repo_hash, manifest.to_bytes()],
+            )
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(())
+    }
+
+    fn get_manifest(&self, repo_hash: &str) -> Result<Option<RepoManifest>, StorageError> {
+        let bytes: Option<Vec<u8>> = self
+            .conn
+            .query_row(
+                "SELECT manifest FROM repo_manifests WHERE repo_hash = ?1",
+                rusqlite::params![repo_hash],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        bytes.map(|b| RepoManifest::from_bytes(&b)).transpose()
+    }
 
+    fn set_lease(&self, repo_hash: &str, paid_until: i64) -> Result<(), StorageError> {
         self.conn
             .execute(
-                "INSERT OR REPLACE INTO fragments
-                 (repo_hash, fragment_id, share_id, data, data_hash, stored_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                rusqlite::params![repo_hash, fragment_id, share_id, data, data_hash, now],
+                "INSERT OR REPLACE INTO leases (repo_hash, paid_until, renewed_at)
+                 VALUES (?1, ?2, ?3)",
+                rusqlite::params![repo_hash, paid_until, unix_now()],
             )
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
         Ok(())
     }
 
-    /// Retrieve a specific fragment by (repo_hash, fragment_id, share_id).
-    pub fn get_fragment(
-        &self,
-        repo_hash: &str,
-        fragment_id: u32,
-        share_id: u32,
-    ) -> Result<FragmentRecord, StorageError> {
+    fn get_lease(&self, repo_hash: &str) -> Result<Option<Lease>, StorageError> {
         self.conn
             .query_row(
-                "SELECT repo_hash, fragment_id, share_id, data, data_hash, stored_at, last_challenged
-                 FROM fragments
-                 WHERE repo_hash = ?1 AND fragment_id = ?2 AND share_id = ?3",
-                rusqlite::params![repo_hash, fragment_id, share_id],
+                "SELECT paid_until, renewed_at FROM leases WHERE repo_hash = ?1",
+                rusqlite::params![repo_hash],
                 |row| {
-                    Ok(FragmentRecord {
-                        repo_hash: row.get(0)?,
-                        fragment_id: row.get::<_, u32>(1)?,
-                        share_id: row.get::<_, u32>(2)?,
-                        data: row.get(3)?,
-                        data_hash: row.get(4)?,
-                        stored_at: row.get(5)?,
-                        last_challenged: row.get(6)?,
+                    Ok(Lease {
+                        paid_until: row.get(0)?,
+                        renewed_at: row.get(1)?,
                     })
                 },
             )
-            .map_err(|e| match e {
-                rusqlite::Error::QueryReturnedNoRows => StorageError::FragmentNotFound {
-                    repo_hash: repo_hash.to_string(),
-                    fragment_id,
-                },
-                other => StorageError::Database(other.to_string()),
+            .optional()
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
+
+    fn purge_fragments(&self, entries: &[GcEntry]) -> Result<(), StorageError> {
+        let mut freed = Vec::new();
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        for entry in entries {
+            let key = &entry.key;
+            let Some(data_hash) = fragment_blob(&tx, &key.repo_hash, key.fragment_id, key.share_id)?
+            else {
+                continue;
+            };
+            tx.execute(
+                "DELETE FROM fragments WHERE repo_hash = ?1 AND fragment_id = ?2 AND share_id = ?3",
+                rusqlite::params![key.repo_hash, key.fragment_id, key.share_id],
+            )
+            .and_then(|_| {
+                tx.execute(
+                    "INSERT INTO gc_log
+                     (repo_hash, fragment_id, share_id, data_hash, reason, deleted_at)
+                     VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                    rusqlite::params![
+                        key.repo_hash,
+                        key.fragment_id,
+                        key.share_id,
+                        entry.data_hash,
+                        entry.reason.as_str(),
+                        entry.deleted_at
+                    ],
+                )
             })
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+            self.release_blob(&tx, &data_hash, &mut freed)?;
+        }
+
+        tx.commit()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+        self.remove_blob_files(&freed);
+
+        Ok(())
     }
 
-    /// List all fragments for a given repo_hash.
-    pub fn list_fragments(&self, repo_hash: &str) -> Result<Vec<FragmentRecord>, StorageError> {
+    fn gc_log(&self, since: i64) -> Result<Vec<GcEntry>, StorageError> {
         let mut stmt = self
             .conn
             .prepare(
-                "SELECT repo_hash, fragment_id, share_id, data, data_hash, stored_at, last_challenged
-                 FROM fragments
-                 WHERE repo_hash = ?1
-                 ORDER BY fragment_id, share_id",
+                "SELECT repo_hash, fragment_id, share_id, data_hash, reason, deleted_at
+                 FROM gc_log WHERE deleted_at >= ?1 ORDER BY id",
             )
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
-        let records = stmt
-            .query_map(rusqlite::params![repo_hash], |row| {
-                Ok(FragmentRecord {
-                    repo_hash: row.get(0)?,
-                    fragment_id: row.get::<_, u32>(1)?,
-                    share_id: row.get::<_, u32>(2)?,
-                    data: row.get(3)?,
-                    data_hash: row.get(4)?,
-                    stored_at: row.get(5)?,
-                    last_challenged: row.get(6)?,
+        let entries = stmt
+            .query_map(rusqlite::params![since], |row| {
+                let reason: String = row.get(4)?;
+                Ok(GcEntry {
+                    key: FragmentKey {
+                        repo_hash: row.get(0)?,
+                        fragment_id: row.get(1)?,
+                        share_id: row.get(2)?,
+                    },
+                    data_hash: row.get(3)?,
+                    reason: GcReason::parse(&reason).ok_or_else(|| {
+                        rusqlite::Error::FromSqlConversionFailure(
+                            4,
+                            rusqlite::types::Type::Text,
+                            format!("unknown gc reason {reason:?}").into(),
+                        )
+                    })?,
+                    deleted_at: row.get(5)?,
                 })
             })
             .map_err(|e| StorageError::Database(e.to_string()))?
             .collect::<Result<Vec<_>, _>>()
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
-        Ok(records)
+        Ok(entries)
+    }
+
+    fn record_challenge(&self, record: &ChallengeRecord) -> Result<(), StorageError> {
+        let tx = self
+            .conn
+            .unchecked_transaction()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        tx.execute(
+            "INSERT OR REPLACE INTO challenges
+             (challenge_id, repo_hash, fragment_id, share_id, challenger, node, nonce,
+              success, failure_reason, response_time, challenged_at)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)",
+            rusqlite::params![
+                record.challenge_id,
+                record.repo_hash,
+                record.fragment_id,
+                record.share_id,
+                record.challenger.0,
+                record.node.0,
+                &record.nonce[..],
+                record.success,
+                record.failure_reason,
+                record.response_time_ms,
+                record.challenged_at
+            ],
+        )
+        .and_then(|_| {
+            tx.execute(
+                "UPDATE fragments SET last_challenged = ?1
+                 WHERE repo_hash = ?2 AND fragment_id = ?3 AND share_id = ?4",
+                rusqlite::params![
+                    record.challenged_at,
+                    record.repo_hash,
+                    record.fragment_id,
+                    record.share_id
+                ],
+            )
+        })
+        .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        tx.commit()
+            .map_err(|e| StorageError::Database(e.to_string()))
     }
 
-    /// Delete a specific fragment.
-    pub fn delete_fragment(
+    fn challenge_history(
         &self,
         repo_hash: &str,
         fragment_id: u32,
-        share_id: u32,
-    ) -> Result<bool, StorageError> {
-        let rows = self
+    ) -> Result<Vec<ChallengeRecord>, StorageError> {
+        let mut stmt = self
             .conn
-            .execute(
-                "DELETE FROM fragments WHERE repo_hash = ?1 AND fragment_id = ?2 AND share_id = ?3",
-                rusqlite::params![repo_hash, fragment_id, share_id],
+            .prepare(
+                "SELECT challenge_id, repo_hash, fragment_id, share_id, challenger, node, nonce,
+                        success, failure_reason, response_time, challenged_at
+                 FROM challenges WHERE repo_hash = ?1 AND fragment_id = ?2
+                 ORDER BY challenged_at, challenge_id",
             )
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
-        Ok(rows > 0)
+        let records = stmt
+            .query_map(rusqlite::params![repo_hash, fragment_id], challenge_row)
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(records)
     }
 
-    /// Record a challenge result.
-    pub fn record_challenge(
+    fn node_challenge_stats(
         &self,
-        challenge_id: &str,
-        repo_hash: &str,
-        fragment_id: u32,
-        success: bool,
-        response_time_ms: u64,
-    ) -> Result<(), StorageError> {
-        let now = unix_now();
-
+        node: &Address,
+        since: i64,
+        until: i64,
+    ) -> Result<NodeChallengeStats, StorageError> {
         self.conn
-            .execute(
-                "INSERT OR REPLACE INTO challenges
-                 (challenge_id, repo_hash, fragment_id, success, response_time, challenged_at)
-                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
-                rusqlite::params![challenge_id, repo_hash, fragment_id, success, response_time_ms, now],
+            .query_row(
+                "SELECT COUNT(*), COALESCE(SUM(success), 0) FROM challenges
+                 WHERE node = ?1 AND challenged_at >= ?2 AND challenged_at < ?3",
+                rusqlite::params![node.0, since, until],
+                |row| {
+                    Ok(NodeChallengeStats {
+                        challenges: row.get(0)?,
+                        successes: row.get(1)?,
+                    })
+                },
             )
-            .map_err(|e| StorageError::Database(e.to_string()))?;
+            .map_err(|e| StorageError::Database(e.to_string()))
+    }
 
-        // Update last_challenged on the fragment
-        self.conn
-            .execute(
-                "UPDATE fragments SET last_challenged = ?1
-                 WHERE repo_hash = ?2 AND fragment_id = ?3",
-                rusqlite::params![now, repo_hash, fragment_id],
+    fn fragments_not_challenged_since(&self, since: i64) -> Result<Vec<FragmentKey>, StorageError> {
+        let mut stmt = self
+            .conn
+            .prepare(
+                "SELECT repo_hash, fragment_id, share_id FROM fragments
+                 WHERE last_challenged IS NULL OR last_challenged < ?1
+                 ORDER BY repo_hash, fragment_id, share_id",
             )
             .map_err(|e| StorageError::Database(e.to_string()))?;
 
-        Ok(())
+        let keys = stmt
+            .query_map(rusqlite::params![since], |row| {
+                Ok(FragmentKey {
+                    repo_hash: row.get(0)?,
+                    fragment_id: row.get(1)?,
+                    share_id: row.get(2)?,
+                })
+            })
+            .map_err(|e| StorageError::Database(e.to_string()))?
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|e| StorageError::Database(e.to_string()))?;
+
+        Ok(keys)
+    }
+}
+
+/// Map a `challenges` row selected in `challenge_history` column order.
+fn challenge_row(row: &rusqlite::Row<'_>) -> rusqlite::Result<ChallengeRecord> {
+    let nonce: Vec<u8> = row.get(6)?;
+    Ok(ChallengeRecord {
+        challenge_id: row.get(0)?,
+        repo_hash: row.get(1)?,
+        fragment_id: row.get(2)?,
+        share_id: row.get(3)?,
+        challenger: Address(row.get(4)?),
+        node: Address(row.get(5)?),
+        nonce: nonce.try_into().map_err(|n: Vec<u8>| {
+            rusqlite::Error::FromSqlConversionFailure(
+                6,
+                rusqlite::types::Type::Blob,
+                format!("nonce is {} bytes, expected 32", n.len()).into(),
+            )
+        })?,
+        success: row.get(7)?,
+        failure_reason: row.get(8)?,
+        response_time_ms: row.get(9)?,
+        challenged_at: row.get(10)?,
+    })
+}
+
+/// Blob referenced by a fragment, if the fragment exists.
+fn fragment_blob(
+    conn: &Connection,
+    repo_hash: &str,
+    fragment_id: u32,
+    share_id: u32,
+) -> Result<Option<String>, StorageError> {
+    conn.prepare_cached(
+        "SELECT data_hash FROM fragments
+         WHERE repo_hash = ?1 AND fragment_id = ?2 AND share_id = ?3",
+    )
+    .and_then(|mut stmt| {
+        stmt.query_row(rusqlite::params![repo_hash, fragment_id, share_id], |row| row.get(0))
+            .optional()
+    })
+    .map_err(|e| StorageError::Database(e.to_string()))
+}
+
+/// Directory of an `open_fs` store holding the blob files.
+const BLOB_DIR: &str = "blobs";
+
+/// Name of a blob file: the blob's hash, suffixed with the id of the key it
+/// is sealed with so files under different keys never collide mid-rotation.
+fn blob_file_name(data_hash: &str, key_id: Option<&str>) -> String {
+    match key_id {
+        Some(key_id) => format!("{data_hash}.{key_id}"),
+        None => data_hash.to_string(),
     }
 }
 
-fn unix_now() -> i64 {
-    SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap()
-        .as_secs() as i64
+/// Location of a blob file: `dir/ab/cd/abcd...`.
+fn blob_path(dir: &Path, file_name: &str) -> PathBuf {
+    dir.join(&file_name[..2]).join(&file_name[2..4]).join(file_name)
+}
+
+/// Location of a blob file in an `open_fs` store rooted at `root`.
+#[cfg(test)]
+pub(crate) fn blob_file_path(root: &Path, data_hash: &str, key_id: Option<&str>) -> PathBuf {
+    blob_path(&root.join(BLOB_DIR), &blob_file_name(data_hash, key_id))
+}
+
+/// Write a blob file via a uniquely named temporary file in the same
+/// directory, synced to disk before it is renamed into place, so a crash
+/// never leaves a partially written blob under its final name and
+/// concurrent writers never share a temporary file. On Unix the directory
+/// is synced too, making the rename itself durable.
+fn write_blob_file(path: &Path, data: &[u8]) -> Result<(), StorageError> {
+    let io = |e: std::io::Error| StorageError::Io(e.to_string());
+    let dir = path.parent().expect("blob path has a parent");
+    fs::create_dir_all(dir).map_err(io)?;
+    let mut tmp = tempfile::NamedTempFile::new_in(dir).map_err(io)?;
+    tmp.write_all(data).map_err(io)?;
+    tmp.as_file().sync_all().map_err(io)?;
+    tmp.persist(path).map_err(|e| io(e.error))?;
+    #[cfg(unix)]
+    File::open(dir).and_then(|d| d.sync_all()).map_err(io)?;
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use gitgold_crypto::shamir;
+    use gitgold_crypto::share::Share;
 
     fn test_store() -> FragmentStore {
         FragmentStore::in_memory().unwrap()
@@ -236,16 +1422,374 @@ mod tests {
         assert!(!store.delete_fragment("repo1", 0, 1).unwrap()); // already deleted
     }
 
+    fn challenge(id: &str, share_id: u32, node: &str, success: bool, at: i64) -> ChallengeRecord {
+        ChallengeRecord {
+            challenge_id: id.to_string(),
+            repo_hash: "repo1".to_string(),
+            fragment_id: 0,
+            share_id,
+            challenger: Address::new("challenger"),
+            node: Address::new(node),
+            nonce: [7; 32],
+            success,
+            failure_reason: (!success).then(|| "hash mismatch".to_string()),
+            response_time_ms: 150,
+            challenged_at: at,
+        }
+    }
+
+    #[test]
+    fn test_store_fragments_batch() {
+        let store = test_store();
+        store.store_fragment("repo1", 0, 1, b"old").unwrap();
+        store.store_fragment("repo1", 5, 1, b"kept").unwrap();
+        let batch: Vec<(u32, u32, Vec<u8>)> = (0..3)
+            .flat_map(|fid| (1..=2).map(move |sid| (fid, sid, format!("{fid}/{sid}").into_bytes())))
+            .collect();
+
+        store.store_fragments_batch("repo1", &batch).unwrap();
+        assert_eq!(store.list_fragments("repo1").unwrap().len(), 7);
+        assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().data, b"0/1");
+        assert!(store.has_fragment("repo1", 2, 2).unwrap());
+        assert!(store.has_fragment("repo1", 5, 1).unwrap());
+        assert!(!store.has_fragment("repo1", 3, 1).unwrap());
+        assert_eq!(store.stats().unwrap().blobs, 7);
+    }
+
+    #[test]
+    fn test_repo_write_transaction() {
+        let store = test_store();
+        store.store_fragment("repo1", 0, 1, b"before").unwrap();
+
+        // Dropped without commit: nothing is kept
+        {
+            let mut tx = store.begin_repo_write("repo1").unwrap();
+            tx.store_fragment(0, 1, b"replaced").unwrap();
+            tx.store_fragment(1, 1, b"new").unwrap();
+            assert!(tx.has_fragment(1, 1).unwrap());
+        }
+        assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().data, b"before");
+        assert!(!store.has_fragment("repo1", 1, 1).unwrap());
+        assert_eq!(store.stats().unwrap().blobs, 1);
+
+        // A resumed push skips what is already stored
+        let mut tx = store.begin_repo_write("repo1").unwrap();
+        for fid in 0..3 {
+            if !tx.has_fragment(fid, 1).unwrap() {
+                tx.store_fragment(fid, 1, format!("chunk {fid}").as_bytes())
+                    .unwrap();
+            }
+        }
+        assert_eq!(tx.commit().unwrap(), 2);
+        assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().data, b"before");
+        assert_eq!(store.get_fragment("repo1", 2, 1).unwrap().data, b"chunk 2");
+    }
+
+    #[test]
+    fn test_repo_write_swaps_data_between_keys() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FragmentStore::open_fs(dir.path()).unwrap();
+        store.store_fragment("r", 0, 1, b"YYYY").unwrap();
+
+        // Overwriting (0, 1) frees the YYYY blob before (0, 2) takes it again
+        store
+            .store_fragments_batch("r", &[(0, 1, b"XXXX".to_vec()), (0, 2, b"YYYY".to_vec())])
+            .unwrap();
+        assert_eq!(store.get_fragment("r", 0, 1).unwrap().data, b"XXXX");
+        assert_eq!(store.get_fragment("r", 0, 2).unwrap().data, b"YYYY");
+
+        // And back again through an explicit transaction
+        let mut tx = store.begin_repo_write("r").unwrap();
+        tx.store_fragment(0, 2, b"XXXX").unwrap();
+        tx.store_fragment(0, 1, b"YYYY").unwrap();
+        tx.commit().unwrap();
+        assert_eq!(store.get_fragment("r", 0, 1).unwrap().data, b"YYYY");
+        assert_eq!(store.get_fragment("r", 0, 2).unwrap().data, b"XXXX");
+        assert_eq!(store.stats().unwrap().physical_bytes, 8);
+    }
+
+    #[test]
+    fn test_list_fragment_meta_paginated() {
+        let store = test_store();
+        for fid in 0..3 {
+            for sid in 1..=2 {
+                store
+                    .store_fragment("repo1", fid, sid, &vec![fid as u8; 100 + sid as usize])
+                    .unwrap();
+            }
+        }
+        store.store_fragment("repo2", 0, 1, b"other repo").unwrap();
+
+        let mut pages = Vec::new();
+        let mut after = None;
+        loop {
+            let page = store.list_fragment_meta("repo1", after, 4).unwrap();
+            let Some(last) = page.last() else { break };
+            after = Some((last.fragment_id, last.share_id));
+            pages.push(page);
+        }
+        assert_eq!(pages.iter().map(Vec::len).collect::<Vec<_>>(), [4, 2]);
+        let metas: Vec<_> = pages.concat();
+        assert_eq!((metas[1].fragment_id, metas[1].share_id, metas[1].size), (0, 2, 102));
+        assert_eq!(metas[5].key(), store.get_fragment("repo1", 2, 2).unwrap().key());
+        assert_eq!(metas[5].data_hash, store.get_fragment("repo1", 2, 2).unwrap().data_hash);
+    }
+
+    #[test]
+    fn test_read_fragment_range() {
+        let dir = tempfile::tempdir().unwrap();
+        let data: Vec<u8> = (0..5000u32).map(|i| (i % 251) as u8).collect();
+        let inline = test_store();
+        let fs_store = FragmentStore::open_fs(dir.path()).unwrap();
+
+        for store in [&inline, &fs_store] {
+            store.store_fragment("repo1", 0, 1, &data).unwrap();
+            assert_eq!(
+                store.read_fragment_range("repo1", 0, 1, 1000..3048).unwrap(),
+                &data[1000..3048]
+            );
+            assert!(store.read_fragment_range("repo1", 0, 1, 5000..5000).unwrap().is_empty());
+            assert!(matches!(
+                store.read_fragment_range("repo1", 0, 1, 4000..5001),
+                Err(StorageError::InvalidByteRange { fragment_size: 5000, .. })
+            ));
+            assert!(matches!(
+                store.read_fragment_range("repo1", 1, 1, 0..1),
+                Err(StorageError::FragmentNotFound { .. })
+            ));
+        }
+    }
+
     #[test]
     fn test_record_challenge() {
         let store = test_store();
         store.store_fragment("repo1", 0, 1, b"data").unwrap();
+        store.store_fragment("repo1", 0, 2, b"other").unwrap();
         store
-            .record_challenge("chal-1", "repo1", 0, true, 150)
+            .record_challenge(&challenge("chal-1", 1, "node-a", true, 1000))
             .unwrap();
 
+        // Only the challenged share is marked
         let record = store.get_fragment("repo1", 0, 1).unwrap();
-        assert!(record.last_challenged.is_some());
+        assert_eq!(record.last_challenged, Some(1000));
+        assert!(store.get_fragment("repo1", 0, 2).unwrap().last_challenged.is_none());
+    }
+
+    #[test]
+    fn test_challenge_queries() {
+        let store = test_store();
+        store.store_fragment("repo1", 0, 1, b"data").unwrap();
+        store.store_fragment("repo1", 0, 2, b"other").unwrap();
+        store.store_fragment("repo1", 1, 1, b"never").unwrap();
+        let log = [
+            challenge("chal-2", 2, "node-b", false, 2000),
+            challenge("chal-1", 1, "node-a", true, 1000),
+            challenge("chal-3", 1, "node-a", false, 3000),
+            challenge("chal-4", 1, "node-a", true, 4000),
+        ];
+        for record in &log {
+            store.record_challenge(record).unwrap();
+        }
+
+        let history = store.challenge_history("repo1", 0).unwrap();
+        let ids: Vec<_> = history.iter().map(|r| r.challenge_id.as_str()).collect();
+        assert_eq!(ids, ["chal-1", "chal-2", "chal-3", "chal-4"]);
+        assert_eq!(history[1], log[0]);
+        assert!(store.challenge_history("repo1", 1).unwrap().is_empty());
+
+        let node_a = Address::new("node-a");
+        let stats = store.node_challenge_stats(&node_a, 1000, 4000).unwrap();
+        assert_eq!((stats.challenges, stats.successes), (2, 1));
+        assert_eq!(stats.success_rate(), Some(0.5));
+        let stats = store.node_challenge_stats(&node_a, 5000, 6000).unwrap();
+        assert_eq!(stats.success_rate(), None);
+
+        let stale: Vec<_> = store
+            .fragments_not_challenged_since(3000)
+            .unwrap()
+            .into_iter()
+            .map(|k| (k.fragment_id, k.share_id))
+            .collect();
+        assert_eq!(stale, [(0, 2), (1, 1)]);
+    }
+
+    #[test]
+    fn test_store_verified_fragment() {
+        let store = test_store();
+        let (shares, commitments) = gitgold_crypto::vss::split(b"verified", 2, 3).unwrap();
+
+        // Rejected until commitments are known
+        let bytes = shares[0].to_bytes();
+        assert!(matches!(
+            store.store_verified_fragment("repo1", 0, 1, &bytes),
+            Err(StorageError::InvalidShare { share_id: 1, .. })
+        ));
+
+        store.store_commitments("repo1", 0, &commitments).unwrap();
+        assert_eq!(store.get_commitments("repo1", 0).unwrap(), Some(commitments));
+        store.store_verified_fragment("repo1", 0, 1, &bytes).unwrap();
+
+        let mut bad = shares[1].clone();
+        bad.data[0] ^= 0x01;
+        assert!(matches!(
+            store.store_verified_fragment("repo1", 0, 2, &bad.to_bytes()),
+            Err(StorageError::InvalidShare { share_id: 2, .. })
+        ));
+        // Share stored under the wrong id
+        assert!(store
+            .store_verified_fragment("repo1", 0, 3, &shares[1].to_bytes())
+            .is_err());
+        assert_eq!(store.list_fragments("repo1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_store_manifest() {
+        let store = test_store();
+        let chunks = crate::chunk::chunk_data(&[0x5A; 3000], 1024);
+        let key = gitgold_crypto::keys::KeyPair::generate();
+        let manifest = RepoManifest::new(
+            &[1; 32],
+            &chunks,
+            &gitgold_core::config::GitGoldConfig::default(),
+            &key,
+        )
+        .unwrap();
+
+        assert!(store.get_manifest("repo1").unwrap().is_none());
+        store.store_manifest("repo1", &manifest).unwrap();
+        assert_eq!(store.get_manifest("repo1").unwrap(), Some(manifest.clone()));
+
+        let mut forged = manifest;
+        forged.n = 3;
+        assert!(matches!(
+            store.store_manifest("repo1", &forged),
+            Err(StorageError::InvalidManifest(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh_repo() {
+        let store = test_store();
+        let secret = b"long-lived repository".to_vec();
+        for chunk_index in 0..2 {
+            for share in shamir::split(&secret, 2, 3).unwrap() {
+                store
+                    .store_fragment("repo1", chunk_index, share.id, &share.to_bytes())
+                    .unwrap();
+            }
+        }
+        let old = store.get_fragment("repo1", 1, 1).unwrap();
+
+        assert_eq!(store.refresh_repo("repo1").unwrap(), 6);
+
+        let shares: Vec<Share> = (2..=3)
+            .map(|sid| Share::from_bytes(&store.get_fragment("repo1", 1, sid).unwrap().data).unwrap())
+            .collect();
+        assert_eq!(shamir::reconstruct(&shares).unwrap(), secret);
+
+        let new = store.get_fragment("repo1", 1, 1).unwrap();
+        assert_ne!(new.data_hash, old.data_hash);
+        let mixed = [Share::from_bytes(&old.data).unwrap(), shares[0].clone()];
+        if let Ok(recovered) = shamir::reconstruct(&mixed) {
+            assert_ne!(recovered, secret);
+        }
+    }
+
+    #[test]
+    fn test_refresh_repo_requires_full_share_set() {
+        let store = test_store();
+        let shares = shamir::split(b"partial", 2, 3).unwrap();
+        store.store_fragment("repo1", 0, 1, &shares[0].to_bytes()).unwrap();
+        store.store_fragment("repo1", 0, 2, &shares[1].to_bytes()).unwrap();
+        assert!(matches!(
+            store.refresh_repo("repo1"),
+            Err(StorageError::InvalidShare { .. })
+        ));
+        // Nothing was rewritten
+        assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().data, shares[0].to_bytes());
+    }
+
+    #[test]
+    fn test_repair_repo() {
+        let store = test_store();
+        let secret = vec![0x5A; 100];
+        let originals = shamir::split(&secret, 3, 5).unwrap();
+        for chunk_index in 0..3 {
+            for share in &originals {
+                store
+                    .store_fragment("repo1", chunk_index, share.id, &share.to_bytes())
+                    .unwrap();
+            }
+        }
+        // Chunk 0 loses two shares, chunk 1 is intact, chunk 2 drops below k
+        store.delete_fragment("repo1", 0, 2).unwrap();
+        store.delete_fragment("repo1", 0, 5).unwrap();
+        for sid in 1..=3 {
+            store.delete_fragment("repo1", 2, sid).unwrap();
+        }
+
+        let report = store.repair_repo("repo1").unwrap();
+        assert_eq!(report.repaired, vec![(0, 2), (0, 5)]);
+        assert_eq!(report.unrepairable, vec![2]);
+
+        let repaired = store.get_fragment("repo1", 0, 5).unwrap();
+        assert_eq!(Share::from_bytes(&repaired.data).unwrap(), originals[4]);
+        assert!(store.repair_repo("repo1").unwrap().repaired.is_empty());
+    }
+
+    #[test]
+    fn test_reshare_repo() {
+        let store = test_store();
+        let secret = b"hot repository".to_vec();
+        for chunk_index in 0..2 {
+            for share in shamir::split(&secret, 5, 9).unwrap() {
+                store
+                    .store_fragment("repo1", chunk_index, share.id, &share.to_bytes())
+                    .unwrap();
+            }
+        }
+        store.store_fragment("repo2", 0, 1, b"untouched").unwrap();
+
+        assert_eq!(store.reshare_repo("repo1", 3, 5).unwrap(), 10);
+
+        let frags = store.list_fragments("repo1").unwrap();
+        assert_eq!(frags.len(), 10);
+        let shares: Vec<Share> = frags
+            .iter()
+            .filter(|f| f.fragment_id == 1 && f.share_id >= 3)
+            .map(|f| Share::from_bytes(&f.data).unwrap())
+            .collect();
+        assert_eq!(shamir::reconstruct(&shares).unwrap(), secret);
+        assert_eq!(store.list_fragments("repo2").unwrap().len(), 1);
+        // Old share blobs are released
+        assert_eq!(store.stats().unwrap().blobs, 11);
+    }
+
+    #[test]
+    fn test_reshare_repo_is_atomic() {
+        let store = test_store();
+        for share in shamir::split(b"chunk zero", 3, 4).unwrap() {
+            store.store_fragment("repo1", 0, share.id, &share.to_bytes()).unwrap();
+        }
+        // Chunk 1 cannot be re-shared: only 2 of k=3 shares
+        for share in shamir::split(b"chunk one", 3, 4).unwrap().into_iter().take(2) {
+            store.store_fragment("repo1", 1, share.id, &share.to_bytes()).unwrap();
+        }
+        let before: Vec<Vec<u8>> = store
+            .list_fragments("repo1")
+            .unwrap()
+            .into_iter()
+            .map(|f| f.data)
+            .collect();
+
+        assert!(store.reshare_repo("repo1", 2, 3).is_err());
+        let after: Vec<Vec<u8>> = store
+            .list_fragments("repo1")
+            .unwrap()
+            .into_iter()
+            .map(|f| f.data)
+            .collect();
+        assert_eq!(before, after);
     }
 
     #[test]
@@ -256,5 +1800,261 @@ mod tests {
 
         let record = store.get_fragment("repo1", 0, 1).unwrap();
         assert_eq!(record.data, b"new");
+        // The replaced blob is released
+        assert_eq!(store.stats().unwrap().blobs, 1);
+    }
+
+    #[test]
+    fn test_identical_fragments_stored_once() {
+        let store = test_store();
+        let data = vec![0xAB; 1000];
+        store.store_fragment("upstream", 0, 1, &data).unwrap();
+        store.store_fragment("fork", 0, 1, &data).unwrap();
+        store.store_fragment("fork", 1, 1, b"fork only").unwrap();
+
+        let hash = store.get_fragment("fork", 0, 1).unwrap().data_hash;
+        assert_eq!(store.blob_refcount(&hash).unwrap(), 2);
+        let stats = store.stats().unwrap();
+        assert_eq!(stats.fragments, 3);
+        assert_eq!(stats.blobs, 2);
+        assert_eq!(stats.logical_bytes, 2009);
+        assert_eq!(stats.physical_bytes, 1009);
+        assert_eq!(stats.saved_bytes(), 1000);
+
+        // Deleting one reference leaves the other repo's data intact
+        assert!(store.delete_fragment("upstream", 0, 1).unwrap());
+        assert_eq!(store.get_fragment("fork", 0, 1).unwrap().data, data);
+        assert_eq!(store.blob_refcount(&hash).unwrap(), 1);
+
+        assert!(store.delete_fragment("fork", 0, 1).unwrap());
+        assert!(!store.delete_fragment("fork", 0, 1).unwrap());
+        assert_eq!(store.blob_refcount(&hash).unwrap(), 0);
+        assert_eq!(store.stats().unwrap().physical_bytes, 9);
+    }
+
+    #[test]
+    fn test_link_fragment() {
+        let store = test_store();
+        store.store_fragment("upstream", 4, 2, b"shared share").unwrap();
+        let hash = store.get_fragment("upstream", 4, 2).unwrap().data_hash;
+
+        store.link_fragment("mirror", 4, 2, &hash).unwrap();
+        assert_eq!(store.get_fragment("mirror", 4, 2).unwrap().data, b"shared share");
+        assert_eq!(store.blob_refcount(&hash).unwrap(), 2);
+
+        assert!(matches!(
+            store.link_fragment("mirror", 5, 2, "00"),
+            Err(StorageError::BlobNotFound(_))
+        ));
+        assert!(store.get_fragment("mirror", 5, 2).is_err());
+    }
+
+    #[test]
+    fn test_open_fs_stores_blobs_as_files() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FragmentStore::open_fs(dir.path()).unwrap();
+        let data = vec![0x5C; 300];
+        store.store_fragment("upstream", 0, 1, &data).unwrap();
+        store.store_fragment("fork", 0, 1, &data).unwrap();
+
+        let hash = store.get_fragment("fork", 0, 1).unwrap().data_hash;
+        let path = blob_file_path(dir.path(), &hash, None);
+        assert_eq!(fs::read(&path).unwrap(), data);
+        assert_eq!(store.stats().unwrap().physical_bytes, 300);
+        // Only the blob itself: no temporary file is left behind
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+
+        // The file outlives the first reference and goes with the last
+        store.delete_fragment("upstream", 0, 1).unwrap();
+        assert!(path.exists());
+        store.store_fragment("fork", 0, 1, b"replacement").unwrap();
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_open_fs_replace_with_same_data() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FragmentStore::open_fs(dir.path()).unwrap();
+        store.store_fragment("s", 0, 1, b"ZZZZ").unwrap();
+
+        // The old blob is freed and the identical one acquired again in the
+        // same transaction: its file must survive the commit
+        store
+            .replace_repo_fragments("s", &[(0, 1, b"ZZZZ".to_vec())])
+            .unwrap();
+        assert_eq!(store.get_fragment("s", 0, 1).unwrap().data, b"ZZZZ");
+        assert_eq!(store.stats().unwrap().physical_bytes, 4);
+    }
+
+    #[test]
+    fn test_open_fs_reopen() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let store = FragmentStore::open_fs(dir.path()).unwrap();
+            store.store_fragment("repo1", 2, 3, b"persisted").unwrap();
+        }
+
+        let store = FragmentStore::open_fs(dir.path()).unwrap();
+        assert_eq!(store.get_fragment("repo1", 2, 3).unwrap().data, b"persisted");
+        assert_eq!(store.list_fragments("repo1").unwrap().len(), 1);
+    }
+
+    #[test]
+    fn test_open_fs_missing_blob_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FragmentStore::open_fs(dir.path()).unwrap();
+        store.store_fragment("repo1", 0, 1, b"lost").unwrap();
+        fs::remove_dir_all(dir.path().join(BLOB_DIR)).unwrap();
+
+        assert!(matches!(
+            store.get_fragment("repo1", 0, 1),
+            Err(StorageError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_encrypted_blobs() {
+        let dir = tempfile::tempdir().unwrap();
+        let key = EncryptionKey::generate();
+        // Three segments, the last one partial
+        let data: Vec<u8> = (0..2 * SEGMENT_LEN + 3000).map(|i| (i % 199) as u8).collect();
+        let sealed_len = encryption::sealed_len(data.len() as u64);
+        let mut inline = test_store();
+        let mut fs_store = FragmentStore::open_fs(dir.path()).unwrap();
+
+        for store in [&mut inline, &mut fs_store] {
+            store.set_encryption_key(Some(key.clone()));
+            assert_eq!(store.physical_size(3000), encryption::sealed_len(3000));
+            store.store_fragment("repo1", 0, 1, &data).unwrap();
+
+            // data_hash and logical sizes stay those of the plaintext;
+            // physical bytes count the nonce and tag of every segment
+            let record = store.get_fragment("repo1", 0, 1).unwrap();
+            assert_eq!(record.data, data);
+            assert_eq!(record.data_hash, sha256_hex(&data));
+            let stats = store.stats().unwrap();
+            assert_eq!(stats.logical_bytes, data.len() as u64);
+            assert_eq!(stats.physical_bytes, sealed_len);
+            for range in [
+                100..200,
+                SEGMENT_LEN - 10..SEGMENT_LEN + 10,
+                SEGMENT_LEN..2 * SEGMENT_LEN + 3000,
+                data.len() - 1..data.len(),
+            ] {
+                assert_eq!(
+                    store.read_fragment_range("repo1", 0, 1, range.clone()).unwrap(),
+                    &data[range]
+                );
+            }
+            assert!(store.read_fragment_range("repo1", 0, 1, 500..500).unwrap().is_empty());
+
+            // Writing plaintext again keeps the key for reading
+            store.set_encryption_key(None);
+            assert_eq!(store.physical_size(3000), 3000);
+            assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().data, data);
+            store.set_encryption_key(Some(key.clone()));
+        }
+
+        let stored: Vec<u8> = inline
+            .conn
+            .query_row("SELECT data FROM blobs", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(stored.len() as u64, sealed_len);
+        assert!(!stored.windows(64).any(|w| w == &data[..64]));
+        let hash = sha256_hex(&data);
+        let path = blob_file_path(dir.path(), &hash, Some(key.id()));
+        assert_eq!(fs::metadata(&path).unwrap().len(), sealed_len);
+
+        // Unreadable without the key
+        drop(fs_store);
+        let reopened = FragmentStore::open_fs(dir.path()).unwrap();
+        assert!(matches!(
+            reopened.get_fragment("repo1", 0, 1),
+            Err(StorageError::Encryption(_))
+        ));
+        assert!(matches!(
+            reopened.read_fragment_range("repo1", 0, 1, 0..10),
+            Err(StorageError::Encryption(_))
+        ));
+
+        // Tampering with one segment fails reads covering it, not others
+        let mut tampered = stored;
+        tampered[SEGMENT_LEN + SEGMENT_OVERHEAD + 50] ^= 1;
+        inline
+            .conn
+            .execute("UPDATE blobs SET data = ?1", rusqlite::params![tampered])
+            .unwrap();
+        assert!(matches!(
+            inline.get_fragment("repo1", 0, 1),
+            Err(StorageError::Decryption(_))
+        ));
+        assert!(matches!(
+            inline.read_fragment_range("repo1", 0, 1, SEGMENT_LEN..SEGMENT_LEN + 1),
+            Err(StorageError::Decryption(_))
+        ));
+        assert_eq!(inline.read_fragment_range("repo1", 0, 1, 0..10).unwrap(), &data[..10]);
+    }
+
+    #[test]
+    fn test_keyring() {
+        let dir = tempfile::tempdir().unwrap();
+        let a = EncryptionKey::generate();
+        let b = EncryptionKey::generate();
+        {
+            let mut store = FragmentStore::open_fs(dir.path()).unwrap();
+            store.set_encryption_key(Some(a.clone()));
+            store.store_fragment("repo1", 0, 1, b"under a").unwrap();
+
+            // Switching keys without rotating: both blobs stay readable
+            store.set_encryption_key(Some(b.clone()));
+            store.store_fragment("repo1", 1, 1, b"under b").unwrap();
+            assert_eq!(store.encryption_key_id(), Some(b.id()));
+            assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().data, b"under a");
+            assert_eq!(store.get_fragment("repo1", 1, 1).unwrap().data, b"under b");
+        }
+
+        // After a restart with only the new key, the old one is added back
+        // for reading, and rotation opens each blob with the key it names
+        let mut store = FragmentStore::open_fs(dir.path()).unwrap();
+        store.set_encryption_key(Some(b.clone()));
+        assert!(matches!(
+            store.rotate_encryption_key(Some(b.clone())),
+            Err(StorageError::Encryption(_))
+        ));
+        store.add_decryption_key(a);
+        assert_eq!(store.encryption_key_id(), Some(b.id()));
+        assert_eq!(store.rotate_encryption_key(Some(b.clone())).unwrap(), 1);
+        assert_eq!(store.rotate_encryption_key(Some(b)).unwrap(), 0);
+        assert_eq!(store.list_fragments("repo1").unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_rotate_encryption_key() {
+        let dir = tempfile::tempdir().unwrap();
+        let mut store = FragmentStore::open_fs(dir.path()).unwrap();
+        store.store_fragment("repo1", 0, 1, b"plaintext share").unwrap();
+        store.store_fragment("repo1", 1, 1, b"another share").unwrap();
+        let hash = sha256_hex(b"plaintext share");
+        let blob_file = |key_id: Option<&str>| blob_file_path(dir.path(), &hash, key_id);
+
+        let a = EncryptionKey::generate();
+        assert_eq!(store.rotate_encryption_key(Some(a.clone())).unwrap(), 2);
+        assert_eq!(store.encryption_key_id(), Some(a.id()));
+        assert!(!blob_file(None).exists());
+        assert!(blob_file(Some(a.id())).exists());
+        // Already under the new key: nothing left to do
+        assert_eq!(store.rotate_encryption_key(Some(a.clone())).unwrap(), 0);
+
+        let b = EncryptionKey::from_bytes([9; 32]);
+        assert_eq!(store.rotate_encryption_key(Some(b.clone())).unwrap(), 2);
+        assert!(!blob_file(Some(a.id())).exists());
+        assert!(blob_file(Some(b.id())).exists());
+        assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().data, b"plaintext share");
+
+        // Back to plaintext
+        assert_eq!(store.rotate_encryption_key(None).unwrap(), 2);
+        assert_eq!(store.encryption_key_id(), None);
+        assert_eq!(fs::read(blob_file(None)).unwrap(), b"plaintext share");
+        assert_eq!(store.list_fragments("repo1").unwrap().len(), 2);
     }
 }
@@ -0,0 +1,308 @@
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::ops::Range;
+use std::path::Path;
+
+use chacha20poly1305::aead::{Aead, KeyInit, Payload};
+use chacha20poly1305::{ChaCha20Poly1305, Nonce};
+use gitgold_core::error::StorageError;
+use gitgold_crypto::hash::sha256_pair;
+use gitgold_crypto::wallet::Wallet;
+use rand::RngCore;
+use zeroize::Zeroizing;
+
+/// Length of an `EncryptionKey` in bytes.
+pub const KEY_LEN: usize = 32;
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// Plaintext bytes per sealed segment (the last segment may be shorter).
+pub const SEGMENT_LEN: usize = 64 * 1024;
+
+/// Bytes each sealed segment adds to its plaintext: nonce and tag.
+pub const SEGMENT_OVERHEAD: usize = NONCE_LEN + TAG_LEN;
+
+/// Domain separator for deriving the at-rest key from a wallet secret, so
+/// the key is never the signing key itself.
+const WALLET_KEY_DOMAIN: &[u8] = b"gitgold-storage-at-rest-key-v1";
+
+/// Key for encrypting blob data at rest with ChaCha20-Poly1305.
+///
+/// A blob is sealed in `SEGMENT_LEN` plaintext segments, each stored as
+/// `nonce || ciphertext || tag` under a fresh random nonce, so a range read
+/// only opens the segments it covers. Every segment is authenticated
+/// together with the blob's `data_hash`, its index and whether it is the
+/// last one, so segments cannot be swapped, reordered or dropped. The
+/// `data_hash` itself stays the SHA-256 of the plaintext.
+///
+/// The key bytes are zeroed when the key is dropped.
+#[derive(Clone)]
+pub struct EncryptionKey {
+    key: Zeroizing<[u8; KEY_LEN]>,
+    id: String,
+}
+
+impl EncryptionKey {
+    /// Use raw key bytes.
+    pub fn from_bytes(key: [u8; KEY_LEN]) -> Self {
+        Self::from_zeroizing(Zeroizing::new(key))
+    }
+
+    /// Take ownership of key bytes already held in a zeroizing buffer, so
+    /// they are never copied out of it.
+    fn from_zeroizing(key: Zeroizing<[u8; KEY_LEN]>) -> Self {
+        // Short fingerprint recorded with each blob; does not reveal the key
+        let id = hex::encode(&sha256_pair(b"gitgold-key-id", &*key)[..8]);
+        Self { key, id }
+    }
+
+    /// Generate a random key.
+    pub fn generate() -> Self {
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        rand::thread_rng().fill_bytes(&mut *key);
+        Self::from_zeroizing(key)
+    }
+
+    /// Derive the key from a node wallet's secret key.
+    pub fn from_wallet(wallet: &Wallet) -> Self {
+        let secret = Zeroizing::new(wallet.secret_bytes());
+        Self::from_zeroizing(Zeroizing::new(sha256_pair(WALLET_KEY_DOMAIN, &*secret)))
+    }
+
+    /// Read a key file holding the key as 64 hex characters.
+    pub fn from_key_file(path: impl AsRef<Path>) -> Result<Self, StorageError> {
+        let text = Zeroizing::new(
+            fs::read_to_string(path).map_err(|e| StorageError::Io(e.to_string()))?,
+        );
+        let bytes = Zeroizing::new(hex::decode(text.trim()).unwrap_or_default());
+        if bytes.len() != KEY_LEN {
+            return Err(StorageError::Encryption(format!(
+                "key file must hold {} hex characters",
+                2 * KEY_LEN
+            )));
+        }
+        let mut key = Zeroizing::new([0u8; KEY_LEN]);
+        key.copy_from_slice(&bytes);
+        Ok(Self::from_zeroizing(key))
+    }
+
+    /// Write the key to a new key file readable only by its owner. Fails if
+    /// the file already exists.
+    pub fn write_key_file(&self, path: impl AsRef<Path>) -> Result<(), StorageError> {
+        let io = |e: std::io::Error| StorageError::Io(e.to_string());
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options.open(path).map_err(io)?;
+        let text = Zeroizing::new(hex::encode(*self.key));
+        writeln!(file, "{}", *text).map_err(io)
+    }
+
+    /// Fingerprint identifying the key, recorded with every blob it seals.
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    fn cipher(&self) -> ChaCha20Poly1305 {
+        ChaCha20Poly1305::new((&*self.key).into())
+    }
+
+    /// Encrypt the blob `data_hash` for storage. The result is
+    /// `sealed_len(plaintext.len())` bytes long.
+    pub fn seal(&self, data_hash: &str, plaintext: &[u8]) -> Vec<u8> {
+        let cipher = self.cipher();
+        let count = segment_count(plaintext.len() as u64);
+        let mut sealed = Vec::with_capacity(sealed_len(plaintext.len() as u64) as usize);
+        for index in 0..count {
+            let start = index as usize * SEGMENT_LEN;
+            let end = (start + SEGMENT_LEN).min(plaintext.len());
+            let mut nonce = [0u8; NONCE_LEN];
+            rand::thread_rng().fill_bytes(&mut nonce);
+            let aad = segment_aad(data_hash, index, index + 1 == count);
+            let ciphertext = cipher
+                .encrypt(
+                    Nonce::from_slice(&nonce),
+                    Payload {
+                        msg: &plaintext[start..end],
+                        aad: &aad,
+                    },
+                )
+                .expect("segment is far below the ChaCha20-Poly1305 length limit");
+            sealed.extend_from_slice(&nonce);
+            sealed.extend(ciphertext);
+        }
+        sealed
+    }
+
+    /// Decrypt a blob sealed by `seal`, failing with `Decryption` if it was
+    /// altered.
+    pub fn open(&self, data_hash: &str, sealed: &[u8]) -> Result<Vec<u8>, StorageError> {
+        let plain_len = plain_len(sealed.len() as u64)
+            .ok_or_else(|| StorageError::Decryption(data_hash.to_string()))?;
+        self.open_segments(data_hash, plain_len, 0, sealed)
+    }
+
+    /// Decrypt consecutive sealed segments of a blob of `plain_len`
+    /// plaintext bytes, starting at segment `first_segment`, as located by
+    /// `sealed_range`. Fails with `Decryption` if any of them was altered.
+    pub fn open_segments(
+        &self,
+        data_hash: &str,
+        plain_len: u64,
+        first_segment: u64,
+        sealed: &[u8],
+    ) -> Result<Vec<u8>, StorageError> {
+        let fail = || StorageError::Decryption(data_hash.to_string());
+        let cipher = self.cipher();
+        let count = segment_count(plain_len);
+        let mut plaintext = Vec::with_capacity(sealed.len());
+        for (index, segment) in (first_segment..).zip(sealed.chunks(SEGMENT_LEN + SEGMENT_OVERHEAD))
+        {
+            if index >= count {
+                return Err(fail());
+            }
+            let (nonce, ciphertext) = segment.split_at_checked(NONCE_LEN).ok_or_else(fail)?;
+            let aad = segment_aad(data_hash, index, index + 1 == count);
+            let opened = cipher
+                .decrypt(
+                    Nonce::from_slice(nonce),
+                    Payload {
+                        msg: ciphertext,
+                        aad: &aad,
+                    },
+                )
+                .map_err(|_| fail())?;
+            plaintext.extend(opened);
+        }
+        Ok(plaintext)
+    }
+}
+
+impl fmt::Debug for EncryptionKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptionKey").field("id", &self.id).finish_non_exhaustive()
+    }
+}
+
+/// Number of segments a blob of `plain_len` bytes is sealed in; an empty
+/// blob still gets one, so it carries a tag.
+fn segment_count(plain_len: u64) -> u64 {
+    plain_len.div_ceil(SEGMENT_LEN as u64).max(1)
+}
+
+/// Size on disk of a blob of `plain_len` bytes once sealed.
+pub fn sealed_len(plain_len: u64) -> u64 {
+    plain_len + segment_count(plain_len) * SEGMENT_OVERHEAD as u64
+}
+
+/// Plaintext size of a sealed blob of `sealed_len` bytes, or `None` if no
+/// plaintext seals to that size.
+fn plain_len(sealed_len: u64) -> Option<u64> {
+    let count = sealed_len
+        .div_ceil((SEGMENT_LEN + SEGMENT_OVERHEAD) as u64)
+        .max(1);
+    let plain = sealed_len.checked_sub(count * SEGMENT_OVERHEAD as u64)?;
+    (segment_count(plain) == count).then_some(plain)
+}
+
+/// Locate the sealed bytes covering the non-empty plaintext `range` of a
+/// blob of `plain_len` bytes. Returns their byte range within the sealed
+/// blob and the index of the first segment, for `open_segments`; the
+/// opened plaintext starts at `first_segment * SEGMENT_LEN`.
+pub fn sealed_range(plain_len: u64, range: &Range<usize>) -> (Range<u64>, u64) {
+    let stride = (SEGMENT_LEN + SEGMENT_OVERHEAD) as u64;
+    let first = (range.start / SEGMENT_LEN) as u64;
+    let last = (range.end.saturating_sub(1) / SEGMENT_LEN) as u64;
+    let end = ((last + 1) * stride).min(sealed_len(plain_len));
+    (first * stride..end, first)
+}
+
+/// Associated data of one segment: the blob hash, the segment index and
+/// whether it is the last segment.
+fn segment_aad(data_hash: &str, index: u64, last: bool) -> Vec<u8> {
+    let mut aad = data_hash.as_bytes().to_vec();
+    aad.extend_from_slice(&index.to_be_bytes());
+    aad.push(u8::from(last));
+    aad
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitgold_crypto::hash::sha256_hex;
+
+    #[test]
+    fn test_seal_open() {
+        let key = EncryptionKey::generate();
+        let data = b"share bytes".to_vec();
+        let data_hash = sha256_hex(&data);
+
+        let sealed = key.seal(&data_hash, &data);
+        assert_eq!(sealed.len() as u64, sealed_len(data.len() as u64));
+        assert_ne!(&sealed[NONCE_LEN..NONCE_LEN + data.len()], &data[..]);
+        assert_eq!(key.open(&data_hash, &sealed).unwrap(), data);
+        assert_eq!(key.open("empty", &key.seal("empty", b"")).unwrap(), b"");
+
+        // Bound to the blob hash and the key
+        assert!(matches!(
+            key.open("other", &sealed),
+            Err(StorageError::Decryption(_))
+        ));
+        assert!(EncryptionKey::generate().open(&data_hash, &sealed).is_err());
+        assert!(key.open(&data_hash, &sealed[..5]).is_err());
+    }
+
+    #[test]
+    fn test_segments() {
+        let key = EncryptionKey::generate();
+        let data: Vec<u8> = (0..2 * SEGMENT_LEN + 100).map(|i| (i % 251) as u8).collect();
+        let data_hash = sha256_hex(&data);
+        let sealed = key.seal(&data_hash, &data);
+        assert_eq!(sealed.len(), data.len() + 3 * SEGMENT_OVERHEAD);
+        assert_eq!(key.open(&data_hash, &sealed).unwrap(), data);
+
+        // A range spanning the last two segments opens only those
+        let range = SEGMENT_LEN + 10..2 * SEGMENT_LEN + 50;
+        let (sealed_bytes, first) = sealed_range(data.len() as u64, &range);
+        assert_eq!(first, 1);
+        assert_eq!(sealed_bytes.end, sealed.len() as u64);
+        let part = &sealed[sealed_bytes.start as usize..sealed_bytes.end as usize];
+        let opened = key
+            .open_segments(&data_hash, data.len() as u64, first, part)
+            .unwrap();
+        let offset = range.start - first as usize * SEGMENT_LEN;
+        assert_eq!(&opened[offset..offset + range.len()], &data[range]);
+
+        // Segments cannot be reordered or dropped
+        let stride = SEGMENT_LEN + SEGMENT_OVERHEAD;
+        let mut swapped = sealed[stride..2 * stride].to_vec();
+        swapped.extend_from_slice(&sealed[..stride]);
+        swapped.extend_from_slice(&sealed[2 * stride..]);
+        assert!(key.open(&data_hash, &swapped).is_err());
+        assert!(key.open(&data_hash, &sealed[..2 * stride]).is_err());
+    }
+
+    #[test]
+    fn test_key_sources() {
+        let wallet = Wallet::from_secret(&[3; 32]);
+        let a = EncryptionKey::from_wallet(&wallet);
+        assert_eq!(a.id(), EncryptionKey::from_wallet(&wallet).id());
+        assert_ne!(*a.key, wallet.secret_bytes());
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("storage.key");
+        a.write_key_file(&path).unwrap();
+        assert_eq!(EncryptionKey::from_key_file(&path).unwrap().id(), a.id());
+        assert!(a.write_key_file(&path).is_err());
+
+        fs::write(&path, "not hex").unwrap();
+        assert!(matches!(
+            EncryptionKey::from_key_file(&path),
+            Err(StorageError::Encryption(_))
+        ));
+        assert!(!format!("{a:?}").contains(&hex::encode(*a.key)));
+    }
+}
@@ -0,0 +1,271 @@
+use gitgold_core::config::GitGoldConfig;
+use gitgold_core::error::StorageError;
+
+use crate::backend::{FragmentBackend, FragmentMeta, GcEntry, GcReason, Lease};
+use crate::manifest::RepoManifest;
+
+/// Fragments read per `list_fragment_meta` page.
+const PAGE_SIZE: usize = 1000;
+
+/// Settings for a `collect_garbage` pass.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct GcOptions {
+    /// Seconds past lease expiry, or since an unreferenced fragment's last
+    /// activity (stored, challenged, or its lease renewed), before it may be
+    /// deleted.
+    pub grace_secs: u64,
+    /// Report what would be deleted without deleting anything or writing the
+    /// audit log.
+    pub dry_run: bool,
+}
+
+impl GcOptions {
+    /// Options with the grace period from `config` (whitepaper §6.2).
+    pub fn from_config(config: &GitGoldConfig) -> Self {
+        Self {
+            grace_secs: config.gc_grace_secs,
+            dry_run: false,
+        }
+    }
+}
+
+impl Default for GcOptions {
+    fn default() -> Self {
+        Self::from_config(&GitGoldConfig::default())
+    }
+}
+
+/// Outcome of a `collect_garbage` pass.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct GcReport {
+    /// Fragments deleted, or that would be deleted in a dry run.
+    pub entries: Vec<GcEntry>,
+    /// Logical bytes of those fragments.
+    pub bytes: u64,
+    /// Whether this was a dry run.
+    pub dry_run: bool,
+}
+
+/// Delete fragments that are no longer paid for or no longer referenced.
+///
+/// Every fragment of a repository is collected when its lease (`set_lease`)
+/// ran out more than `grace_secs` before `now`. Otherwise a fragment is
+/// collected as unreferenced only when its repository has a stored manifest
+/// that does not reference it (the fragment_id is past the manifest's last
+/// chunk), no unexpired lease, and no activity for `grace_secs`: it was not
+/// stored, challenged, or its lease renewed since. Repositories without a
+/// manifest are never collected as unreferenced, since manifests are
+/// optional. Deletions and their reasons are written to the audit log
+/// (`gc_log`) in the same step.
+pub fn collect_garbage<B: FragmentBackend + ?Sized>(
+    backend: &B,
+    options: &GcOptions,
+    now: i64,
+) -> Result<GcReport, StorageError> {
+    let cutoff = now.saturating_sub(i64::try_from(options.grace_secs).unwrap_or(i64::MAX));
+    let mut report = GcReport {
+        dry_run: options.dry_run,
+        ..GcReport::default()
+    };
+
+    for usage in backend.usage()? {
+        let repo_hash = &usage.repo_hash;
+        let lease = backend.get_lease(repo_hash)?;
+        let lease_expired = lease.is_some_and(|l| l.paid_until < cutoff);
+        let manifest = if lease_expired || lease.is_some_and(|l| l.paid_until >= now) {
+            None
+        } else {
+            backend.get_manifest(repo_hash)?
+        };
+        if !lease_expired && manifest.is_none() {
+            continue;
+        }
+
+        let mut after = None;
+        loop {
+            let page = backend.list_fragment_meta(repo_hash, after, PAGE_SIZE)?;
+            for meta in &page {
+                let reason = if lease_expired {
+                    GcReason::LeaseExpired
+                } else if !is_referenced(manifest.as_ref(), meta)
+                    && last_activity(meta, lease.as_ref()) < cutoff
+                {
+                    GcReason::Unreferenced
+                } else {
+                    continue;
+                };
+                report.bytes += meta.size;
+                report.entries.push(GcEntry {
+                    key: meta.key(),
+                    data_hash: meta.data_hash.clone(),
+                    reason,
+                    deleted_at: now,
+                });
+            }
+            match page.last() {
+                Some(last) if page.len() == PAGE_SIZE => {
+                    after = Some((last.fragment_id, last.share_id));
+                }
+                _ => break,
+            }
+        }
+    }
+
+    if !options.dry_run && !report.entries.is_empty() {
+        backend.purge_fragments(&report.entries)?;
+    }
+    Ok(report)
+}
+
+/// Whether `manifest` references the chunk a fragment belongs to. Share ids
+/// are not checked, since resharing may legitimately change n.
+fn is_referenced(manifest: Option<&RepoManifest>, meta: &FragmentMeta) -> bool {
+    manifest.is_some_and(|m| (meta.fragment_id as usize) < m.chunks.len())
+}
+
+/// Latest activity on a fragment: when it was stored, last challenged, or
+/// its repository's lease last renewed.
+fn last_activity(meta: &FragmentMeta, lease: Option<&Lease>) -> i64 {
+    let challenged = meta.last_challenged.unwrap_or(i64::MIN);
+    let renewed = lease.map_or(i64::MIN, |l| l.renewed_at);
+    meta.stored_at.max(challenged).max(renewed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::ChallengeRecord;
+    use crate::chunk::chunk_data;
+    use crate::db::FragmentStore;
+    use crate::memory::MemoryFragmentStore;
+    use gitgold_core::time::unix_now;
+    use gitgold_core::types::Address;
+    use gitgold_crypto::keys::KeyPair;
+
+    const DAY: i64 = 24 * 60 * 60;
+
+    /// Store three chunks of one share each under a manifest covering the
+    /// first two.
+    fn populate(store: &dyn FragmentBackend, repo_hash: &str) {
+        let chunks = chunk_data(&[0x5A; 2000], 1000);
+        let manifest =
+            RepoManifest::new(&[1; 32], &chunks, &GitGoldConfig::default(), &KeyPair::generate())
+                .unwrap();
+        store.store_manifest(repo_hash, &manifest).unwrap();
+        for fid in 0..3 {
+            store
+                .store_fragment(repo_hash, fid, 1, format!("{repo_hash} {fid}").as_bytes())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_unreferenced_after_grace() {
+        let store = FragmentStore::in_memory().unwrap();
+        populate(&store, "repo1");
+        populate(&store, "active");
+        store.store_fragment("orphan", 0, 1, b"no manifest").unwrap();
+        let options = GcOptions::default();
+
+        // A challenge counts as activity and restarts the grace period
+        store
+            .record_challenge(&ChallengeRecord {
+                challenge_id: "c1".to_string(),
+                repo_hash: "active".to_string(),
+                fragment_id: 2,
+                share_id: 1,
+                challenger: Address::new("validator"),
+                node: Address::new("node"),
+                nonce: [0; 32],
+                success: true,
+                failure_reason: None,
+                response_time_ms: 10,
+                challenged_at: unix_now() + 80 * DAY,
+            })
+            .unwrap();
+
+        // Within the grace period nothing is collected
+        let report = collect_garbage(&store, &options, unix_now() + 89 * DAY).unwrap();
+        assert!(report.entries.is_empty());
+
+        let later = unix_now() + 91 * DAY;
+        let report = collect_garbage(&store, &options, later).unwrap();
+        let keys: Vec<_> = report
+            .entries
+            .iter()
+            .map(|e| (e.key.repo_hash.as_str(), e.key.fragment_id, e.reason))
+            .collect();
+        // Repositories without a manifest are never unreferenced
+        assert_eq!(keys, [("repo1", 2, GcReason::Unreferenced)]);
+        assert_eq!(report.bytes, 7);
+        assert_eq!(store.list_fragments("repo1").unwrap().len(), 2);
+        assert_eq!(store.list_fragments("active").unwrap().len(), 3);
+        assert_eq!(store.list_fragments("orphan").unwrap().len(), 1);
+        assert_eq!(store.gc_log(later).unwrap(), report.entries);
+    }
+
+    #[test]
+    fn test_paid_lease_keeps_fragments() {
+        let store = MemoryFragmentStore::new();
+        let now = unix_now();
+        store.store_fragment("no-manifest", 0, 1, b"live data").unwrap();
+        store.set_lease("no-manifest", now + 365 * DAY).unwrap();
+        populate(&store, "paid");
+        store.set_lease("paid", now + 365 * DAY).unwrap();
+
+        // Long past the grace period, but still paid for
+        let report = collect_garbage(&store, &GcOptions::default(), now + 200 * DAY).unwrap();
+        assert!(report.entries.is_empty());
+
+        // Once the lease has run out, the unreferenced fragment still waits
+        // out the grace period from its last activity
+        store.set_lease("paid", now - DAY).unwrap();
+        let report = collect_garbage(&store, &GcOptions::default(), now + 30 * DAY).unwrap();
+        assert!(report.entries.is_empty());
+        let report = collect_garbage(&store, &GcOptions::default(), now + 91 * DAY).unwrap();
+        assert_eq!(report.entries.len(), 3);
+        assert!(report
+            .entries
+            .iter()
+            .all(|e| e.key.repo_hash == "paid" && e.reason == GcReason::LeaseExpired));
+    }
+
+    #[test]
+    fn test_lease_expiry() {
+        let store = MemoryFragmentStore::new();
+        populate(&store, "paid");
+        populate(&store, "lapsed");
+        let now = unix_now();
+        store.set_lease("paid", now + 365 * DAY).unwrap();
+        store.set_lease("lapsed", now - 100 * DAY).unwrap();
+        let lease = store.get_lease("lapsed").unwrap().unwrap();
+        assert_eq!(lease.paid_until, now - 100 * DAY);
+        assert!(lease.renewed_at >= now);
+
+        let report = collect_garbage(&store, &GcOptions::default(), now).unwrap();
+        assert_eq!(report.entries.len(), 3);
+        assert!(report
+            .entries
+            .iter()
+            .all(|e| e.key.repo_hash == "lapsed" && e.reason == GcReason::LeaseExpired));
+        assert!(store.list_fragments("lapsed").unwrap().is_empty());
+        assert_eq!(store.list_fragments("paid").unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_dry_run() {
+        let store = FragmentStore::in_memory().unwrap();
+        populate(&store, "lapsed");
+        store.set_lease("lapsed", 0).unwrap();
+        let options = GcOptions {
+            dry_run: true,
+            ..GcOptions::default()
+        };
+
+        let report = collect_garbage(&store, &options, unix_now()).unwrap();
+        assert!(report.dry_run);
+        assert_eq!(report.entries.len(), 3);
+        assert_eq!(store.list_fragments("lapsed").unwrap().len(), 3);
+        assert!(store.gc_log(0).unwrap().is_empty());
+    }
+}
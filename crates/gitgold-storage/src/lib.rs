@@ -1,3 +1,12 @@
+pub mod backend;
 pub mod chunk;
 pub mod db;
+pub mod encryption;
+pub mod gc;
+pub mod manifest;
+pub mod memory;
+pub mod parallel;
+pub mod quota;
 pub mod schema;
+pub mod scrub;
+pub mod stream;
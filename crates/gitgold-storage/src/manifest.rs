@@ -0,0 +1,403 @@
+use gitgold_core::config::GitGoldConfig;
+use gitgold_core::error::StorageError;
+use gitgold_core::types::{Hash256, ShareScheme};
+use gitgold_crypto::hash::sha256;
+use gitgold_crypto::keys::{KeyPair, PublicKey};
+use gitgold_ledger::merkle::{MerkleTree, ProofStep};
+
+/// Manifest envelope magic bytes.
+const MANIFEST_MAGIC: &[u8; 4] = b"GGMF";
+
+/// Current manifest format version.
+const MANIFEST_VERSION: u8 = 1;
+
+/// Magic, version, repo hash, total length, scheme, k, n, chunk count.
+const MANIFEST_HEADER_LEN: usize = 4 + 1 + 32 + 8 + 1 + 4 + 4 + 4;
+
+/// Serialized size of one chunk entry: length, then hash.
+const CHUNK_ENTRY_LEN: usize = 8 + 32;
+
+/// Ed25519 public key and signature sizes.
+const PUBLIC_KEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+
+/// One chunk of a repository as recorded in its manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ChunkEntry {
+    /// Chunk length in bytes.
+    pub length: u64,
+    /// SHA-256 of the chunk data.
+    pub hash: Hash256,
+}
+
+/// Signed description of how a repository was fragmented.
+///
+/// Chunks are listed in index order; chunk `i` is `chunks[i]`. The Merkle
+/// root commits to the ordered chunk hashes, so a single chunk can be proven
+/// against the manifest with `chunk_proof`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RepoManifest {
+    /// Hash identifying the repository.
+    pub repo_hash: Hash256,
+    /// Total repository length in bytes.
+    pub total_len: u64,
+    /// Engine used to split every chunk.
+    pub share_scheme: ShareScheme,
+    /// Sharing threshold.
+    pub k: u32,
+    /// Shares per chunk.
+    pub n: u32,
+    /// Ordered chunk list.
+    pub chunks: Vec<ChunkEntry>,
+    /// Merkle root over the chunk hashes.
+    pub merkle_root: Hash256,
+    /// Public key of the signer.
+    pub signer: PublicKey,
+    /// Ed25519 signature over `signable_bytes`.
+    pub signature: Vec<u8>,
+}
+
+impl RepoManifest {
+    /// Describe `chunks` (as produced by `chunk_with_config`) and sign the
+    /// result with `key`.
+    ///
+    /// Chunk indices must be 0, 1, 2, ... in order.
+    pub fn new(
+        repo_hash: &Hash256,
+        chunks: &[(u32, Vec<u8>)],
+        config: &GitGoldConfig,
+        key: &KeyPair,
+    ) -> Result<Self, StorageError> {
+        if let Some((_, (index, _))) = chunks
+            .iter()
+            .enumerate()
+            .find(|(pos, (index, _))| *index != *pos as u32)
+        {
+            return Err(StorageError::InvalidChunkIndex {
+                index: *index,
+                total: chunks.len() as u32,
+            });
+        }
+
+        let entries: Vec<ChunkEntry> = chunks
+            .iter()
+            .map(|(_, data)| ChunkEntry {
+                length: data.len() as u64,
+                hash: sha256(data),
+            })
+            .collect();
+
+        let mut manifest = Self {
+            repo_hash: *repo_hash,
+            total_len: entries.iter().map(|e| e.length).sum(),
+            share_scheme: config.share_scheme,
+            k: config.k as u32,
+            n: config.n as u32,
+            merkle_root: merkle_root(&entries),
+            chunks: entries,
+            signer: key.public_key(),
+            signature: Vec::new(),
+        };
+        manifest.signature = key.sign(&manifest.signable_bytes());
+        Ok(manifest)
+    }
+
+    /// Canonical bytes covered by the signature: every field except the
+    /// signature itself, in the order of `to_bytes` (integers big-endian).
+    pub fn signable_bytes(&self) -> Vec<u8> {
+        let mut out = Vec::with_capacity(
+            MANIFEST_HEADER_LEN + self.chunks.len() * CHUNK_ENTRY_LEN + 32 + PUBLIC_KEY_LEN,
+        );
+        out.extend_from_slice(MANIFEST_MAGIC);
+        out.push(MANIFEST_VERSION);
+        out.extend_from_slice(&self.repo_hash);
+        out.extend_from_slice(&self.total_len.to_be_bytes());
+        out.push(self.share_scheme.id());
+        out.extend_from_slice(&self.k.to_be_bytes());
+        out.extend_from_slice(&self.n.to_be_bytes());
+        out.extend_from_slice(&(self.chunks.len() as u32).to_be_bytes());
+        for entry in &self.chunks {
+            out.extend_from_slice(&entry.length.to_be_bytes());
+            out.extend_from_slice(&entry.hash);
+        }
+        out.extend_from_slice(&self.merkle_root);
+        out.extend_from_slice(&self.signer.bytes);
+        out
+    }
+
+    /// Serialize as `signable_bytes || signature`.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut out = self.signable_bytes();
+        out.extend_from_slice(&self.signature);
+        out
+    }
+
+    /// Parse a serialized manifest and check that it is internally
+    /// consistent (length total and Merkle root). The signature is not
+    /// checked; see `verify_signature`.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, StorageError> {
+        if bytes.len() < MANIFEST_HEADER_LEN || &bytes[..4] != MANIFEST_MAGIC {
+            return Err(invalid("not a manifest envelope"));
+        }
+        if bytes[4] != MANIFEST_VERSION {
+            return Err(invalid(format!("unsupported manifest version {}", bytes[4])));
+        }
+
+        let repo_hash: Hash256 = bytes[5..37].try_into().unwrap();
+        let total_len = u64::from_be_bytes(bytes[37..45].try_into().unwrap());
+        let share_scheme = ShareScheme::from_id(bytes[45])
+            .ok_or_else(|| invalid(format!("unknown share scheme id {}", bytes[45])))?;
+        let k = u32::from_be_bytes(bytes[46..50].try_into().unwrap());
+        let n = u32::from_be_bytes(bytes[50..54].try_into().unwrap());
+        let count = u32::from_be_bytes(bytes[54..58].try_into().unwrap()) as usize;
+
+        let expected = count
+            .checked_mul(CHUNK_ENTRY_LEN)
+            .and_then(|c| c.checked_add(MANIFEST_HEADER_LEN + 32 + PUBLIC_KEY_LEN + SIGNATURE_LEN));
+        if expected != Some(bytes.len()) {
+            return Err(invalid(format!(
+                "{} bytes for a manifest of {count} chunks",
+                bytes.len()
+            )));
+        }
+
+        let body = &bytes[MANIFEST_HEADER_LEN..];
+        let (entries, rest) = body.split_at(count * CHUNK_ENTRY_LEN);
+        let chunks: Vec<ChunkEntry> = entries
+            .chunks_exact(CHUNK_ENTRY_LEN)
+            .map(|e| ChunkEntry {
+                length: u64::from_be_bytes(e[..8].try_into().unwrap()),
+                hash: e[8..].try_into().unwrap(),
+            })
+            .collect();
+        let merkle_root: Hash256 = rest[..32].try_into().unwrap();
+        let signer = PublicKey {
+            bytes: rest[32..32 + PUBLIC_KEY_LEN].to_vec(),
+        };
+        let signature = rest[32 + PUBLIC_KEY_LEN..].to_vec();
+
+        // Lengths are untrusted, so their sum may overflow
+        let sum = chunks
+            .iter()
+            .try_fold(0u64, |sum, e| sum.checked_add(e.length))
+            .ok_or_else(|| invalid("chunk lengths overflow"))?;
+        if sum != total_len {
+            return Err(invalid("chunk lengths do not add up to the total length"));
+        }
+        if self::merkle_root(&chunks) != merkle_root {
+            return Err(invalid("Merkle root does not match the chunk hashes"));
+        }
+
+        Ok(Self {
+            repo_hash,
+            total_len,
+            share_scheme,
+            k,
+            n,
+            chunks,
+            merkle_root,
+            signer,
+            signature,
+        })
+    }
+
+    /// Check the signature against the embedded signer key.
+    ///
+    /// Callers must still check that `signer` is a key they trust.
+    pub fn verify_signature(&self) -> Result<(), StorageError> {
+        if self.signer.verify(&self.signable_bytes(), &self.signature) {
+            Ok(())
+        } else {
+            Err(invalid("signature does not verify"))
+        }
+    }
+
+    /// Indices of the chunks that fail verification, ascending and without
+    /// repeats: chunks in `chunks` that do not match the manifest (wrong
+    /// hash or length, an index the manifest does not list, or an index
+    /// supplied more than once) and listed chunks that are absent. Empty
+    /// when every chunk verifies.
+    pub fn verify_chunks(&self, chunks: &[(u32, Vec<u8>)]) -> Vec<u32> {
+        let mut seen = vec![false; self.chunks.len()];
+        let mut failed: Vec<u32> = Vec::new();
+
+        for (index, data) in chunks {
+            match self.chunks.get(*index as usize) {
+                Some(_) if seen[*index as usize] => failed.push(*index),
+                Some(entry) if entry.length == data.len() as u64 && entry.hash == sha256(data) => {
+                    seen[*index as usize] = true;
+                }
+                _ => failed.push(*index),
+            }
+        }
+        failed.extend(
+            seen.iter()
+                .enumerate()
+                .filter(|(_, seen)| !**seen)
+                .map(|(i, _)| i as u32),
+        );
+        failed.sort_unstable();
+        failed.dedup();
+        failed
+    }
+
+    /// Reassemble the repository from its chunks, verifying each one against
+    /// the manifest first.
+    ///
+    /// Fails with `StorageError::CorruptChunks` listing every chunk that is
+    /// missing or does not match.
+    pub fn reassemble(&self, mut chunks: Vec<(u32, Vec<u8>)>) -> Result<Vec<u8>, StorageError> {
+        let failed = self.verify_chunks(&chunks);
+        if !failed.is_empty() {
+            return Err(StorageError::CorruptChunks { indices: failed });
+        }
+
+        chunks.sort_by_key(|(idx, _)| *idx);
+        let mut result = Vec::with_capacity(self.total_len as usize);
+        for (_, data) in chunks {
+            result.extend_from_slice(&data);
+        }
+        if result.len() as u64 != self.total_len {
+            return Err(invalid("chunks do not add up to the total length"));
+        }
+        Ok(result)
+    }
+
+    /// Merkle inclusion proof for chunk `index` against `merkle_root`.
+    pub fn chunk_proof(&self, index: u32) -> Option<Vec<ProofStep>> {
+        MerkleTree::build(self.chunks.iter().map(|e| e.hash).collect()).proof(index as usize)
+    }
+}
+
+fn merkle_root(chunks: &[ChunkEntry]) -> Hash256 {
+    MerkleTree::build(chunks.iter().map(|e| e.hash).collect()).root()
+}
+
+fn invalid(reason: impl Into<String>) -> StorageError {
+    StorageError::InvalidManifest(reason.into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::chunk_data;
+
+    /// 2500 bytes in chunks of 1000, and their manifest signed by `key`.
+    fn sample(key: &KeyPair) -> (Vec<u8>, RepoManifest) {
+        let data: Vec<u8> = (0..2500).map(|i| (i * 7 % 256) as u8).collect();
+        let chunks = chunk_data(&data, 1000);
+        let manifest =
+            RepoManifest::new(&sha256(&data), &chunks, &GitGoldConfig::default(), key).unwrap();
+        (data, manifest)
+    }
+
+    #[test]
+    fn test_manifest_describes_chunks() {
+        let key = KeyPair::generate();
+        let (data, manifest) = sample(&key);
+        let chunks = chunk_data(&data, 1000);
+        assert_eq!(manifest.total_len, 2500);
+        assert_eq!(manifest.chunks.len(), 3);
+        assert_eq!(manifest.chunks[2].length, 500);
+        assert_eq!(manifest.chunks[1].hash, sha256(&chunks[1].1));
+        assert_eq!((manifest.k, manifest.n), (5, 9));
+        assert_eq!(manifest.signer.bytes, key.public_key().bytes);
+        manifest.verify_signature().unwrap();
+        assert_eq!(manifest.reassemble(chunks).unwrap(), data);
+    }
+
+    #[test]
+    fn test_canonical_roundtrip() {
+        let (_, manifest) = sample(&KeyPair::generate());
+        let bytes = manifest.to_bytes();
+        let parsed = RepoManifest::from_bytes(&bytes).unwrap();
+        assert_eq!(parsed, manifest);
+        assert_eq!(parsed.to_bytes(), bytes);
+        parsed.verify_signature().unwrap();
+    }
+
+    #[test]
+    fn test_tampering_detected() {
+        let (_, manifest) = sample(&KeyPair::generate());
+
+        let mut edited = manifest.clone();
+        edited.k = 2;
+        assert!(edited.verify_signature().is_err());
+
+        // A changed chunk hash breaks the Merkle root on parse
+        let mut bytes = manifest.to_bytes();
+        bytes[MANIFEST_HEADER_LEN + 8] ^= 1;
+        assert!(matches!(
+            RepoManifest::from_bytes(&bytes),
+            Err(StorageError::InvalidManifest(_))
+        ));
+        assert!(RepoManifest::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+
+        // Chunk lengths whose sum overflows are rejected, not summed
+        let mut bytes = manifest.to_bytes();
+        for entry in 0..2 {
+            let offset = MANIFEST_HEADER_LEN + entry * CHUNK_ENTRY_LEN;
+            bytes[offset..offset + 8].copy_from_slice(&u64::MAX.to_be_bytes());
+        }
+        assert!(matches!(
+            RepoManifest::from_bytes(&bytes),
+            Err(StorageError::InvalidManifest(_))
+        ));
+    }
+
+    #[test]
+    fn test_reassemble_reports_bad_chunks() {
+        let (data, manifest) = sample(&KeyPair::generate());
+        let mut chunks = chunk_data(&data, 1000);
+        chunks[0].1[3] ^= 0xFF;
+        chunks.remove(2);
+        chunks.push((7, vec![1, 2, 3]));
+
+        assert_eq!(manifest.verify_chunks(&chunks), vec![0, 2, 7]);
+        assert!(matches!(
+            manifest.reassemble(chunks),
+            Err(StorageError::CorruptChunks { indices }) if indices == vec![0, 2, 7]
+        ));
+    }
+
+    #[test]
+    fn test_reassemble_rejects_repeated_chunk() {
+        let (data, manifest) = sample(&KeyPair::generate());
+        let mut chunks = chunk_data(&data, 1000);
+        chunks.push(chunks[1].clone());
+
+        assert_eq!(manifest.verify_chunks(&chunks), vec![1]);
+        assert!(matches!(
+            manifest.reassemble(chunks),
+            Err(StorageError::CorruptChunks { indices }) if indices == vec![1]
+        ));
+    }
+
+    #[test]
+    fn test_reassemble_checks_total_len() {
+        let (data, mut manifest) = sample(&KeyPair::generate());
+        manifest.total_len += 1;
+        assert!(matches!(
+            manifest.reassemble(chunk_data(&data, 1000)),
+            Err(StorageError::InvalidManifest(_))
+        ));
+    }
+
+    #[test]
+    fn test_chunk_proof() {
+        let (data, manifest) = sample(&KeyPair::generate());
+        let chunks = chunk_data(&data, 1000);
+        for (index, data) in &chunks {
+            let proof = manifest.chunk_proof(*index).unwrap();
+            assert!(MerkleTree::verify_proof(sha256(data), &proof, manifest.merkle_root));
+        }
+        assert!(manifest.chunk_proof(3).is_none());
+    }
+
+    #[test]
+    fn test_non_contiguous_indices_rejected() {
+        let chunks = vec![(0, vec![1]), (2, vec![2])];
+        let result = RepoManifest::new(&[0; 32], &chunks, &GitGoldConfig::default(), &KeyPair::generate());
+        assert!(matches!(result, Err(StorageError::InvalidChunkIndex { index: 2, total: 2 })));
+    }
+}
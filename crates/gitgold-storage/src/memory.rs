@@ -0,0 +1,658 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::{Bound, Range};
+use std::sync::{Mutex, MutexGuard};
+
+use gitgold_core::error::StorageError;
+use gitgold_core::time::unix_now;
+use gitgold_core::types::Address;
+use gitgold_crypto::hash::sha256_hex;
+use gitgold_crypto::vss::ShareCommitments;
+
+use crate::backend::{
+    check_range, ChallengeRecord, FragmentBackend, FragmentKey, FragmentMeta, FragmentRecord,
+    FragmentStatus, GcEntry, Lease, NodeChallengeStats, RepoUsage, StorageStats,
+};
+use crate::manifest::RepoManifest;
+
+/// Fragment backend that keeps everything in process memory.
+///
+/// Behaves like `FragmentStore` (content-addressed, reference-counted blobs)
+/// but persists nothing; intended for tests and simulations.
+#[derive(Debug, Default)]
+pub struct MemoryFragmentStore {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    /// Blob data and reference count, keyed by SHA-256 hex.
+    blobs: HashMap<String, (Vec<u8>, u64)>,
+    /// Fragments keyed by (repo_hash, fragment_id, share_id), so iteration
+    /// follows the `list_fragments` order.
+    fragments: BTreeMap<(String, u32, u32), Fragment>,
+    commitments: HashMap<(String, u32), ShareCommitments>,
+    manifests: HashMap<String, RepoManifest>,
+    leases: HashMap<String, Lease>,
+    gc_log: Vec<GcEntry>,
+    /// Challenge audit log keyed by challenge id.
+    challenges: HashMap<String, ChallengeRecord>,
+}
+
+#[derive(Debug, Clone)]
+struct Fragment {
+    data_hash: String,
+    stored_at: i64,
+    last_challenged: Option<i64>,
+    status: FragmentStatus,
+}
+
+impl MemoryFragmentStore {
+    /// Create an empty store.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // A panic while holding the lock cannot leave the maps half-updated
+        // in a way later calls rely on, so a poisoned lock is still usable.
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl State {
+    /// Store `data` as a blob, or take one more reference to it. Returns
+    /// the blob hash.
+    fn acquire_blob(&mut self, data: &[u8]) -> String {
+        let data_hash = sha256_hex(data);
+        self.blobs
+            .entry(data_hash.clone())
+            .and_modify(|(_, refcount)| *refcount += 1)
+            .or_insert_with(|| (data.to_vec(), 1));
+        data_hash
+    }
+
+    /// Drop one reference to a blob, removing it when none remain.
+    fn release_blob(&mut self, data_hash: &str) {
+        if let Some((_, refcount)) = self.blobs.get_mut(data_hash) {
+            *refcount -= 1;
+            if *refcount == 0 {
+                self.blobs.remove(data_hash);
+            }
+        }
+    }
+
+    /// Point a fragment at an already acquired blob, releasing the blob it
+    /// referenced before (if any).
+    fn bind_fragment(&mut self, key: (String, u32, u32), data_hash: String, stored_at: i64) {
+        let fragment = Fragment {
+            data_hash,
+            stored_at,
+            last_challenged: None,
+            status: FragmentStatus::Ok,
+        };
+        if let Some(previous) = self.fragments.insert(key, fragment) {
+            self.release_blob(&previous.data_hash);
+        }
+    }
+
+    fn record(&self, key: &(String, u32, u32), fragment: &Fragment) -> FragmentRecord {
+        FragmentRecord {
+            repo_hash: key.0.clone(),
+            fragment_id: key.1,
+            share_id: key.2,
+            data: self.blobs[&fragment.data_hash].0.clone(),
+            data_hash: fragment.data_hash.clone(),
+            stored_at: fragment.stored_at,
+            last_challenged: fragment.last_challenged,
+            status: fragment.status,
+        }
+    }
+}
+
+impl FragmentBackend for MemoryFragmentStore {
+    fn store_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        data: &[u8],
+    ) -> Result<(), StorageError> {
+        let mut state = self.state();
+        let data_hash = state.acquire_blob(data);
+        state.bind_fragment((repo_hash.to_string(), fragment_id, share_id), data_hash, unix_now());
+        Ok(())
+    }
+
+    fn link_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        data_hash: &str,
+    ) -> Result<(), StorageError> {
+        let mut state = self.state();
+        let (_, refcount) = state
+            .blobs
+            .get_mut(data_hash)
+            .ok_or_else(|| StorageError::BlobNotFound(data_hash.to_string()))?;
+        *refcount += 1;
+        state.bind_fragment(
+            (repo_hash.to_string(), fragment_id, share_id),
+            data_hash.to_string(),
+            unix_now(),
+        );
+        Ok(())
+    }
+
+    fn store_fragments_batch(
+        &self,
+        repo_hash: &str,
+        fragments: &[(u32, u32, Vec<u8>)],
+    ) -> Result<(), StorageError> {
+        let mut state = self.state();
+        let now = unix_now();
+        for (fragment_id, share_id, data) in fragments {
+            let data_hash = state.acquire_blob(data);
+            state.bind_fragment((repo_hash.to_string(), *fragment_id, *share_id), data_hash, now);
+        }
+        Ok(())
+    }
+
+    fn has_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<bool, StorageError> {
+        Ok(self
+            .state()
+            .fragments
+            .contains_key(&(repo_hash.to_string(), fragment_id, share_id)))
+    }
+
+    fn get_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<FragmentRecord, StorageError> {
+        let state = self.state();
+        let key = (repo_hash.to_string(), fragment_id, share_id);
+        let fragment = state
+            .fragments
+            .get(&key)
+            .ok_or_else(|| StorageError::FragmentNotFound {
+                repo_hash: repo_hash.to_string(),
+                fragment_id,
+            })?;
+        Ok(state.record(&key, fragment))
+    }
+
+    fn list_fragments(&self, repo_hash: &str) -> Result<Vec<FragmentRecord>, StorageError> {
+        let state = self.state();
+        let start = (repo_hash.to_string(), 0, 0);
+        Ok(state
+            .fragments
+            .range(start..)
+            .take_while(|(key, _)| key.0 == repo_hash)
+            .map(|(key, fragment)| state.record(key, fragment))
+            .collect())
+    }
+
+    fn list_fragment_meta(
+        &self,
+        repo_hash: &str,
+        after: Option<(u32, u32)>,
+        limit: usize,
+    ) -> Result<Vec<FragmentMeta>, StorageError> {
+        let state = self.state();
+        let start = match after {
+            Some((fragment_id, share_id)) => {
+                Bound::Excluded((repo_hash.to_string(), fragment_id, share_id))
+            }
+            None => Bound::Included((repo_hash.to_string(), 0, 0)),
+        };
+        Ok(state
+            .fragments
+            .range((start, Bound::Unbounded))
+            .take_while(|(key, _)| key.0 == repo_hash)
+            .take(limit)
+            .map(|((repo_hash, fragment_id, share_id), fragment)| FragmentMeta {
+                repo_hash: repo_hash.clone(),
+                fragment_id: *fragment_id,
+                share_id: *share_id,
+                data_hash: fragment.data_hash.clone(),
+                size: state.blobs[&fragment.data_hash].0.len() as u64,
+                stored_at: fragment.stored_at,
+                last_challenged: fragment.last_challenged,
+                status: fragment.status,
+            })
+            .collect())
+    }
+
+    fn read_fragment_range(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        range: Range<usize>,
+    ) -> Result<Vec<u8>, StorageError> {
+        let state = self.state();
+        let fragment = state
+            .fragments
+            .get(&(repo_hash.to_string(), fragment_id, share_id))
+            .ok_or_else(|| StorageError::FragmentNotFound {
+                repo_hash: repo_hash.to_string(),
+                fragment_id,
+            })?;
+        let data = &state.blobs[&fragment.data_hash].0;
+        check_range(&range, data.len() as u64)?;
+        Ok(data[range].to_vec())
+    }
+
+    fn delete_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<bool, StorageError> {
+        let mut state = self.state();
+        let key = (repo_hash.to_string(), fragment_id, share_id);
+        match state.fragments.remove(&key) {
+            Some(fragment) => {
+                state.release_blob(&fragment.data_hash);
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn replace_repo_fragments(
+        &self,
+        repo_hash: &str,
+        fragments: &[(u32, u32, Vec<u8>)],
+    ) -> Result<(), StorageError> {
+        let mut state = self.state();
+        let now = unix_now();
+
+        let old_keys: Vec<_> = state
+            .fragments
+            .range((repo_hash.to_string(), 0, 0)..)
+            .take_while(|(key, _)| key.0 == repo_hash)
+            .map(|(key, _)| key.clone())
+            .collect();
+        for key in old_keys {
+            if let Some(fragment) = state.fragments.remove(&key) {
+                state.release_blob(&fragment.data_hash);
+            }
+        }
+        for (fragment_id, share_id, data) in fragments {
+            let data_hash = state.acquire_blob(data);
+            state.bind_fragment((repo_hash.to_string(), *fragment_id, *share_id), data_hash, now);
+        }
+
+        Ok(())
+    }
+
+    fn scan_fragment_hashes(
+        &self,
+        after: Option<&FragmentKey>,
+        limit: usize,
+    ) -> Result<Vec<(FragmentKey, String)>, StorageError> {
+        let state = self.state();
+        let start = match after {
+            Some(k) => Bound::Excluded((k.repo_hash.clone(), k.fragment_id, k.share_id)),
+            None => Bound::Unbounded,
+        };
+        Ok(state
+            .fragments
+            .range((start, Bound::Unbounded))
+            .take(limit)
+            .map(|((repo_hash, fragment_id, share_id), fragment)| {
+                let key = FragmentKey {
+                    repo_hash: repo_hash.clone(),
+                    fragment_id: *fragment_id,
+                    share_id: *share_id,
+                };
+                (key, fragment.data_hash.clone())
+            })
+            .collect())
+    }
+
+    fn set_fragment_status(
+        &self,
+        key: &FragmentKey,
+        status: FragmentStatus,
+    ) -> Result<bool, StorageError> {
+        let mut state = self.state();
+        match state
+            .fragments
+            .get_mut(&(key.repo_hash.clone(), key.fragment_id, key.share_id))
+        {
+            Some(fragment) => {
+                fragment.status = status;
+                Ok(true)
+            }
+            None => Ok(false),
+        }
+    }
+
+    fn rewrite_blob(&self, data: &[u8]) -> Result<(), StorageError> {
+        let data_hash = sha256_hex(data);
+        let mut state = self.state();
+        let (stored, _) = state
+            .blobs
+            .get_mut(&data_hash)
+            .ok_or_else(|| StorageError::BlobNotFound(data_hash.clone()))?;
+        *stored = data.to_vec();
+        for fragment in state.fragments.values_mut() {
+            if fragment.data_hash == data_hash {
+                fragment.status = FragmentStatus::Ok;
+            }
+        }
+        Ok(())
+    }
+
+    fn blob_refcount(&self, data_hash: &str) -> Result<u64, StorageError> {
+        Ok(self
+            .state()
+            .blobs
+            .get(data_hash)
+            .map_or(0, |(_, refcount)| *refcount))
+    }
+
+    fn blob_size(&self, data_hash: &str) -> Result<Option<u64>, StorageError> {
+        Ok(self
+            .state()
+            .blobs
+            .get(data_hash)
+            .map(|(data, _)| data.len() as u64))
+    }
+
+    fn stats(&self) -> Result<StorageStats, StorageError> {
+        let state = self.state();
+        Ok(StorageStats {
+            fragments: state.fragments.len() as u64,
+            blobs: state.blobs.len() as u64,
+            logical_bytes: state
+                .fragments
+                .values()
+                .map(|f| state.blobs[&f.data_hash].0.len() as u64)
+                .sum(),
+            physical_bytes: state.blobs.values().map(|(data, _)| data.len() as u64).sum(),
+        })
+    }
+
+    fn repo_usage(&self, repo_hash: &str) -> Result<RepoUsage, StorageError> {
+        let state = self.state();
+        let mut usage = RepoUsage {
+            repo_hash: repo_hash.to_string(),
+            ..RepoUsage::default()
+        };
+        for (_, fragment) in state
+            .fragments
+            .range((repo_hash.to_string(), 0, 0)..)
+            .take_while(|(key, _)| key.0 == repo_hash)
+        {
+            usage.fragments += 1;
+            usage.bytes += state.blobs[&fragment.data_hash].0.len() as u64;
+        }
+        Ok(usage)
+    }
+
+    fn usage(&self) -> Result<Vec<RepoUsage>, StorageError> {
+        let state = self.state();
+        let mut usage: Vec<RepoUsage> = Vec::new();
+        for ((repo_hash, _, _), fragment) in &state.fragments {
+            if usage.last().is_none_or(|u| u.repo_hash != *repo_hash) {
+                usage.push(RepoUsage {
+                    repo_hash: repo_hash.clone(),
+                    ..RepoUsage::default()
+                });
+            }
+            let entry = usage.last_mut().expect("pushed above");
+            entry.fragments += 1;
+            entry.bytes += state.blobs[&fragment.data_hash].0.len() as u64;
+        }
+        Ok(usage)
+    }
+
+    fn store_commitments(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        commitments: &ShareCommitments,
+    ) -> Result<(), StorageError> {
+        self.state()
+            .commitments
+            .insert((repo_hash.to_string(), fragment_id), commitments.clone());
+        Ok(())
+    }
+
+    fn get_commitments(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+    ) -> Result<Option<ShareCommitments>, StorageError> {
+        Ok(self
+            .state()
+            .commitments
+            .get(&(repo_hash.to_string(), fragment_id))
+            .cloned())
+    }
+
+    fn store_manifest(&self, repo_hash: &str, manifest: &RepoManifest) -> Result<(), StorageError> {
+        manifest.verify_signature()?;
+        self.state()
+            .manifests
+            .insert(repo_hash.to_string(), manifest.clone());
+        Ok(())
+    }
+
+    fn get_manifest(&self, repo_hash: &str) -> Result<Option<RepoManifest>, StorageError> {
+        Ok(self.state().manifests.get(repo_hash).cloned())
+    }
+
+    fn set_lease(&self, repo_hash: &str, paid_until: i64) -> Result<(), StorageError> {
+        let lease = Lease {
+            paid_until,
+            renewed_at: unix_now(),
+        };
+        self.state().leases.insert(repo_hash.to_string(), lease);
+        Ok(())
+    }
+
+    fn get_lease(&self, repo_hash: &str) -> Result<Option<Lease>, StorageError> {
+        Ok(self.state().leases.get(repo_hash).copied())
+    }
+
+    fn purge_fragments(&self, entries: &[GcEntry]) -> Result<(), StorageError> {
+        let mut state = self.state();
+        for entry in entries {
+            let key = &entry.key;
+            let key_tuple = (key.repo_hash.clone(), key.fragment_id, key.share_id);
+            if let Some(fragment) = state.fragments.remove(&key_tuple) {
+                state.release_blob(&fragment.data_hash);
+                state.gc_log.push(entry.clone());
+            }
+        }
+        Ok(())
+    }
+
+    fn gc_log(&self, since: i64) -> Result<Vec<GcEntry>, StorageError> {
+        Ok(self
+            .state()
+            .gc_log
+            .iter()
+            .filter(|e| e.deleted_at >= since)
+            .cloned()
+            .collect())
+    }
+
+    fn record_challenge(&self, record: &ChallengeRecord) -> Result<(), StorageError> {
+        let mut state = self.state();
+        state
+            .challenges
+            .insert(record.challenge_id.clone(), record.clone());
+        let key = (record.repo_hash.clone(), record.fragment_id, record.share_id);
+        if let Some(fragment) = state.fragments.get_mut(&key) {
+            fragment.last_challenged = Some(record.challenged_at);
+        }
+        Ok(())
+    }
+
+    fn challenge_history(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+    ) -> Result<Vec<ChallengeRecord>, StorageError> {
+        let mut records: Vec<ChallengeRecord> = self
+            .state()
+            .challenges
+            .values()
+            .filter(|r| r.repo_hash == repo_hash && r.fragment_id == fragment_id)
+            .cloned()
+            .collect();
+        records.sort_by(|a, b| {
+            (a.challenged_at, &a.challenge_id).cmp(&(b.challenged_at, &b.challenge_id))
+        });
+        Ok(records)
+    }
+
+    fn node_challenge_stats(
+        &self,
+        node: &Address,
+        since: i64,
+        until: i64,
+    ) -> Result<NodeChallengeStats, StorageError> {
+        let mut stats = NodeChallengeStats::default();
+        for record in self.state().challenges.values() {
+            if record.node == *node && (since..until).contains(&record.challenged_at) {
+                stats.challenges += 1;
+                stats.successes += u64::from(record.success);
+            }
+        }
+        Ok(stats)
+    }
+
+    fn fragments_not_challenged_since(&self, since: i64) -> Result<Vec<FragmentKey>, StorageError> {
+        Ok(self
+            .state()
+            .fragments
+            .iter()
+            .filter(|(_, f)| f.last_challenged.is_none_or(|t| t < since))
+            .map(|((repo_hash, fragment_id, share_id), _)| FragmentKey {
+                repo_hash: repo_hash.clone(),
+                fragment_id: *fragment_id,
+                share_id: *share_id,
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gitgold_crypto::shamir;
+    use gitgold_crypto::share::Share;
+
+    #[test]
+    fn test_store_get_list() {
+        let store = MemoryFragmentStore::new();
+        store.store_fragment("repo1", 1, 2, b"b").unwrap();
+        store.store_fragment("repo1", 0, 1, b"a").unwrap();
+        store.store_fragment("repo2", 0, 1, b"c").unwrap();
+
+        assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().data, b"a");
+        let ids: Vec<_> = store
+            .list_fragments("repo1")
+            .unwrap()
+            .iter()
+            .map(|r| (r.fragment_id, r.share_id))
+            .collect();
+        assert_eq!(ids, [(0, 1), (1, 2)]);
+        assert!(store.has_fragment("repo2", 0, 1).unwrap());
+        let metas = store.list_fragment_meta("repo1", Some((0, 1)), 10).unwrap();
+        assert_eq!(metas.len(), 1);
+        assert_eq!((metas[0].fragment_id, metas[0].size), (1, 1));
+        store.store_fragment("repo2", 1, 1, b"abcdef").unwrap();
+        assert_eq!(store.read_fragment_range("repo2", 1, 1, 2..5).unwrap(), b"cde");
+        assert!(!store.has_fragment("repo2", 0, 2).unwrap());
+        assert!(matches!(
+            store.get_fragment("repo1", 9, 1),
+            Err(StorageError::FragmentNotFound { .. })
+        ));
+    }
+
+    #[test]
+    fn test_blobs_reference_counted() {
+        let store = MemoryFragmentStore::new();
+        let data = vec![0x42; 500];
+        store.store_fragment("upstream", 0, 1, &data).unwrap();
+        let hash = store.get_fragment("upstream", 0, 1).unwrap().data_hash;
+        store.link_fragment("fork", 0, 1, &hash).unwrap();
+
+        assert_eq!(store.blob_refcount(&hash).unwrap(), 2);
+        let stats = store.stats().unwrap();
+        assert_eq!((stats.logical_bytes, stats.physical_bytes), (1000, 500));
+
+        assert!(store.delete_fragment("upstream", 0, 1).unwrap());
+        store.store_fragment("fork", 0, 1, b"other").unwrap();
+        assert_eq!(store.blob_refcount(&hash).unwrap(), 0);
+        assert!(matches!(
+            store.link_fragment("fork", 1, 1, &hash),
+            Err(StorageError::BlobNotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_refresh_repo() {
+        let store = MemoryFragmentStore::new();
+        let secret = b"memory backend refresh".to_vec();
+        for share in shamir::split(&secret, 2, 3).unwrap() {
+            store.store_fragment("repo1", 0, share.id, &share.to_bytes()).unwrap();
+        }
+
+        assert_eq!(store.refresh_repo("repo1").unwrap(), 3);
+        let shares: Vec<Share> = store
+            .list_fragments("repo1")
+            .unwrap()
+            .iter()
+            .map(|r| Share::from_bytes(&r.data).unwrap())
+            .collect();
+        assert_eq!(shamir::reconstruct(&shares[1..]).unwrap(), secret);
+        assert_eq!(store.stats().unwrap().blobs, 3);
+    }
+
+    #[test]
+    fn test_record_challenge() {
+        let store = MemoryFragmentStore::new();
+        store.store_fragment("repo1", 0, 1, b"x").unwrap();
+        store.store_fragment("repo1", 1, 1, b"y").unwrap();
+        let record = ChallengeRecord {
+            challenge_id: "ch-001".to_string(),
+            repo_hash: "repo1".to_string(),
+            fragment_id: 0,
+            share_id: 1,
+            challenger: Address::new("challenger"),
+            node: Address::new("node"),
+            nonce: [0; 32],
+            success: true,
+            failure_reason: None,
+            response_time_ms: 150,
+            challenged_at: 1000,
+        };
+        store.record_challenge(&record).unwrap();
+
+        assert_eq!(store.get_fragment("repo1", 0, 1).unwrap().last_challenged, Some(1000));
+        assert!(store.get_fragment("repo1", 1, 1).unwrap().last_challenged.is_none());
+        assert_eq!(store.challenge_history("repo1", 0).unwrap(), vec![record]);
+        let stats = store
+            .node_challenge_stats(&Address::new("node"), 0, 2000)
+            .unwrap();
+        assert_eq!(stats.success_rate(), Some(1.0));
+        let stale = store.fragments_not_challenged_since(1000).unwrap();
+        assert_eq!(stale.len(), 1);
+        assert_eq!(stale[0].fragment_id, 1);
+    }
+}
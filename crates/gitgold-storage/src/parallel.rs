@@ -0,0 +1,112 @@
+use gitgold_core::config::GitGoldConfig;
+use gitgold_core::error::StorageError;
+use gitgold_core::types::{Hash256, ShareScheme};
+use gitgold_crypto::share::{assign_origin, Share};
+use gitgold_crypto::{scheme, shamir};
+use rayon::prelude::*;
+
+/// Split every chunk with the engine selected in `config`, spreading chunks
+/// across the rayon thread pool.
+///
+/// Prime-field Shamir chunks are additionally split block-parallel, so a
+/// single large chunk still uses every core. The result is in the same order
+/// as `chunks`, with every share tagged with `repo_hash` and its chunk index.
+pub fn split_chunks_parallel(
+    chunks: &[(u32, Vec<u8>)],
+    config: &GitGoldConfig,
+    repo_hash: &Hash256,
+) -> Result<Vec<(u32, Vec<Share>)>, StorageError> {
+    chunks
+        .par_iter()
+        .map(|(chunk_index, data)| {
+            let mut shares = match config.share_scheme {
+                ShareScheme::Shamir256 => shamir::split_parallel(data, config.k, config.n),
+                _ => scheme::split_with_config(data, config),
+            }
+            .map_err(|e| StorageError::Sharing(e.to_string()))?;
+            assign_origin(&mut shares, repo_hash, *chunk_index);
+            Ok((*chunk_index, shares))
+        })
+        .collect()
+}
+
+/// Reconstruct every chunk from its shares in parallel.
+///
+/// The result is in the same order as `chunk_shares` and can be passed to
+/// `reassemble_chunks`.
+pub fn reconstruct_chunks_parallel(
+    chunk_shares: &[(u32, Vec<Share>)],
+) -> Result<Vec<(u32, Vec<u8>)>, StorageError> {
+    chunk_shares
+        .par_iter()
+        .map(|(chunk_index, shares)| {
+            let data = match shares.first().map(|s| s.scheme) {
+                Some(ShareScheme::Shamir256) => shamir::reconstruct_parallel(shares),
+                _ => scheme::reconstruct_any(shares),
+            }
+            .map_err(|e| StorageError::Sharing(e.to_string()))?;
+            Ok((*chunk_index, data))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::{chunk_data, reassemble_chunks};
+
+    #[test]
+    fn test_parallel_roundtrip_preserves_order() {
+        let config = GitGoldConfig {
+            k: 3,
+            n: 5,
+            chunk_size: 10_000,
+            ..GitGoldConfig::default()
+        };
+        let data: Vec<u8> = (0..95_000).map(|i| (i * 17 % 256) as u8).collect();
+        let chunks = chunk_data(&data, config.chunk_size);
+
+        let split = split_chunks_parallel(&chunks, &config, &[9; 32]).unwrap();
+        assert_eq!(split.len(), chunks.len());
+        for ((idx, shares), (expected, _)) in split.iter().zip(&chunks) {
+            assert_eq!(idx, expected);
+            assert!(shares.iter().all(|s| s.chunk_index == *idx && s.repo_hash == [9; 32]));
+        }
+
+        let subsets: Vec<(u32, Vec<Share>)> = split
+            .into_iter()
+            .map(|(idx, shares)| (idx, shares[2..].to_vec()))
+            .collect();
+        let recovered = reconstruct_chunks_parallel(&subsets).unwrap();
+        assert_eq!(recovered, chunks);
+        assert_eq!(reassemble_chunks(recovered).unwrap(), data);
+    }
+
+    #[test]
+    fn test_parallel_other_engine() {
+        let config = GitGoldConfig {
+            k: 2,
+            n: 4,
+            chunk_size: 1000,
+            share_scheme: ShareScheme::ReedSolomon,
+            ..GitGoldConfig::default()
+        };
+        let chunks = chunk_data(&[0x33; 4500], config.chunk_size);
+        let split = split_chunks_parallel(&chunks, &config, &[0; 32]).unwrap();
+        assert!(split[0].1.iter().all(|s| s.scheme == ShareScheme::ReedSolomon));
+        assert_eq!(reconstruct_chunks_parallel(&split).unwrap(), chunks);
+    }
+
+    #[test]
+    fn test_parallel_error_propagates() {
+        let config = GitGoldConfig {
+            k: 1,
+            ..GitGoldConfig::default()
+        };
+        let chunks = chunk_data(&[1; 10], 5);
+        assert!(matches!(
+            split_chunks_parallel(&chunks, &config, &[0; 32]),
+            Err(StorageError::Sharing(_))
+        ));
+    }
+}
@@ -0,0 +1,786 @@
+use std::collections::{HashMap, HashSet};
+use std::ops::Range;
+
+use gitgold_core::error::StorageError;
+use gitgold_core::types::Address;
+use gitgold_crypto::hash::sha256_hex;
+use gitgold_crypto::vss::ShareCommitments;
+
+use crate::backend::{
+    ChallengeRecord, FragmentBackend, FragmentKey, FragmentMeta, FragmentRecord, FragmentStatus,
+    GcEntry, Lease, NodeChallengeStats, RepoUsage, StorageStats,
+};
+use crate::db::{FragmentStore, RepoWriteTransaction};
+use crate::manifest::RepoManifest;
+use crate::stream::MAX_FRAME_LEN;
+
+/// Byte and fragment limits. `None` means unlimited.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Quota {
+    pub max_bytes: Option<u64>,
+    pub max_fragments: Option<u64>,
+}
+
+/// Admission limits enforced by `QuotaStore`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QuotaConfig {
+    /// Limits for the whole node. Bytes are physical: a blob shared by
+    /// several fragments counts once.
+    pub node: Quota,
+    /// Default limits for each repository. Bytes are logical, as reported by
+    /// `repo_usage`.
+    pub per_repo: Quota,
+    /// Repository-specific limits that replace `per_repo`.
+    pub repo_overrides: HashMap<String, Quota>,
+    /// Largest single fragment accepted (default `MAX_FRAME_LEN`).
+    pub max_fragment_size: usize,
+}
+
+impl Default for QuotaConfig {
+    fn default() -> Self {
+        Self {
+            node: Quota::default(),
+            per_repo: Quota::default(),
+            repo_overrides: HashMap::new(),
+            max_fragment_size: MAX_FRAME_LEN,
+        }
+    }
+}
+
+/// Node capacity as reported by `QuotaStore::capacity`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capacity {
+    pub max_bytes: Option<u64>,
+    /// Physical bytes stored.
+    pub used_bytes: u64,
+    pub max_fragments: Option<u64>,
+    pub used_fragments: u64,
+}
+
+impl Capacity {
+    /// Bytes that can still be accepted, or `None` if unlimited.
+    pub fn free_bytes(&self) -> Option<u64> {
+        self.max_bytes.map(|max| max.saturating_sub(self.used_bytes))
+    }
+
+    /// Fragments that can still be accepted, or `None` if unlimited.
+    pub fn free_fragments(&self) -> Option<u64> {
+        self.max_fragments
+            .map(|max| max.saturating_sub(self.used_fragments))
+    }
+}
+
+/// A `FragmentBackend` that enforces node and per-repository quotas.
+///
+/// Every write that can grow the store (`store_fragment`, `link_fragment`,
+/// `store_fragments_batch`, `replace_repo_fragments`, and the provided
+/// maintenance methods built on them) is checked before it reaches the
+/// wrapped backend and rejected with `QuotaExceeded` or `DataTooLarge`.
+/// Overwriting an existing fragment is admitted as if its old data stayed
+/// stored, so a store at its limit rejects overwrites too;
+/// `replace_repo_fragments` accounts exactly.
+///
+/// A push streamed into a `FragmentStore` goes through
+/// `QuotaStore::begin_repo_write`, which checks each fragment as it is
+/// written. Writes made on the wrapped backend directly, through `inner()`,
+/// are not checked.
+pub struct QuotaStore<B> {
+    inner: B,
+    config: QuotaConfig,
+}
+
+impl<B: FragmentBackend> QuotaStore<B> {
+    /// Wrap `inner`, enforcing `config`.
+    pub fn new(inner: B, config: QuotaConfig) -> Self {
+        Self { inner, config }
+    }
+
+    /// The wrapped backend.
+    pub fn inner(&self) -> &B {
+        &self.inner
+    }
+
+    /// Unwrap, returning the backend.
+    pub fn into_inner(self) -> B {
+        self.inner
+    }
+
+    /// The limits in force.
+    pub fn config(&self) -> &QuotaConfig {
+        &self.config
+    }
+
+    /// Replace the limits. Data already stored is kept even if it now
+    /// exceeds them; only further writes are rejected.
+    pub fn set_config(&mut self, config: QuotaConfig) {
+        self.config = config;
+    }
+
+    /// Set the limits for one repository, replacing the `per_repo` default.
+    pub fn set_repo_quota(&mut self, repo_hash: &str, quota: Quota) {
+        self.config
+            .repo_overrides
+            .insert(repo_hash.to_string(), quota);
+    }
+
+    /// The limits that apply to a repository.
+    pub fn repo_quota(&self, repo_hash: &str) -> Quota {
+        self.config
+            .repo_overrides
+            .get(repo_hash)
+            .copied()
+            .unwrap_or(self.config.per_repo)
+    }
+
+    /// Node limits and current physical usage, for advertising free
+    /// capacity.
+    pub fn capacity(&self) -> Result<Capacity, StorageError> {
+        let stats = self.inner.stats()?;
+        Ok(Capacity {
+            max_bytes: self.config.node.max_bytes,
+            used_bytes: stats.physical_bytes,
+            max_fragments: self.config.node.max_fragments,
+            used_fragments: stats.fragments,
+        })
+    }
+
+    /// Check that writing `fragments` (as data sizes and blob hashes) to a
+    /// repository stays within every limit. With `replaces_repo` the
+    /// repository's current fragments are counted as removed.
+    fn admit(
+        &self,
+        repo_hash: &str,
+        fragments: &[(u64, String)],
+        replaces_repo: bool,
+    ) -> Result<(), StorageError> {
+        let current = self.inner.repo_usage(repo_hash)?;
+        let stats = self.inner.stats()?;
+
+        let repo_used = if replaces_repo {
+            RepoUsage::default()
+        } else {
+            current.clone()
+        };
+
+        // Only blobs not stored yet take physical space. Blobs freed by
+        // replacing the repository are not credited.
+        let mut new_blobs = HashSet::new();
+        let mut new_bytes = 0;
+        for (size, data_hash) in fragments {
+            if self.inner.blob_refcount(data_hash)? == 0 && new_blobs.insert(data_hash) {
+                new_bytes += self.inner.physical_size(*size);
+            }
+        }
+        let node_fragments = if replaces_repo {
+            stats.fragments - current.fragments
+        } else {
+            stats.fragments
+        };
+        let used = Usage {
+            repo_bytes: repo_used.bytes,
+            repo_fragments: repo_used.fragments,
+            node_bytes: stats.physical_bytes,
+            node_fragments,
+        };
+        let requested = Usage {
+            repo_bytes: fragments.iter().map(|(size, _)| size).sum(),
+            repo_fragments: fragments.len() as u64,
+            node_bytes: new_bytes,
+            node_fragments: fragments.len() as u64,
+        };
+        self.check_usage(repo_hash, &used, &requested)
+    }
+
+    /// Check `requested` on top of `used` against the repository and node
+    /// limits.
+    fn check_usage(
+        &self,
+        repo_hash: &str,
+        used: &Usage,
+        requested: &Usage,
+    ) -> Result<(), StorageError> {
+        let quota = self.repo_quota(repo_hash);
+        check(
+            &format!("repo {repo_hash} bytes"),
+            used.repo_bytes,
+            requested.repo_bytes,
+            quota.max_bytes,
+        )?;
+        check(
+            &format!("repo {repo_hash} fragments"),
+            used.repo_fragments,
+            requested.repo_fragments,
+            quota.max_fragments,
+        )?;
+        check("node bytes", used.node_bytes, requested.node_bytes, self.config.node.max_bytes)?;
+        check(
+            "node fragments",
+            used.node_fragments,
+            requested.node_fragments,
+            self.config.node.max_fragments,
+        )
+    }
+
+    fn check_size(&self, data: &[u8]) -> Result<(), StorageError> {
+        if data.len() > self.config.max_fragment_size {
+            return Err(StorageError::DataTooLarge {
+                size: data.len(),
+                max: self.config.max_fragment_size,
+            });
+        }
+        Ok(())
+    }
+}
+
+impl QuotaStore<FragmentStore> {
+    /// Start writing fragments of one repository in a single transaction,
+    /// checking each fragment against the quotas as it is stored.
+    ///
+    /// See `FragmentStore::begin_repo_write`. Usage is read once when the
+    /// transaction starts and kept as a running total of its writes, which
+    /// are admitted like `store_fragment`: an overwritten fragment counts
+    /// as if its old data stayed stored.
+    pub fn begin_repo_write(&self, repo_hash: &str) -> Result<QuotaRepoWrite<'_>, StorageError> {
+        let tx = self.inner.begin_repo_write(repo_hash)?;
+        let repo = self.inner.repo_usage(repo_hash)?;
+        let stats = self.inner.stats()?;
+        Ok(QuotaRepoWrite {
+            quotas: self,
+            tx,
+            used: Usage {
+                repo_bytes: repo.bytes,
+                repo_fragments: repo.fragments,
+                node_bytes: stats.physical_bytes,
+                node_fragments: stats.fragments,
+            },
+        })
+    }
+}
+
+/// A `RepoWriteTransaction` whose writes are checked against the quotas of
+/// a `QuotaStore`. Created by `QuotaStore::begin_repo_write`.
+///
+/// A fragment rejected with `QuotaExceeded` or `DataTooLarge` is not
+/// written; the transaction stays open, so the caller decides whether to
+/// commit what was written so far or roll back.
+pub struct QuotaRepoWrite<'a> {
+    quotas: &'a QuotaStore<FragmentStore>,
+    tx: RepoWriteTransaction<'a>,
+    /// Usage including every write admitted so far.
+    used: Usage,
+}
+
+impl QuotaRepoWrite<'_> {
+    /// The repository being written.
+    pub fn repo_hash(&self) -> &str {
+        self.tx.repo_hash()
+    }
+
+    /// Store a fragment if it fits within every quota, replacing any
+    /// existing fragment with the same key.
+    pub fn store_fragment(
+        &mut self,
+        fragment_id: u32,
+        share_id: u32,
+        data: &[u8],
+    ) -> Result<(), StorageError> {
+        self.quotas.check_size(data)?;
+        let inner = &self.quotas.inner;
+        // Blob refcounts include this transaction's writes, so a blob is
+        // only counted the first time it is stored
+        let node_bytes = if inner.blob_refcount(&sha256_hex(data))? == 0 {
+            inner.physical_size(data.len() as u64)
+        } else {
+            0
+        };
+        let requested = Usage {
+            repo_bytes: data.len() as u64,
+            repo_fragments: 1,
+            node_bytes,
+            node_fragments: 1,
+        };
+        self.quotas
+            .check_usage(self.tx.repo_hash(), &self.used, &requested)?;
+
+        self.tx.store_fragment(fragment_id, share_id, data)?;
+        self.used.repo_bytes += requested.repo_bytes;
+        self.used.repo_fragments += 1;
+        self.used.node_bytes += requested.node_bytes;
+        self.used.node_fragments += 1;
+        Ok(())
+    }
+
+    /// Whether the repository holds a fragment, counting ones stored earlier
+    /// in this transaction.
+    pub fn has_fragment(&self, fragment_id: u32, share_id: u32) -> Result<bool, StorageError> {
+        self.tx.has_fragment(fragment_id, share_id)
+    }
+
+    /// Make every write durable. Returns the number of fragments stored.
+    pub fn commit(self) -> Result<usize, StorageError> {
+        self.tx.commit()
+    }
+
+    /// Discard every write. Equivalent to dropping the transaction.
+    pub fn rollback(self) -> Result<(), StorageError> {
+        self.tx.rollback()
+    }
+}
+
+/// Repository and node usage, or an amount requested on top of it.
+#[derive(Debug, Clone, Copy, Default)]
+struct Usage {
+    repo_bytes: u64,
+    repo_fragments: u64,
+    /// Physical bytes.
+    node_bytes: u64,
+    node_fragments: u64,
+}
+
+fn check(scope: &str, used: u64, requested: u64, limit: Option<u64>) -> Result<(), StorageError> {
+    match limit {
+        Some(limit) if used + requested > limit => Err(StorageError::QuotaExceeded {
+            scope: scope.to_string(),
+            used,
+            requested,
+            limit,
+        }),
+        _ => Ok(()),
+    }
+}
+
+impl<B: FragmentBackend> FragmentBackend for QuotaStore<B> {
+    fn store_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        data: &[u8],
+    ) -> Result<(), StorageError> {
+        self.check_size(data)?;
+        self.admit(repo_hash, &[(data.len() as u64, sha256_hex(data))], false)?;
+        self.inner
+            .store_fragment(repo_hash, fragment_id, share_id, data)
+    }
+
+    fn link_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        data_hash: &str,
+    ) -> Result<(), StorageError> {
+        let size = self
+            .inner
+            .blob_size(data_hash)?
+            .ok_or_else(|| StorageError::BlobNotFound(data_hash.to_string()))?;
+        self.admit(repo_hash, &[(size, data_hash.to_string())], false)?;
+        self.inner
+            .link_fragment(repo_hash, fragment_id, share_id, data_hash)
+    }
+
+    fn store_fragments_batch(
+        &self,
+        repo_hash: &str,
+        fragments: &[(u32, u32, Vec<u8>)],
+    ) -> Result<(), StorageError> {
+        let mut sized = Vec::with_capacity(fragments.len());
+        for (_, _, data) in fragments {
+            self.check_size(data)?;
+            sized.push((data.len() as u64, sha256_hex(data)));
+        }
+        self.admit(repo_hash, &sized, false)?;
+        self.inner.store_fragments_batch(repo_hash, fragments)
+    }
+
+    fn has_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<bool, StorageError> {
+        self.inner.has_fragment(repo_hash, fragment_id, share_id)
+    }
+
+    fn get_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<FragmentRecord, StorageError> {
+        self.inner.get_fragment(repo_hash, fragment_id, share_id)
+    }
+
+    fn list_fragments(&self, repo_hash: &str) -> Result<Vec<FragmentRecord>, StorageError> {
+        self.inner.list_fragments(repo_hash)
+    }
+
+    fn list_fragment_meta(
+        &self,
+        repo_hash: &str,
+        after: Option<(u32, u32)>,
+        limit: usize,
+    ) -> Result<Vec<FragmentMeta>, StorageError> {
+        self.inner.list_fragment_meta(repo_hash, after, limit)
+    }
+
+    fn read_fragment_range(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+        range: Range<usize>,
+    ) -> Result<Vec<u8>, StorageError> {
+        self.inner
+            .read_fragment_range(repo_hash, fragment_id, share_id, range)
+    }
+
+    fn delete_fragment(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        share_id: u32,
+    ) -> Result<bool, StorageError> {
+        self.inner.delete_fragment(repo_hash, fragment_id, share_id)
+    }
+
+    fn replace_repo_fragments(
+        &self,
+        repo_hash: &str,
+        fragments: &[(u32, u32, Vec<u8>)],
+    ) -> Result<(), StorageError> {
+        let mut sized = Vec::with_capacity(fragments.len());
+        for (_, _, data) in fragments {
+            self.check_size(data)?;
+            sized.push((data.len() as u64, sha256_hex(data)));
+        }
+        self.admit(repo_hash, &sized, true)?;
+        self.inner.replace_repo_fragments(repo_hash, fragments)
+    }
+
+    fn scan_fragment_hashes(
+        &self,
+        after: Option<&FragmentKey>,
+        limit: usize,
+    ) -> Result<Vec<(FragmentKey, String)>, StorageError> {
+        self.inner.scan_fragment_hashes(after, limit)
+    }
+
+    fn set_fragment_status(
+        &self,
+        key: &FragmentKey,
+        status: FragmentStatus,
+    ) -> Result<bool, StorageError> {
+        self.inner.set_fragment_status(key, status)
+    }
+
+    fn rewrite_blob(&self, data: &[u8]) -> Result<(), StorageError> {
+        self.inner.rewrite_blob(data)
+    }
+
+    fn blob_refcount(&self, data_hash: &str) -> Result<u64, StorageError> {
+        self.inner.blob_refcount(data_hash)
+    }
+
+    fn blob_size(&self, data_hash: &str) -> Result<Option<u64>, StorageError> {
+        self.inner.blob_size(data_hash)
+    }
+
+    fn physical_size(&self, size: u64) -> u64 {
+        self.inner.physical_size(size)
+    }
+
+    fn stats(&self) -> Result<StorageStats, StorageError> {
+        self.inner.stats()
+    }
+
+    fn repo_usage(&self, repo_hash: &str) -> Result<RepoUsage, StorageError> {
+        self.inner.repo_usage(repo_hash)
+    }
+
+    fn usage(&self) -> Result<Vec<RepoUsage>, StorageError> {
+        self.inner.usage()
+    }
+
+    fn store_commitments(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+        commitments: &ShareCommitments,
+    ) -> Result<(), StorageError> {
+        self.inner
+            .store_commitments(repo_hash, fragment_id, commitments)
+    }
+
+    fn get_commitments(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+    ) -> Result<Option<ShareCommitments>, StorageError> {
+        self.inner.get_commitments(repo_hash, fragment_id)
+    }
+
+    fn store_manifest(&self, repo_hash: &str, manifest: &RepoManifest) -> Result<(), StorageError> {
+        self.inner.store_manifest(repo_hash, manifest)
+    }
+
+    fn get_manifest(&self, repo_hash: &str) -> Result<Option<RepoManifest>, StorageError> {
+        self.inner.get_manifest(repo_hash)
+    }
+
+    fn set_lease(&self, repo_hash: &str, paid_until: i64) -> Result<(), StorageError> {
+        self.inner.set_lease(repo_hash, paid_until)
+    }
+
+    fn get_lease(&self, repo_hash: &str) -> Result<Option<Lease>, StorageError> {
+        self.inner.get_lease(repo_hash)
+    }
+
+    fn purge_fragments(&self, entries: &[GcEntry]) -> Result<(), StorageError> {
+        self.inner.purge_fragments(entries)
+    }
+
+    fn gc_log(&self, since: i64) -> Result<Vec<GcEntry>, StorageError> {
+        self.inner.gc_log(since)
+    }
+
+    fn record_challenge(&self, record: &ChallengeRecord) -> Result<(), StorageError> {
+        self.inner.record_challenge(record)
+    }
+
+    fn challenge_history(
+        &self,
+        repo_hash: &str,
+        fragment_id: u32,
+    ) -> Result<Vec<ChallengeRecord>, StorageError> {
+        self.inner.challenge_history(repo_hash, fragment_id)
+    }
+
+    fn node_challenge_stats(
+        &self,
+        node: &Address,
+        since: i64,
+        until: i64,
+    ) -> Result<NodeChallengeStats, StorageError> {
+        self.inner.node_challenge_stats(node, since, until)
+    }
+
+    fn fragments_not_challenged_since(&self, since: i64) -> Result<Vec<FragmentKey>, StorageError> {
+        self.inner.fragments_not_challenged_since(since)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::FragmentStore;
+    use crate::encryption::{self, EncryptionKey};
+    use gitgold_crypto::shamir;
+
+    fn quota_store(config: QuotaConfig) -> QuotaStore<FragmentStore> {
+        QuotaStore::new(FragmentStore::in_memory().unwrap(), config)
+    }
+
+    #[test]
+    fn test_unlimited_by_default() {
+        let store = quota_store(QuotaConfig::default());
+        store.store_fragment("repo1", 0, 1, &[1; 10_000]).unwrap();
+
+        let capacity = store.capacity().unwrap();
+        assert_eq!(capacity.used_bytes, 10_000);
+        assert_eq!(capacity.used_fragments, 1);
+        assert_eq!(capacity.free_bytes(), None);
+    }
+
+    #[test]
+    fn test_fragment_too_large() {
+        let store = quota_store(QuotaConfig {
+            max_fragment_size: 100,
+            ..QuotaConfig::default()
+        });
+        store.store_fragment("repo1", 0, 1, &[0; 100]).unwrap();
+        assert!(matches!(
+            store.store_fragment("repo1", 0, 2, &[0; 101]),
+            Err(StorageError::DataTooLarge { size: 101, max: 100 })
+        ));
+    }
+
+    #[test]
+    fn test_node_quota() {
+        let store = quota_store(QuotaConfig {
+            node: Quota {
+                max_bytes: Some(1000),
+                max_fragments: Some(3),
+            },
+            ..QuotaConfig::default()
+        });
+        store.store_fragment("repo1", 0, 1, &[1; 600]).unwrap();
+        match store.store_fragment("repo2", 0, 1, &[2; 500]) {
+            Err(StorageError::QuotaExceeded {
+                scope,
+                used,
+                requested,
+                limit,
+            }) => {
+                assert_eq!(scope, "node bytes");
+                assert_eq!((used, requested, limit), (600, 500, 1000));
+            }
+            other => panic!("expected node quota error, got {other:?}"),
+        }
+        assert!(store.get_fragment("repo2", 0, 1).is_err());
+
+        // Identical data takes no further physical space
+        store.store_fragment("repo2", 0, 1, &[1; 600]).unwrap();
+        store.store_fragment("repo2", 0, 2, &[3; 400]).unwrap();
+        assert_eq!(store.capacity().unwrap().free_bytes(), Some(0));
+        assert_eq!(store.capacity().unwrap().free_fragments(), Some(0));
+        assert!(matches!(
+            store.store_fragment("repo3", 0, 1, &[1; 600]),
+            Err(StorageError::QuotaExceeded { .. })
+        ));
+    }
+
+    #[test]
+    fn test_node_quota_counts_encryption_overhead() {
+        let mut inner = FragmentStore::in_memory().unwrap();
+        inner.set_encryption_key(Some(EncryptionKey::generate()));
+        let store = QuotaStore::new(
+            inner,
+            QuotaConfig {
+                node: Quota {
+                    max_bytes: Some(1000),
+                    max_fragments: None,
+                },
+                ..QuotaConfig::default()
+            },
+        );
+        let sealed = encryption::sealed_len(500);
+        store.store_fragment("repo1", 0, 1, &[1; 500]).unwrap();
+        assert_eq!(store.capacity().unwrap().used_bytes, sealed);
+        match store.store_fragment("repo1", 1, 1, &[2; 500]) {
+            Err(StorageError::QuotaExceeded { used, requested, .. }) => {
+                assert_eq!((used, requested), (sealed, sealed));
+            }
+            other => panic!("expected node quota error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn test_batch_admitted_as_a_whole() {
+        let store = quota_store(QuotaConfig {
+            per_repo: Quota {
+                max_bytes: None,
+                max_fragments: Some(3),
+            },
+            ..QuotaConfig::default()
+        });
+        let batch: Vec<_> = (0..4).map(|fid| (fid, 1, vec![fid as u8; 10])).collect();
+
+        assert!(matches!(
+            store.store_fragments_batch("repo1", &batch),
+            Err(StorageError::QuotaExceeded { .. })
+        ));
+        assert!(!store.has_fragment("repo1", 0, 1).unwrap());
+        store.store_fragments_batch("repo1", &batch[..3]).unwrap();
+        assert_eq!(store.repo_usage("repo1").unwrap().fragments, 3);
+    }
+
+    #[test]
+    fn test_repo_write_checks_quotas() {
+        let store = quota_store(QuotaConfig {
+            node: Quota {
+                max_bytes: Some(1000),
+                max_fragments: None,
+            },
+            per_repo: Quota {
+                max_bytes: None,
+                max_fragments: Some(4),
+            },
+            ..QuotaConfig::default()
+        });
+        store.store_fragment("repo1", 0, 1, &[1; 400]).unwrap();
+
+        let mut tx = store.begin_repo_write("repo1").unwrap();
+        tx.store_fragment(1, 1, &[2; 500]).unwrap();
+        // Node bytes count the fragment written earlier in the transaction
+        match tx.store_fragment(2, 1, &[3; 200]) {
+            Err(StorageError::QuotaExceeded { scope, used, requested, .. }) => {
+                assert_eq!(scope, "node bytes");
+                assert_eq!((used, requested), (900, 200));
+            }
+            other => panic!("expected node quota error, got {other:?}"),
+        }
+        assert!(!tx.has_fragment(2, 1).unwrap());
+        // A blob already stored in the transaction takes no further space
+        tx.store_fragment(2, 1, &[2; 500]).unwrap();
+        tx.store_fragment(3, 1, &[1; 400]).unwrap();
+        assert!(matches!(
+            tx.store_fragment(4, 1, &[1; 400]),
+            Err(StorageError::QuotaExceeded { scope, .. }) if scope == "repo repo1 fragments"
+        ));
+        assert_eq!(tx.commit().unwrap(), 3);
+        assert_eq!(store.repo_usage("repo1").unwrap().fragments, 4);
+        assert_eq!(store.capacity().unwrap().used_bytes, 900);
+    }
+
+    #[test]
+    fn test_repo_quota_and_override() {
+        let mut store = quota_store(QuotaConfig {
+            per_repo: Quota {
+                max_bytes: Some(100),
+                max_fragments: None,
+            },
+            ..QuotaConfig::default()
+        });
+        store.store_fragment("small", 0, 1, &[0; 80]).unwrap();
+        assert!(matches!(
+            store.store_fragment("small", 0, 2, &[1; 30]),
+            Err(StorageError::QuotaExceeded { .. })
+        ));
+
+        store.set_repo_quota("big", Quota { max_bytes: Some(1000), max_fragments: Some(2) });
+        store.store_fragment("big", 0, 1, &[2; 500]).unwrap();
+        store.store_fragment("big", 0, 2, &[3; 500]).unwrap();
+        assert!(matches!(
+            store.store_fragment("big", 0, 3, &[4; 1]),
+            Err(StorageError::QuotaExceeded { .. })
+        ));
+
+        // link_fragment counts the linked blob against the repository
+        let hash = store.get_fragment("big", 0, 1).unwrap().data_hash;
+        assert!(matches!(
+            store.link_fragment("small", 1, 1, &hash),
+            Err(StorageError::QuotaExceeded { .. })
+        ));
+
+        let usage = store.usage().unwrap();
+        assert_eq!(usage.len(), 2);
+        assert_eq!((usage[0].repo_hash.as_str(), usage[0].bytes), ("big", 1000));
+        assert_eq!((usage[1].repo_hash.as_str(), usage[1].fragments), ("small", 1));
+    }
+
+    #[test]
+    fn test_refresh_at_repo_limit() {
+        let secret = vec![0x33; 200];
+        let shares = shamir::split(&secret, 2, 3).unwrap();
+        let size = shares[0].to_bytes().len() as u64;
+        let store = quota_store(QuotaConfig {
+            per_repo: Quota {
+                max_bytes: Some(3 * size),
+                max_fragments: Some(3),
+            },
+            ..QuotaConfig::default()
+        });
+        for share in &shares {
+            store.store_fragment("repo1", 0, share.id, &share.to_bytes()).unwrap();
+        }
+
+        // Replacing the repository's fragments only counts the new set
+        assert_eq!(store.refresh_repo("repo1").unwrap(), 3);
+        assert!(matches!(
+            store.reshare_repo("repo1", 2, 4),
+            Err(StorageError::QuotaExceeded { .. })
+        ));
+        assert_eq!(store.repo_usage("repo1").unwrap().fragments, 3);
+    }
+}
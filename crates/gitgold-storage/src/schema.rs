@@ -1,37 +1,164 @@
+use gitgold_core::error::MigrationError;
+use gitgold_db::migrate::{migrate, Migration};
 use rusqlite::Connection;
 
-/// Initialize the storage database schema.
-pub fn init_schema(conn: &Connection) -> Result<(), rusqlite::Error> {
-    conn.execute_batch(
-        "
-        CREATE TABLE IF NOT EXISTS fragments (
-            repo_hash   TEXT NOT NULL,
-            fragment_id INTEGER NOT NULL,
-            share_id    INTEGER NOT NULL,
-            data        BLOB NOT NULL,
-            data_hash   TEXT NOT NULL,
-            stored_at   INTEGER NOT NULL,
-            last_challenged INTEGER,
-            PRIMARY KEY (repo_hash, fragment_id, share_id)
-        );
+/// Storage database schema history, applied in order by `init_schema`.
+pub const STORAGE_MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "fragments and challenges",
+        sql: "
+            CREATE TABLE IF NOT EXISTS fragments (
+                repo_hash   TEXT NOT NULL,
+                fragment_id INTEGER NOT NULL,
+                share_id    INTEGER NOT NULL,
+                data        BLOB NOT NULL,
+                data_hash   TEXT NOT NULL,
+                stored_at   INTEGER NOT NULL,
+                last_challenged INTEGER,
+                PRIMARY KEY (repo_hash, fragment_id, share_id)
+            );
 
-        CREATE TABLE IF NOT EXISTS challenges (
-            challenge_id    TEXT PRIMARY KEY,
-            repo_hash       TEXT NOT NULL,
-            fragment_id     INTEGER NOT NULL,
-            success         INTEGER NOT NULL,
-            response_time   INTEGER NOT NULL,
-            challenged_at   INTEGER NOT NULL
-        );
+            CREATE TABLE IF NOT EXISTS challenges (
+                challenge_id    TEXT PRIMARY KEY,
+                repo_hash       TEXT NOT NULL,
+                fragment_id     INTEGER NOT NULL,
+                success         INTEGER NOT NULL,
+                response_time   INTEGER NOT NULL,
+                challenged_at   INTEGER NOT NULL
+            );
 
-        CREATE INDEX IF NOT EXISTS idx_fragments_repo
-            ON fragments (repo_hash);
+            CREATE INDEX IF NOT EXISTS idx_fragments_repo
+                ON fragments (repo_hash);
 
-        CREATE INDEX IF NOT EXISTS idx_challenges_repo_fragment
-            ON challenges (repo_hash, fragment_id);
+            CREATE INDEX IF NOT EXISTS idx_challenges_repo_fragment
+                ON challenges (repo_hash, fragment_id);
+        ",
+    },
+    Migration {
+        version: 2,
+        description: "VSS share commitments",
+        sql: "
+            CREATE TABLE share_commitments (
+                repo_hash   TEXT NOT NULL,
+                fragment_id INTEGER NOT NULL,
+                commitments BLOB NOT NULL,
+                PRIMARY KEY (repo_hash, fragment_id)
+            );
         ",
-    )?;
-    Ok(())
+    },
+    Migration {
+        version: 3,
+        description: "signed repository manifests",
+        sql: "
+            CREATE TABLE repo_manifests (
+                repo_hash   TEXT PRIMARY KEY,
+                manifest    BLOB NOT NULL
+            );
+        ",
+    },
+    Migration {
+        version: 4,
+        description: "content-addressed blobs",
+        // Moves fragment data into reference-counted blobs keyed by the
+        // existing data_hash. Blob data is NULL when kept on the filesystem.
+        sql: "
+            CREATE TABLE blobs (
+                hash        TEXT PRIMARY KEY,
+                data        BLOB,
+                size        INTEGER NOT NULL,
+                refcount    INTEGER NOT NULL
+            );
+
+            INSERT INTO blobs (hash, data, size, refcount)
+                SELECT data_hash, data, length(data), COUNT(*)
+                FROM fragments GROUP BY data_hash;
+
+            CREATE TABLE fragments_v4 (
+                repo_hash   TEXT NOT NULL,
+                fragment_id INTEGER NOT NULL,
+                share_id    INTEGER NOT NULL,
+                data_hash   TEXT NOT NULL REFERENCES blobs (hash),
+                stored_at   INTEGER NOT NULL,
+                last_challenged INTEGER,
+                PRIMARY KEY (repo_hash, fragment_id, share_id)
+            );
+
+            INSERT INTO fragments_v4
+                SELECT repo_hash, fragment_id, share_id, data_hash, stored_at, last_challenged
+                FROM fragments;
+
+            DROP TABLE fragments;
+            ALTER TABLE fragments_v4 RENAME TO fragments;
+
+            CREATE INDEX idx_fragments_repo
+                ON fragments (repo_hash);
+
+            CREATE INDEX idx_fragments_blob
+                ON fragments (data_hash);
+        ",
+    },
+    Migration {
+        version: 5,
+        description: "fragment scrub status",
+        sql: "
+            ALTER TABLE fragments ADD COLUMN status TEXT NOT NULL DEFAULT 'ok';
+        ",
+    },
+    Migration {
+        version: 6,
+        description: "storage leases and garbage collection log",
+        sql: "
+            CREATE TABLE leases (
+                repo_hash   TEXT PRIMARY KEY,
+                paid_until  INTEGER NOT NULL,
+                renewed_at  INTEGER NOT NULL
+            );
+
+            CREATE TABLE gc_log (
+                id          INTEGER PRIMARY KEY AUTOINCREMENT,
+                repo_hash   TEXT NOT NULL,
+                fragment_id INTEGER NOT NULL,
+                share_id    INTEGER NOT NULL,
+                data_hash   TEXT NOT NULL,
+                reason      TEXT NOT NULL,
+                deleted_at  INTEGER NOT NULL
+            );
+        ",
+    },
+    Migration {
+        version: 7,
+        description: "challenge audit log",
+        // Entries recorded before this version get share_id 0, empty
+        // addresses and an all-zero nonce.
+        sql: "
+            ALTER TABLE challenges ADD COLUMN share_id INTEGER NOT NULL DEFAULT 0;
+            ALTER TABLE challenges ADD COLUMN challenger TEXT NOT NULL DEFAULT '';
+            ALTER TABLE challenges ADD COLUMN node TEXT NOT NULL DEFAULT '';
+            ALTER TABLE challenges ADD COLUMN nonce BLOB NOT NULL DEFAULT X'0000000000000000000000000000000000000000000000000000000000000000';
+            ALTER TABLE challenges ADD COLUMN failure_reason TEXT;
+
+            CREATE INDEX idx_challenges_node
+                ON challenges (node, challenged_at);
+
+            CREATE INDEX idx_fragments_last_challenged
+                ON fragments (last_challenged);
+        ",
+    },
+    Migration {
+        version: 8,
+        description: "blob encryption at rest",
+        // Id of the key a blob is sealed with; NULL for plaintext blobs
+        sql: "
+            ALTER TABLE blobs ADD COLUMN key_id TEXT;
+        ",
+    },
+];
+
+/// Initialize the storage database schema, migrating an existing database
+/// forward. Returns the schema version.
+pub fn init_schema(conn: &Connection) -> Result<u32, MigrationError> {
+    migrate(conn, STORAGE_MIGRATIONS)
 }
 
 #[cfg(test)]
@@ -60,4 +187,66 @@ mod tests {
         init_schema(&conn).unwrap();
         init_schema(&conn).unwrap(); // Should not error
     }
+
+    #[test]
+    fn test_migrations_numbered() {
+        gitgold_db::migrate::check_numbering(STORAGE_MIGRATIONS).unwrap();
+    }
+
+    #[test]
+    fn test_upgrade_v1_fixture() {
+        use crate::backend::{FragmentBackend, FragmentStatus};
+        use crate::db::FragmentStore;
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("fragments.sqlite");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(include_str!("../test-fixtures/storage_v1.sql"))
+            .unwrap();
+        drop(conn);
+
+        let store = FragmentStore::open(path.to_str().unwrap()).unwrap();
+        let conn = Connection::open(&path).unwrap();
+        assert_eq!(
+            gitgold_db::migrate::schema_version(&conn).unwrap(),
+            STORAGE_MIGRATIONS.len() as u32
+        );
+
+        let shared = store.get_fragment("upstream", 0, 1).unwrap();
+        assert_eq!(shared.data, b"shared share data");
+        assert_eq!(shared.last_challenged, Some(1700000500));
+        assert_eq!(shared.status, FragmentStatus::Ok);
+        assert_eq!(store.get_fragment("fork", 0, 1).unwrap().data, shared.data);
+        assert_eq!(store.blob_refcount(&shared.data_hash).unwrap(), 2);
+
+        let stats = store.stats().unwrap();
+        assert_eq!((stats.fragments, stats.blobs), (3, 2));
+        let history = store.challenge_history("upstream", 0).unwrap();
+        assert_eq!(history.len(), 1);
+        assert_eq!(history[0].share_id, 0);
+        assert_eq!(history[0].nonce, [0; 32]);
+        assert_eq!(history[0].challenged_at, 1700000500);
+
+        // The upgraded store keeps working
+        assert!(store.delete_fragment("upstream", 0, 1).unwrap());
+        store.store_fragment("fork", 1, 1, b"new").unwrap();
+        assert_eq!(store.blob_refcount(&shared.data_hash).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_refuses_newer_schema() {
+        let conn = Connection::open_in_memory().unwrap();
+        init_schema(&conn).unwrap();
+        conn.execute(
+            "INSERT INTO schema_version (version, description, applied_at)
+             VALUES (?1, 'future', 0)",
+            [STORAGE_MIGRATIONS.len() as u32 + 1],
+        )
+        .unwrap();
+
+        assert!(matches!(
+            init_schema(&conn),
+            Err(MigrationError::TooNew { .. })
+        ));
+    }
 }
@@ -0,0 +1,312 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use gitgold_core::error::StorageError;
+use gitgold_crypto::hash::sha256_hex;
+
+use crate::backend::{FragmentBackend, FragmentKey, FragmentStatus};
+
+/// Limits for one `scrub` run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ScrubOptions {
+    /// Fragment keys fetched per index query.
+    pub batch_size: usize,
+    /// Stop after checking this many fragments; `None` scrubs to the end of
+    /// the store.
+    pub max_fragments: Option<usize>,
+    /// Throttle reads to this many bytes per second; `None` is unthrottled.
+    pub max_bytes_per_sec: Option<u64>,
+}
+
+impl Default for ScrubOptions {
+    fn default() -> Self {
+        Self {
+            batch_size: 256,
+            max_fragments: None,
+            max_bytes_per_sec: None,
+        }
+    }
+}
+
+/// Outcome of a `scrub` run.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ScrubReport {
+    /// Fragments checked.
+    pub checked: u64,
+    /// Fragment bytes read and hashed.
+    pub bytes: u64,
+    /// Fragments found unreadable or not matching their `data_hash`, and
+    /// left marked `FragmentStatus::Corrupt`.
+    pub corrupt: Vec<FragmentKey>,
+    /// Corrupt fragments whose blob was restored from a fetched copy.
+    pub repaired: Vec<FragmentKey>,
+    /// Fragments sealed with a key the store does not hold, which cannot be
+    /// checked; their status is left unchanged.
+    pub skipped: Vec<FragmentKey>,
+    /// Where the next run should resume, or `None` once the whole store has
+    /// been checked.
+    pub resume_from: Option<FragmentKey>,
+}
+
+impl ScrubReport {
+    /// Whether this run reached the end of the store.
+    pub fn is_complete(&self) -> bool {
+        self.resume_from.is_none()
+    }
+}
+
+/// Verify stored fragments against their `data_hash`.
+///
+/// Walks fragments in `FragmentKey` order starting just after `from` (or at
+/// the beginning), recomputes the SHA-256 of each one and records the result
+/// with `set_fragment_status`. A run stops at the end of the store or after
+/// `options.max_fragments`; pass the report's `resume_from` to the next run
+/// to continue where it stopped.
+pub fn scrub<B: FragmentBackend + ?Sized>(
+    backend: &B,
+    from: Option<&FragmentKey>,
+    options: &ScrubOptions,
+) -> Result<ScrubReport, StorageError> {
+    scrub_with_repair(backend, from, options, |_, _| None)
+}
+
+/// Like `scrub`, but asks `fetch` for a replacement copy of every corrupt
+/// fragment, given its key and expected `data_hash`.
+///
+/// A fetched copy that hashes to `data_hash` is written over the damaged
+/// blob with `rewrite_blob`, which also clears the corrupt status of every
+/// fragment sharing that blob. Copies that do not match are discarded and
+/// the fragment stays corrupt.
+pub fn scrub_with_repair<B, F>(
+    backend: &B,
+    from: Option<&FragmentKey>,
+    options: &ScrubOptions,
+    mut fetch: F,
+) -> Result<ScrubReport, StorageError>
+where
+    B: FragmentBackend + ?Sized,
+    F: FnMut(&FragmentKey, &str) -> Option<Vec<u8>>,
+{
+    let mut report = ScrubReport::default();
+    let mut cursor = from.cloned();
+    let mut remaining = options.max_fragments.unwrap_or(usize::MAX);
+    let started = Instant::now();
+
+    while remaining > 0 {
+        let batch =
+            backend.scan_fragment_hashes(cursor.as_ref(), options.batch_size.clamp(1, remaining))?;
+        if batch.is_empty() {
+            return Ok(report);
+        }
+
+        for (key, data_hash) in batch {
+            let Some(mut status) = check_fragment(backend, &key, &mut report.bytes)? else {
+                report.skipped.push(key.clone());
+                cursor = Some(key);
+                remaining -= 1;
+                continue;
+            };
+            report.checked += 1;
+
+            if status == FragmentStatus::Corrupt {
+                if let Some(copy) = fetch(&key, &data_hash).filter(|c| sha256_hex(c) == data_hash) {
+                    backend.rewrite_blob(&copy)?;
+                    report.repaired.push(key.clone());
+                    status = FragmentStatus::Ok;
+                } else {
+                    report.corrupt.push(key.clone());
+                }
+            }
+            backend.set_fragment_status(&key, status)?;
+
+            if let Some(rate) = options.max_bytes_per_sec.filter(|r| *r > 0) {
+                let due = Duration::from_secs_f64(report.bytes as f64 / rate as f64);
+                if let Some(wait) = due.checked_sub(started.elapsed()) {
+                    thread::sleep(wait);
+                }
+            }
+            cursor = Some(key);
+            remaining -= 1;
+        }
+    }
+
+    // Stopped at max_fragments: resume after the last fragment checked,
+    // unless that was the last one in the store.
+    if !backend.scan_fragment_hashes(cursor.as_ref(), 1)?.is_empty() {
+        report.resume_from = cursor;
+    }
+    Ok(report)
+}
+
+/// Read one fragment and compare its data with its `data_hash`.
+///
+/// Unreadable data (a missing blob file, say, or a sealed blob that fails
+/// authentication) counts as corruption; a fragment deleted since it was
+/// scanned counts as `Ok`. A blob sealed with a key the store does not hold
+/// cannot be checked and gives `None`.
+fn check_fragment<B: FragmentBackend + ?Sized>(
+    backend: &B,
+    key: &FragmentKey,
+    bytes: &mut u64,
+) -> Result<Option<FragmentStatus>, StorageError> {
+    match backend.get_fragment(&key.repo_hash, key.fragment_id, key.share_id) {
+        Ok(record) => {
+            *bytes += record.data.len() as u64;
+            if sha256_hex(&record.data) == record.data_hash {
+                Ok(Some(FragmentStatus::Ok))
+            } else {
+                Ok(Some(FragmentStatus::Corrupt))
+            }
+        }
+        Err(StorageError::FragmentNotFound { .. }) => Ok(Some(FragmentStatus::Ok)),
+        Err(StorageError::Io(_))
+        | Err(StorageError::BlobNotFound(_))
+        | Err(StorageError::Decryption(_)) => {
+            Ok(Some(FragmentStatus::Corrupt))
+        }
+        Err(StorageError::Encryption(_)) => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::{blob_file_path, FragmentStore};
+    use crate::encryption::EncryptionKey;
+    use crate::memory::MemoryFragmentStore;
+    use std::fs;
+
+    fn fill(store: &dyn FragmentBackend, fragments: u32) {
+        for fid in 0..fragments {
+            store
+                .store_fragment("repo1", fid, 1, format!("fragment {fid}").as_bytes())
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_clean_store() {
+        let store = MemoryFragmentStore::new();
+        fill(&store, 5);
+
+        let report = scrub(&store, None, &ScrubOptions::default()).unwrap();
+        assert_eq!(report.checked, 5);
+        assert_eq!(report.bytes, 5 * 10);
+        assert!(report.corrupt.is_empty());
+        assert!(report.is_complete());
+    }
+
+    #[test]
+    fn test_detects_bit_rot() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FragmentStore::open_fs(dir.path()).unwrap();
+        fill(&store, 3);
+        let rotten = store.get_fragment("repo1", 1, 1).unwrap();
+        fs::write(blob_file_path(dir.path(), &rotten.data_hash, None), b"fragment X").unwrap();
+
+        let report = scrub(&store, None, &ScrubOptions::default()).unwrap();
+        assert_eq!(report.checked, 3);
+        assert_eq!(report.corrupt, vec![rotten.key()]);
+        assert_eq!(
+            store.get_fragment("repo1", 1, 1).unwrap().status,
+            FragmentStatus::Corrupt
+        );
+        assert_eq!(
+            store.get_fragment("repo1", 0, 1).unwrap().status,
+            FragmentStatus::Ok
+        );
+    }
+
+    #[test]
+    fn test_resumes_where_it_stopped() {
+        let store = FragmentStore::in_memory().unwrap();
+        fill(&store, 5);
+        let options = ScrubOptions {
+            batch_size: 1,
+            max_fragments: Some(2),
+            ..ScrubOptions::default()
+        };
+
+        let first = scrub(&store, None, &options).unwrap();
+        assert_eq!(first.checked, 2);
+        let cursor = first.resume_from.clone().unwrap();
+        assert_eq!(cursor.fragment_id, 1);
+
+        let second = scrub(&store, Some(&cursor), &options).unwrap();
+        let third = scrub(&store, second.resume_from.as_ref(), &options).unwrap();
+        assert_eq!(second.checked + third.checked, 3);
+        assert!(third.is_complete());
+    }
+
+    #[test]
+    fn test_repairs_from_fetched_copy() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = FragmentStore::open_fs(dir.path()).unwrap();
+        fill(&store, 2);
+        // The same blob shared by a second repository
+        store.store_fragment("fork", 0, 1, b"fragment 0").unwrap();
+        let lost = store.get_fragment("repo1", 0, 1).unwrap();
+        fs::remove_file(blob_file_path(dir.path(), &lost.data_hash, None)).unwrap();
+
+        // A bad copy is discarded
+        let report = scrub_with_repair(&store, None, &ScrubOptions::default(), |_, _| {
+            Some(b"wrong".to_vec())
+        })
+        .unwrap();
+        assert_eq!(report.corrupt.len(), 2);
+        assert!(report.repaired.is_empty());
+
+        let mut fetched = Vec::new();
+        let report = scrub_with_repair(&store, None, &ScrubOptions::default(), |key, hash| {
+            fetched.push((key.clone(), hash.to_string()));
+            Some(lost.data.clone())
+        })
+        .unwrap();
+        assert!(report.corrupt.is_empty());
+        assert_eq!(report.repaired.len(), 1);
+        assert_eq!(fetched, vec![(report.repaired[0].clone(), lost.data_hash.clone())]);
+
+        for repo in ["fork", "repo1"] {
+            let record = store.get_fragment(repo, 0, 1).unwrap();
+            assert_eq!(record.data, b"fragment 0");
+            assert_eq!(record.status, FragmentStatus::Ok);
+        }
+    }
+
+    #[test]
+    fn test_skips_blobs_under_missing_key() {
+        let dir = tempfile::tempdir().unwrap();
+        {
+            let mut store = FragmentStore::open_fs(dir.path()).unwrap();
+            store.set_encryption_key(Some(EncryptionKey::generate()));
+            fill(&store, 2);
+        }
+        // Reopened without the key the first two blobs are sealed with
+        let mut store = FragmentStore::open_fs(dir.path()).unwrap();
+        store.set_encryption_key(Some(EncryptionKey::generate()));
+        store.store_fragment("repo1", 2, 1, b"fragment 2").unwrap();
+
+        let report = scrub(&store, None, &ScrubOptions::default()).unwrap();
+        assert_eq!(report.checked, 1);
+        assert_eq!(report.skipped.len(), 2);
+        assert!(report.corrupt.is_empty());
+        assert!(report.is_complete());
+    }
+
+    #[test]
+    fn test_rate_limited() {
+        let store = MemoryFragmentStore::new();
+        store.store_fragment("repo1", 0, 1, &[7; 500]).unwrap();
+        store.store_fragment("repo1", 1, 1, &[8; 500]).unwrap();
+        let options = ScrubOptions {
+            max_bytes_per_sec: Some(5_000),
+            ..ScrubOptions::default()
+        };
+
+        let started = Instant::now();
+        let report = scrub(&store, None, &options).unwrap();
+        assert_eq!(report.bytes, 1000);
+        assert!(started.elapsed() >= Duration::from_millis(200));
+    }
+}
@@ -0,0 +1,350 @@
+use std::io::{ErrorKind, Read, Write};
+
+use gitgold_core::config::GitGoldConfig;
+use gitgold_core::error::StorageError;
+use gitgold_core::types::{ChunkingStrategy, Hash256};
+use gitgold_crypto::scheme;
+use gitgold_crypto::share::{assign_origin, Share};
+
+use crate::chunk::{check_chunking, cut_point};
+
+/// Largest share frame accepted from a share stream, so a corrupt length
+/// prefix cannot make the reader allocate without bound.
+pub const MAX_FRAME_LEN: usize = 64 * 1024 * 1024;
+
+/// Totals reported by the streaming functions.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct StreamStats {
+    /// Number of chunks processed.
+    pub chunks: u32,
+    /// Number of repository bytes read or written.
+    pub bytes: u64,
+}
+
+/// Read a repository from `reader` one chunk at a time, split each chunk with
+/// the engine selected in `config`, and hand its shares to `on_chunk`.
+///
+/// Only one chunk and its shares are held in memory at once, so arbitrarily
+/// large inputs can be processed. Chunk boundaries follow `config.chunking`
+/// and match what `chunk_with_config` followed by `split_with_config` would
+/// produce. An invalid `config.chunking` fails with `InvalidChunking` before
+/// anything is read.
+pub fn split_stream<R, F>(
+    mut reader: R,
+    config: &GitGoldConfig,
+    repo_hash: &Hash256,
+    mut on_chunk: F,
+) -> Result<StreamStats, StorageError>
+where
+    R: Read,
+    F: FnMut(u32, Vec<Share>) -> Result<(), StorageError>,
+{
+    check_chunking(config)?;
+    let mut stats = StreamStats::default();
+    let max_chunk = match config.chunking {
+        ChunkingStrategy::Fixed => config.chunk_size,
+        ChunkingStrategy::FastCdc { max_size, .. } => max_size,
+    };
+    // Holds up to one maximum-size chunk; bytes past a content-defined cut
+    // point are carried over to the next chunk.
+    let mut buf = vec![0u8; max_chunk];
+    let mut filled = 0;
+    let mut eof = false;
+
+    loop {
+        if !eof {
+            let read = read_full(&mut reader, &mut buf[filled..])?;
+            eof = filled + read < buf.len();
+            filled += read;
+        }
+        if filled == 0 {
+            return Ok(stats);
+        }
+
+        let len = match config.chunking {
+            ChunkingStrategy::Fixed => filled,
+            ChunkingStrategy::FastCdc {
+                min_size,
+                avg_size,
+                max_size,
+            } => cut_point(&buf[..filled], min_size, avg_size, max_size),
+        };
+
+        let mut shares = scheme::split_with_config(&buf[..len], config)
+            .map_err(|e| StorageError::Sharing(e.to_string()))?;
+        assign_origin(&mut shares, repo_hash, stats.chunks);
+        on_chunk(stats.chunks, shares)?;
+
+        stats.chunks += 1;
+        stats.bytes += len as u64;
+        buf.copy_within(len..filled, 0);
+        filled -= len;
+    }
+}
+
+/// Split a repository from `reader` into one share stream per writer.
+///
+/// `writers[i]` receives the framed envelopes of share id `i + 1` for every
+/// chunk, in chunk order; there must be exactly `config.n` writers.
+pub fn split_to_writers<R: Read, W: Write>(
+    reader: R,
+    config: &GitGoldConfig,
+    repo_hash: &Hash256,
+    writers: &mut [W],
+) -> Result<StreamStats, StorageError> {
+    if writers.len() != config.n {
+        return Err(StorageError::Sharing(format!(
+            "{} share writers for n={}",
+            writers.len(),
+            config.n
+        )));
+    }
+
+    let stats = split_stream(reader, config, repo_hash, |_, shares| {
+        for (writer, share) in writers.iter_mut().zip(&shares) {
+            write_frame(writer, share)?;
+        }
+        Ok(())
+    })?;
+    for writer in writers.iter_mut() {
+        writer.flush().map_err(io_error)?;
+    }
+    Ok(stats)
+}
+
+/// Reconstruct a repository into `writer` from share streams produced by
+/// `split_to_writers`.
+///
+/// Each reader supplies one share per chunk; at least k readers are needed.
+/// Chunks are reconstructed and written one at a time, so memory use is
+/// bounded by a single chunk regardless of repository size.
+pub fn reconstruct_from_readers<R: Read, W: Write>(
+    readers: &mut [R],
+    mut writer: W,
+) -> Result<StreamStats, StorageError> {
+    let mut stats = StreamStats::default();
+    let mut shares = Vec::with_capacity(readers.len());
+
+    loop {
+        shares.clear();
+        for reader in readers.iter_mut() {
+            if let Some(share) = read_frame(reader)? {
+                shares.push(share);
+            }
+        }
+        if shares.is_empty() {
+            break;
+        }
+        if shares.len() != readers.len() {
+            return Err(StorageError::Sharing(format!(
+                "share streams end at different chunks (chunk {})",
+                stats.chunks
+            )));
+        }
+        if let Some(share) = shares.iter().find(|s| s.chunk_index != stats.chunks) {
+            return Err(StorageError::Sharing(format!(
+                "share {} belongs to chunk {}, expected chunk {}",
+                share.id, share.chunk_index, stats.chunks
+            )));
+        }
+
+        let chunk = scheme::reconstruct_any(&shares)
+            .map_err(|e| StorageError::Sharing(e.to_string()))?;
+        writer.write_all(&chunk).map_err(io_error)?;
+        stats.chunks += 1;
+        stats.bytes += chunk.len() as u64;
+    }
+
+    writer.flush().map_err(io_error)?;
+    Ok(stats)
+}
+
+/// Write one share as a frame: big-endian u32 length, then the envelope.
+pub fn write_frame<W: Write>(writer: &mut W, share: &Share) -> Result<(), StorageError> {
+    let bytes = share.to_bytes();
+    writer
+        .write_all(&(bytes.len() as u32).to_be_bytes())
+        .and_then(|_| writer.write_all(&bytes))
+        .map_err(io_error)
+}
+
+/// Read one framed share, or `None` at a clean end of stream.
+pub fn read_frame<R: Read>(reader: &mut R) -> Result<Option<Share>, StorageError> {
+    let mut len_buf = [0u8; 4];
+    match read_full(reader, &mut len_buf)? {
+        0 => return Ok(None),
+        4 => {}
+        _ => return Err(StorageError::Io("truncated frame length".to_string())),
+    }
+
+    let len = u32::from_be_bytes(len_buf) as usize;
+    if len > MAX_FRAME_LEN {
+        return Err(StorageError::DataTooLarge {
+            size: len,
+            max: MAX_FRAME_LEN,
+        });
+    }
+    let mut bytes = vec![0u8; len];
+    if read_full(reader, &mut bytes)? != len {
+        return Err(StorageError::Io("truncated share frame".to_string()));
+    }
+
+    Share::from_bytes(&bytes)
+        .map(Some)
+        .map_err(|e| StorageError::Sharing(e.to_string()))
+}
+
+/// Fill `buf` from `reader`, stopping early only at end of stream. Returns
+/// the number of bytes read.
+fn read_full<R: Read>(reader: &mut R, buf: &mut [u8]) -> Result<usize, StorageError> {
+    let mut filled = 0;
+    while filled < buf.len() {
+        match reader.read(&mut buf[filled..]) {
+            Ok(0) => break,
+            Ok(n) => filled += n,
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(io_error(e)),
+        }
+    }
+    Ok(filled)
+}
+
+fn io_error(e: std::io::Error) -> StorageError {
+    StorageError::Io(e.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::chunk::chunk_data;
+    use gitgold_core::types::ShareScheme;
+
+    fn small_config() -> GitGoldConfig {
+        GitGoldConfig {
+            k: 2,
+            n: 3,
+            chunk_size: 1000,
+            ..GitGoldConfig::default()
+        }
+    }
+
+    /// Reader that returns at most 7 bytes per call.
+    struct Trickle<'a>(&'a [u8]);
+
+    impl Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let n = buf.len().min(self.0.len()).min(7);
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
+    }
+
+    #[test]
+    fn test_split_stream_matches_chunk_data() {
+        let config = small_config();
+        let data: Vec<u8> = (0..2500).map(|i| (i % 256) as u8).collect();
+        let expected = chunk_data(&data, config.chunk_size);
+
+        let mut seen = Vec::new();
+        let stats = split_stream(Trickle(&data), &config, &[1; 32], |idx, shares| {
+            assert_eq!(shares.len(), config.n);
+            assert!(shares.iter().all(|s| s.chunk_index == idx));
+            seen.push((idx, scheme::reconstruct_any(&shares[1..]).unwrap()));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(stats, StreamStats { chunks: 3, bytes: 2500 });
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn test_split_stream_content_defined() {
+        let config = GitGoldConfig {
+            chunking: ChunkingStrategy::fast_cdc(512),
+            ..small_config()
+        };
+        let data: Vec<u8> = (0..20_000u32).map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8).collect();
+        let expected = crate::chunk::chunk_with_config(&data, &config).unwrap();
+        assert!(expected.len() > 10);
+
+        let mut seen = Vec::new();
+        let stats = split_stream(Trickle(&data), &config, &[1; 32], |idx, shares| {
+            seen.push((idx, scheme::reconstruct_any(&shares[..2]).unwrap()));
+            Ok(())
+        })
+        .unwrap();
+
+        assert_eq!(stats.chunks as usize, expected.len());
+        assert_eq!(seen, expected);
+    }
+
+    #[test]
+    fn test_split_stream_invalid_chunking() {
+        let config = GitGoldConfig {
+            chunking: ChunkingStrategy::fast_cdc(2),
+            ..small_config()
+        };
+        let result = split_stream(&b"data"[..], &config, &[1; 32], |_, _| Ok(()));
+        assert!(matches!(result, Err(StorageError::InvalidChunking(_))));
+    }
+
+    #[test]
+    fn test_writers_roundtrip() {
+        let config = GitGoldConfig {
+            share_scheme: ShareScheme::Gf256,
+            ..small_config()
+        };
+        let data: Vec<u8> = (0..3000).map(|i| (i * 7 % 256) as u8).collect();
+
+        let mut streams = vec![Vec::new(); config.n];
+        split_to_writers(&data[..], &config, &[2; 32], &mut streams).unwrap();
+
+        // Any k of the n share streams
+        let mut readers: Vec<&[u8]> = vec![&streams[0], &streams[2]];
+        let mut out = Vec::new();
+        let stats = reconstruct_from_readers(&mut readers, &mut out).unwrap();
+        assert_eq!(stats, StreamStats { chunks: 3, bytes: 3000 });
+        assert_eq!(out, data);
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let config = small_config();
+        let mut streams = vec![Vec::new(); config.n];
+        let stats = split_to_writers(&[][..], &config, &[0; 32], &mut streams).unwrap();
+        assert_eq!(stats.chunks, 0);
+        assert!(streams.iter().all(|s| s.is_empty()));
+    }
+
+    #[test]
+    fn test_truncated_stream_rejected() {
+        let config = small_config();
+        let data = vec![0x11; 2500];
+        let mut streams = vec![Vec::new(); config.n];
+        split_to_writers(&data[..], &config, &[3; 32], &mut streams).unwrap();
+
+        let short = streams[1].len() - 10;
+        let mut readers: Vec<&[u8]> = vec![&streams[0], &streams[1][..short]];
+        assert!(matches!(
+            reconstruct_from_readers(&mut readers, Vec::new()),
+            Err(StorageError::Io(_))
+        ));
+    }
+
+    #[test]
+    fn test_wrong_writer_count() {
+        let mut streams = vec![Vec::new(); 2];
+        assert!(split_to_writers(&[1u8][..], &small_config(), &[0; 32], &mut streams).is_err());
+    }
+
+    #[test]
+    fn test_oversized_frame_rejected() {
+        let bytes = u32::MAX.to_be_bytes();
+        assert!(matches!(
+            read_frame(&mut &bytes[..]),
+            Err(StorageError::DataTooLarge { .. })
+        ));
+    }
+}
@@ -0,0 +1,40 @@
+-- Storage database as written by the v1 release, before schema versioning:
+-- fragment data inline in `fragments`, no schema_version table. `upstream`
+-- and `fork` hold one identical share ("shared share data").
+
+CREATE TABLE fragments (
+    repo_hash   TEXT NOT NULL,
+    fragment_id INTEGER NOT NULL,
+    share_id    INTEGER NOT NULL,
+    data        BLOB NOT NULL,
+    data_hash   TEXT NOT NULL,
+    stored_at   INTEGER NOT NULL,
+    last_challenged INTEGER,
+    PRIMARY KEY (repo_hash, fragment_id, share_id)
+);
+
+CREATE TABLE challenges (
+    challenge_id    TEXT PRIMARY KEY,
+    repo_hash       TEXT NOT NULL,
+    fragment_id     INTEGER NOT NULL,
+    success         INTEGER NOT NULL,
+    response_time   INTEGER NOT NULL,
+    challenged_at   INTEGER NOT NULL
+);
+
+CREATE INDEX idx_fragments_repo
+    ON fragments (repo_hash);
+
+CREATE INDEX idx_challenges_repo_fragment
+    ON challenges (repo_hash, fragment_id);
+
+INSERT INTO fragments VALUES
+    ('upstream', 0, 1, X'7368617265642073686172652064617461',
+     'd49098d3ea1e8f15e6a560bc7762c24d1bd4be2fcc482208835583a48922717c', 1700000000, 1700000500),
+    ('upstream', 0, 2, X'757073747265616d206f6e6c79',
+     '1dcefb971da4549f65b045c13671452df3e2b739be73ec6ab0fc0b9765e6bfbb', 1700000000, NULL),
+    ('fork', 0, 1, X'7368617265642073686172652064617461',
+     'd49098d3ea1e8f15e6a560bc7762c24d1bd4be2fcc482208835583a48922717c', 1700000000, NULL);
+
+INSERT INTO challenges VALUES
+    ('ch-001', 'upstream', 0, 1, 120, 1700000500);
@@ -2,5 +2,6 @@
 pub use gitgold_challenge as challenge;
 pub use gitgold_core as core;
 pub use gitgold_crypto as crypto;
+pub use gitgold_db as db;
 pub use gitgold_ledger as ledger;
 pub use gitgold_storage as storage;